@@ -15,6 +15,46 @@ defmodule BicExporter do
   Use `headers/0` to get the column names.
   """
 
+  # Options passed through to the NIF, with their defaults
+  @native_options [
+    strict: false,
+    recover: false,
+    password: nil,
+    repair: false,
+    archive_entry: nil,
+    max_pages: nil,
+    max_operations_per_page: nil,
+    max_stream_size: nil,
+    max_text_elements: nil,
+    timeout: nil,
+    max_input_size: nil
+  ]
+
+  # CSV export options, with their defaults
+  @csv_options [
+    delimiter: ",",
+    quote_style: :necessary,
+    line_ending: :lf,
+    bom: false,
+    header: true,
+    sanitize: false
+  ]
+
+  # XLSX export options, with their defaults
+  @xlsx_options [sanitize: false]
+
+  # Fixed-width export options, with their defaults
+  @fixed_width_options [fields: nil, padding: " ", truncation: :truncate, line_ending: :lf]
+
+  # JSON export options, with their defaults
+  @json_options [format: :json, metadata: false, edition: nil]
+
+  # SQL script options, with their defaults
+  @sql_options [table: "bic_records", rows_per_insert: 1000, create_table: false]
+
+  # BIC suggestion options, with their defaults
+  @suggest_options [limit: 5, max_distance: 2.0, same_country: false]
+
   @doc """
   Returns the CSV column headers.
 
@@ -27,18 +67,659 @@ defmodule BicExporter do
   """
   defdelegate headers(), to: BicExporter.Native
 
+  @doc """
+  Returns the country of a BIC or of an extracted record's BIC.
+
+  The country is the ISO 3166-1 alpha-2 code in the BIC's 5th and 6th
+  characters, with its English name. Besides the codes ISO assigns, `"XK"`
+  is Kosovo, as SWIFT uses it.
+
+  Returns `{:ok, %{code: code, name: name}}`, or `:error` when the code
+  isn't a known country.
+
+  ## Examples
+
+      iex> BicExporter.country("AAAARSBG")
+      {:ok, %{code: "RS", name: "Serbia"}}
+
+      iex> BicExporter.country("AAAAQQBG")
+      :error
+  """
+  def country([_creation_date, _update_date, bic | _]), do: country(bic)
+
+  def country(bic) when is_binary(bic) do
+    case BicExporter.Native.bic_country(bic) do
+      nil -> :error
+      country -> {:ok, country}
+    end
+  end
+
+  @doc """
+  Returns every country `country/1` knows, as maps with the `:code` and
+  English `:name`, sorted by code.
+  """
+  defdelegate countries(), to: BicExporter.Native
+
+  @doc """
+  Parses and validates an 8 or 11-character BIC per ISO 9362.
+
+  Parsing is strict: the code must be uppercase, without spaces. An
+  8-character BIC is that of the primary office, with the `"XXX"` branch
+  code.
+
+  Returns `{:ok, bic}`, where `bic` is a map with:
+
+    * `:bic` and `:bic8` - the 11-character BIC and its first 8 characters
+    * `:business_party_prefix`, `:country_code`, `:location_code` and
+      `:branch_code` - the parts of the BIC
+    * `:country` - the country as returned by `country/1`, or `nil` when
+      the country code isn't in ISO 3166
+    * `:primary_office` - whether the branch code is `"XXX"`
+    * `:test` - whether it's a test and training BIC, with `"0"` as the
+      location code's second character
+    * `:passive` - whether it belongs to a passive participant, with `"1"`
+      as the location code's second character
+    * `:reverse_billing` - whether it's a reverse billing BIC, with `"2"` as
+      the location code's second character
+
+  Otherwise returns `{:error, reason}`, where `reason` is the first part
+  found invalid: `:length`, `:business_party_prefix`, `:country_code`,
+  `:location_code` or `:branch_code`.
+
+  ## Examples
+
+      iex> {:ok, bic} = BicExporter.parse_bic("AAAARSBG")
+      iex> {bic.bic, bic.branch_code, bic.primary_office}
+      {"AAAARSBGXXX", "XXX", true}
+
+      iex> BicExporter.parse_bic("AAAA1SBG")
+      {:error, :country_code}
+  """
+  defdelegate parse_bic(code), to: BicExporter.Native
+
+  @doc """
+  Returns whether `code` is a structurally valid BIC, see `parse_bic/1`.
+  """
+  def valid_bic?(code) when is_binary(code), do: match?({:ok, _}, parse_bic(code))
+
+  @doc """
+  Indexes extracted records by BIC, for `lookup_bic/2`.
+
+  The directory is an opaque reference, cheap to share between processes.
+  Records whose BIC or branch code isn't valid can't be looked up, and when
+  a BIC appears more than once its first record is found.
+
+  ## Example
+
+      {:ok, records} = BicExporter.extract_table_from_path("/path/to/ISOBIC.pdf")
+      directory = BicExporter.load_directory(records)
+  """
+  defdelegate load_directory(records), to: BicExporter.Native
+
+  @doc """
+  Looks up a BIC as customers type it in a directory from `load_directory/1`.
+
+  The input is normalized first: whitespace anywhere is removed and letters
+  are uppercased. An 8-character BIC is that of the head office, with the
+  `"XXX"` branch code.
+
+  Returns `{:ok, lookup}`, where `lookup` is a map with:
+
+    * `:bic` - the normalized 11-character BIC
+    * `:exists` - whether the BIC is in the directory
+    * `:primary_office` - whether the BIC is the head office's rather than a
+      branch's
+    * `:record` - the BIC's record, or `nil` when it isn't in the directory
+
+  Otherwise returns `{:error, reason}` as `parse_bic/1` does, when the input
+  isn't a well-formed BIC.
+
+  ## Example
+
+      {:ok, %{exists: true, primary_office: true, record: record}} =
+        BicExporter.lookup_bic(directory, " aaaa rs bg ")
+  """
+  defdelegate lookup_bic(directory, input), to: BicExporter.Native
+
+  @doc """
+  Suggests the BICs in a directory from `load_directory/1` closest to
+  `input`, typically one `lookup_bic/2` didn't find.
+
+  The input is normalized as for `lookup_bic/2`, but needn't be a valid BIC.
+  Closeness is an edit distance where inserting, deleting or replacing a
+  character costs 1, but the usual typing mistakes cost less: swapping two
+  neighbouring characters costs 0.5, and mixing up `O` with `0` or `I` with
+  `1` costs 0.25. A head office matches with or without its `"XXX"` branch
+  code.
+
+  Returns a list of maps with the `:bic`, its `:distance` from the input and
+  its `:record`, closest first, then in BIC order. A BIC in the directory is
+  its own closest suggestion, at distance 0.
+
+  ## Options
+
+    * `:limit` - maximum number of suggestions. Defaults to `5`.
+    * `:max_distance` - only suggest BICs at most this far from the input.
+      Defaults to `2.0`.
+    * `:same_country` - when `true`, only suggests BICs with the input's
+      country code, its 5th and 6th characters. Defaults to `false`.
+
+  ## Example
+
+      [%{bic: "AAAARSBGXXX", distance: 0.5} | _] =
+        BicExporter.suggest_bics(directory, "AAAARSGB")
+  """
+  def suggest_bics(directory, input, opts \\ []) do
+    opts =
+      opts
+      |> Keyword.validate!(@suggest_options)
+      |> Map.new()
+      |> Map.update!(:max_distance, &(&1 / 1))
+
+    BicExporter.Native.suggest_bics(directory, input, opts)
+  end
+
+  @doc """
+  Extracts BIC records from a PDF file.
+
+  Takes the same options as `extract_table_from_binary/2`, and returns
+  `{:error, reason}` with a `File.read/1` reason if the file can't be read.
+
+  ## Example
+
+      {:ok, records} = BicExporter.extract_table_from_path("/path/to/ISOBIC.pdf.gz")
+  """
+  def extract_table_from_path(path, opts \\ []) do
+    with {:ok, data} <- File.read(path) do
+      extract_table_from_binary(data, opts)
+    end
+  end
+
   @doc """
   Extracts BIC records from PDF binary data.
 
   This is useful when the PDF is already loaded in memory (e.g., downloaded
   from a URL or read from a database).
 
+  The data may also be gzip-compressed, or a zip archive containing the PDF.
+  The archive's only PDF is extracted, unless another entry is named with
+  `:archive_entry`.
+
   Returns `{:ok, records}` on success or `{:error, reason}` on failure.
 
+  ## Options
+
+    * `:report` - when `true`, returns `{:ok, records, report}` where `report`
+      is a map describing what extraction dropped or found suspicious:
+
+      * `:orphan_rows` - rows with content but no open record to merge into
+      * `:out_of_grid` - text left of the first column, ignored by extraction
+      * `:missing_fields` - records with an empty BIC, legal name or
+        institution type
+      * `:invalid_dates` - records whose dates aren't valid `YYYY-MM-DD` dates
+      * `:invalid_bics` - records whose BIC or branch code isn't structurally
+        valid, with the `:error` `parse_bic/1` would return
+      * `:unknown_countries` - records whose BIC has a country code that isn't
+        in ISO 3166, see `country/1`
+      * `:column_drift` - pages whose table lines don't match the detected
+        columns
+      * `:sort_violations` - records out of the directory's BIC order, either
+        sorting before (`:inversion`) or repeating (`:duplicate`) the previous
+        record, which usually means a row was mis-segmented, or following it
+        after pages that produced no records (`:gap`). Gaps are a heuristic
+        that only notices whole pages without records
+      * `:empty_pages` - pages after the cover that produced no records
+      * `:records_per_page` - number of records extracted from each page
+      * `:repaired` - whether the PDF's cross-reference table had to be
+        rebuilt, when `:repair` is set
+      * `:page_errors` - pages skipped because they failed to load or parse,
+        when `:recover` is set
+
+      Page numbers are 1-based and records are referenced by their 0-based
+      index in `records`. Defaults to `false`.
+
+    * `:recover` - when `true`, a page that fails to load or parse is skipped
+      instead of failing the whole document, and `{:ok, records, errors}` is
+      returned where each error is a map with the `:page` and its `:cause`.
+      When `:report` is also set, the errors are in the report's
+      `:page_errors` instead. Defaults to `false`.
+
+    * `:strict` - when `true`, returns
+      `{:error, {:quality_issues, issues}}` instead of records if the report
+      finds anything suspicious, such as orphan rows, text outside the table
+      grid, invalid BIC structure, records out of BIC order or pages whose
+      table lines don't match the detected columns. Each issue is a map with
+      `:kind`, `:page`, `:record` (`nil` for page-level issues) and
+      `:message`. Defaults to `false`.
+
+    * `:password` - the user or owner password of an encrypted PDF. RC4 and
+      AES-128 encryption are supported. PDFs encrypted only to restrict
+      permissions open without one. A PDF that can't be opened returns
+      `{:error, :password_required}` when no password was given, or
+      `{:error, :wrong_password}`. Defaults to `nil`.
+
+    * `:archive_entry` - name of the PDF to extract from a zip archive holding
+      more than one. Defaults to `nil`.
+
+    * `:repair` - when `true`, a PDF that fails to load, e.g. because the
+      download was truncated or its cross-reference table is broken, is
+      repaired by scanning the file for objects, and the report's
+      `:repaired` flag is set. Combine with `:recover` to get the records
+      from the pages that survived a truncation. Defaults to `false`.
+
+  ### Limits
+
+  When extracting untrusted PDFs, these options stop extraction with
+  `{:error, {:limit_exceeded, limit, max}}` instead of exhausting memory or
+  keeping a scheduler busy. `limit` is the name of the option that was
+  exceeded and `max` its configured value. Limits apply even when
+  recovering. All default to `nil`, meaning unlimited.
+
+    * `:max_pages` - maximum number of pages, including the cover
+    * `:max_operations_per_page` - maximum number of content stream
+      operations on a single page
+    * `:max_stream_size` - maximum decoded size in bytes of any stream.
+      Streams that can't be measured, such as one with an unsupported
+      filter, count as exceeding it
+    * `:max_text_elements` - maximum number of text fragments in the document
+    * `:timeout` - maximum extraction time in milliseconds, checked between
+      pages and while a page's content is parsed
+    * `:max_input_size` - maximum size in bytes of the PDF after decompressing
+      gzip or zip input
+
   ## Example
 
       pdf_data = File.read!("/path/to/ISOBIC.pdf")
       {:ok, records} = BicExporter.extract_table_from_binary(pdf_data)
+
+      {:ok, records, report} = BicExporter.extract_table_from_binary(pdf_data, report: true)
+
+      {:ok, records, errors} = BicExporter.extract_table_from_binary(pdf_data, recover: true)
+
+      {:ok, records} = BicExporter.extract_table_from_binary(encrypted_data, password: "secret")
+
+      case BicExporter.extract_table_from_binary(pdf_data, strict: true) do
+        {:ok, records} -> import!(records)
+        {:error, {:quality_issues, issues}} -> reject!(issues)
+        {:error, reason} -> reject!(reason)
+      end
+
+      BicExporter.extract_table_from_binary(untrusted_data,
+        max_pages: 5_000,
+        max_stream_size: 10_000_000,
+        timeout: 60_000
+      )
+  """
+  def extract_table_from_binary(data, opts \\ []) do
+    opts = Keyword.validate!(opts, [report: false] ++ @native_options)
+    native_opts = opts |> Keyword.delete(:report) |> Map.new()
+
+    with {:ok, {records, report}} <-
+           BicExporter.Native.extract_table_with_options_from_binary(data, native_opts) do
+      cond do
+        opts[:report] -> {:ok, records, report}
+        opts[:recover] -> {:ok, records, report.page_errors}
+        true -> {:ok, records}
+      end
+    end
+  end
+
+  @doc """
+  Starts extracting BIC records from PDF binary data in the background.
+
+  Extraction runs on its own thread and reports back with messages tagged
+  with the returned `ref`:
+
+    * `{:bic_exporter, ref, {:progress, page, total}}` - after each page,
+      where `page` is 1-based and `total` includes the cover
+    * `{:bic_exporter, ref, {:ok, records, report}}` - when done, with the
+      same report as `extract_table_from_binary/2` returns with `report: true`
+    * `{:bic_exporter, ref, {:error, reason}}` - on failure, with the same
+      reasons as `extract_table_from_binary/2`, `:cancelled` after
+      `cancel_extraction/1`, or `:panic` if extraction crashed
+
+  Returns `{:ok, ref, handle}`, where `handle` can be passed to
+  `cancel_extraction/1`. Extraction also stops if the receiving process exits.
+
+  ## Options
+
+    * `:pid` - the process receiving the messages. Defaults to `self()`.
+
+  Accepts the same `:strict`, `:recover`, `:password`, `:repair` and limit options as
+  `extract_table_from_binary/2`. The report is always included in the result.
+
+  ## Example
+
+      {:ok, ref, handle} = BicExporter.extract_table_async(pdf_data)
+
+      def handle_info({:bic_exporter, ref, {:progress, page, total}}, socket) do
+        {:noreply, assign(socket, progress: page / total)}
+      end
+
+      def handle_info({:bic_exporter, ref, {:ok, records, _report}}, socket) do
+        {:noreply, assign(socket, records: records)}
+      end
+  """
+  def extract_table_async(data, opts \\ []) do
+    opts = Keyword.validate!(opts, [pid: self()] ++ @native_options)
+    {pid, native_opts} = Keyword.pop!(opts, :pid)
+
+    {ref, handle} = BicExporter.Native.start_extraction(data, Map.new(native_opts), pid)
+    {:ok, ref, handle}
+  end
+
+  @doc """
+  Cancels an extraction started with `extract_table_async/2`.
+
+  Extraction stops after the page it is currently processing, and
+  `{:bic_exporter, ref, {:error, :cancelled}}` is sent instead of the result.
+  Cancelling an extraction that already finished has no effect.
+
+  Always returns `:ok`.
+  """
+  defdelegate cancel_extraction(handle), to: BicExporter.Native
+
+  @doc """
+  Converts extracted records to CSV.
+
+  The first row holds the column headers from `headers/0`. With the default
+  options the CSV is comma separated, quotes only the fields that need it and
+  ends rows with `"\\n"`.
+
+  Returns `{:ok, csv}` or `{:error, reason}` for invalid options.
+
+  ## Options
+
+    * `:delimiter` - a single ASCII character separating fields. Use `";"`
+      for Excel in locales with a decimal comma. Defaults to `","`.
+    * `:quote_style` - `:necessary` to quote only fields containing the
+      delimiter, a quote or a line break, or `:always`. Defaults to `:necessary`.
+    * `:line_ending` - `:lf` or `:crlf`. Defaults to `:lf`.
+    * `:bom` - when `true`, starts with a UTF-8 byte order mark so Excel
+      detects the encoding. Defaults to `false`.
+    * `:header` - when `false`, leaves out the header row. Defaults to `true`.
+    * `:sanitize` - when `true`, fields starting with `=`, `+`, `-`, `@`, a
+      tab or a carriage return are prefixed with `'` so spreadsheets don't
+      evaluate them as formulas, and `{:ok, csv, sanitized}` is returned.
+      Each entry of `sanitized` is a map with the 0-based `:record` and
+      `:column` indexes and the original `:value`. Defaults to `false`.
+
+  ## Example
+
+      {:ok, records} = BicExporter.extract_table_from_binary(pdf_data)
+      {:ok, csv} = BicExporter.to_csv(records, delimiter: ";", bom: true)
+
+      {:ok, csv, sanitized} = BicExporter.to_csv(records, sanitize: true)
+  """
+  def to_csv(records, opts \\ []) do
+    opts = Keyword.validate!(opts, @csv_options)
+
+    with {:ok, {csv, sanitized}} <- BicExporter.Native.records_to_csv(records, Map.new(opts)) do
+      if opts[:sanitize], do: {:ok, csv, sanitized}, else: {:ok, csv}
+    end
+  end
+
+  @doc """
+  Writes extracted records to a CSV file at `path`.
+
+  Takes the same options as `to_csv/2`. Rows are written to the file as
+  they are formatted, without building the whole CSV in memory.
+
+  Returns `:ok`, `{:ok, sanitized}` when `:sanitize` is set, or
+  `{:error, reason}`.
+  """
+  def write_csv(records, path, opts \\ []) do
+    opts = Keyword.validate!(opts, @csv_options)
+
+    with {:ok, sanitized} <- BicExporter.Native.write_csv_file(records, path, Map.new(opts)) do
+      if opts[:sanitize], do: {:ok, sanitized}, else: :ok
+    end
+  end
+
+  @doc """
+  Converts extracted records to an Excel workbook.
+
+  Each sheet starts with the column headers from `headers/0` as a frozen
+  header row with auto-filters. Dates are date cells, so they sort and
+  filter as dates, and past Excel's limit of 1,048,576 rows the records
+  continue on another sheet.
+
+  Returns `{:ok, xlsx}` or `{:error, reason}`.
+
+  ## Options
+
+    * `:sanitize` - when `true`, text cells starting with `=`, `+`, `-`, `@`,
+      a tab or a carriage return are marked as text that Excel won't
+      evaluate as a formula, even once edited, and `{:ok, xlsx, sanitized}`
+      is returned as for `to_csv/2`. Unlike in CSV, the values are left
+      unchanged. Defaults to `false`.
+
+  ## Example
+
+      {:ok, records} = BicExporter.extract_table_from_binary(pdf_data)
+      {:ok, xlsx, sanitized} = BicExporter.to_xlsx(records, sanitize: true)
+  """
+  def to_xlsx(records, opts \\ []) do
+    opts = Keyword.validate!(opts, @xlsx_options)
+
+    with {:ok, {xlsx, sanitized}} <- BicExporter.Native.records_to_xlsx(records, Map.new(opts)) do
+      if opts[:sanitize], do: {:ok, xlsx, sanitized}, else: {:ok, xlsx}
+    end
+  end
+
+  @doc """
+  Writes extracted records to an Excel workbook at `path`.
+
+  Takes the same options as `to_xlsx/2`, compressing sheets into the file
+  as they are written.
+
+  Returns `:ok`, `{:ok, sanitized}` when `:sanitize` is set, or
+  `{:error, reason}`.
+  """
+  def write_xlsx(records, path, opts \\ []) do
+    opts = Keyword.validate!(opts, @xlsx_options)
+
+    with {:ok, sanitized} <- BicExporter.Native.write_xlsx_file(records, path, Map.new(opts)) do
+      if opts[:sanitize], do: {:ok, sanitized}, else: :ok
+    end
+  end
+
+  @doc """
+  Converts extracted records to fixed-width lines, for legacy systems that
+  import the older BIC directory text files.
+
+  Each record is a line of fields padded to their width, without separators
+  or a header. Widths count characters, and control characters in values
+  are written as spaces.
+
+  Returns `{:ok, data, truncated}`, where each entry of `truncated` is a map
+  with the 0-based `:record` and `:column` indexes and the full `:value` of
+  a value cut to fit its field, or `{:error, reason}`.
+
+  ## Options
+
+    * `:fields` - the fields of each line in order, as `{field, width}` or
+      `{field, width, align}` tuples. `field` is a field name from the JSON
+      export such as `:bic`, and `align` is `:left` (the default) or
+      `:right`. Defaults to every field, wide enough for the values of
+      recent editions.
+    * `:padding` - the character filling fields up to their width.
+      Defaults to `" "`.
+    * `:truncation` - `:truncate` to cut values longer than their field, or
+      `:error` to return an error instead. Defaults to `:truncate`.
+    * `:line_ending` - `:lf` or `:crlf`. Defaults to `:lf`.
+
+  ## Example
+
+      {:ok, records} = BicExporter.extract_table_from_binary(pdf_data)
+
+      {:ok, data, truncated} =
+        BicExporter.to_fixed_width(records,
+          fields: [bic: 8, branch_code: 3, full_legal_name: 105, institution_type: 4],
+          line_ending: :crlf
+        )
+  """
+  def to_fixed_width(records, opts \\ []) do
+    with {:ok, {data, truncated}} <-
+           BicExporter.Native.records_to_fixed_width(records, fixed_width_options(opts)) do
+      {:ok, data, truncated}
+    end
+  end
+
+  @doc """
+  Writes extracted records as fixed-width lines to a file at `path`.
+
+  Takes the same options as `to_fixed_width/2`.
+
+  Returns `{:ok, truncated}` or `{:error, reason}`.
+  """
+  def write_fixed_width(records, path, opts \\ []) do
+    BicExporter.Native.write_fixed_width_file(records, path, fixed_width_options(opts))
+  end
+
+  defp fixed_width_options(opts) do
+    opts = Keyword.validate!(opts, @fixed_width_options)
+    fields = opts[:fields] && Enum.map(opts[:fields], &fixed_width_field/1)
+    opts |> Map.new() |> Map.put(:fields, fields)
+  end
+
+  defp fixed_width_field({field, width}), do: fixed_width_field({field, width, :left})
+
+  defp fixed_width_field({field, width, align}),
+    do: %{field: to_string(field), width: width, align: align}
+
+  @doc """
+  Converts extracted records to JSON or JSON Lines.
+
+  Each record is an object keyed by stable field names such as `"bic"` and
+  `"full_legal_name"`, plus the `"country_code"` and `"country_name"` of the
+  BIC's country, following the JSON Schema returned by `json_schema/0`.
+
+  Returns `{:ok, json}` or `{:error, reason}` for invalid options.
+
+  ## Options
+
+    * `:format` - `:json` for a single document,
+      `{"records": [...]}`, or `:ndjson` for JSON Lines, one record per
+      line. Defaults to `:json`.
+    * `:metadata` - when `true`, adds a header with the schema version, the
+      `:edition` and the time of the export. In a JSON document it is the
+      `"metadata"` key; in JSON Lines it is the first line, as
+      `{"metadata": {...}}`. Defaults to `false`.
+    * `:edition` - the directory's publication date, as a `Date`, written
+      in the metadata header. Defaults to `nil`.
+
+  ## Example
+
+      {:ok, records} = BicExporter.extract_table_from_binary(pdf_data)
+
+      {:ok, ndjson} =
+        BicExporter.to_json(records,
+          format: :ndjson,
+          metadata: true,
+          edition: ~D[2025-12-04]
+        )
+  """
+  def to_json(records, opts \\ []) do
+    BicExporter.Native.records_to_json(records, json_options(opts))
+  end
+
+  @doc """
+  Writes extracted records to a JSON or JSON Lines file at `path`.
+
+  Takes the same options as `to_json/2`, writing records to the file as
+  they are formatted.
+
+  Returns `:ok` or `{:error, reason}`.
+  """
+  def write_json(records, path, opts \\ []) do
+    with {:ok, _} <- BicExporter.Native.write_json_file(records, path, json_options(opts)) do
+      :ok
+    end
+  end
+
+  @doc """
+  Returns the JSON Schema, as a string, describing the output of `to_json/2`.
+
+  Its `$defs` hold the `record` and `metadata_line` schemas that each line
+  of JSON Lines output follows.
+  """
+  defdelegate json_schema(), to: BicExporter.Native
+
+  defp json_options(opts) do
+    opts = Keyword.validate!(opts, @json_options)
+    Map.new(opts) |> Map.update!(:edition, &(&1 && Date.to_iso8601(&1)))
+  end
+
+  @doc """
+  Converts extracted records to PostgreSQL `COPY` data in text format.
+
+  The data loads into the columns listed by `pg_copy_statement/1`, in one
+  round trip instead of an insert per record. Empty fields and dates that
+  don't parse are loaded as `NULL`.
+
+  Returns `{:ok, data}` or `{:error, reason}`.
+
+  ## Example
+
+      {:ok, records} = BicExporter.extract_table_from_binary(pdf_data)
+      {:ok, data} = BicExporter.to_pg_copy(records)
+
+      Repo.transaction(fn ->
+        stream = Ecto.Adapters.SQL.stream(Repo, BicExporter.pg_copy_statement())
+        Enum.into([data], stream)
+      end)
+  """
+  defdelegate to_pg_copy(records), to: BicExporter.Native, as: :records_to_pg_copy
+
+  @doc """
+  Returns the `COPY ... FROM STDIN` statement loading `to_pg_copy/1` data
+  into `table`, which may be schema-qualified as `"schema.table"`.
+
+  The table needs the columns of `to_sql_inserts/2` with
+  `create_table: true`: `text` columns named as in `to_json/2`, except for
+  the two dates, which are `date`.
+  """
+  def pg_copy_statement(table \\ "bic_records") do
+    BicExporter.Native.pg_copy_statement(table)
+  end
+
+  @doc """
+  Converts extracted records to a script of multi-row `INSERT` statements.
+
+  Returns `{:ok, sql}` or `{:error, reason}` for invalid options.
+
+  ## Options
+
+    * `:table` - the table to insert into, which may be schema-qualified.
+      Defaults to `"bic_records"`.
+    * `:rows_per_insert` - the number of rows in each statement. Defaults
+      to `1000`.
+    * `:create_table` - when `true`, starts with a
+      `CREATE TABLE IF NOT EXISTS` statement. Defaults to `false`.
+  """
+  def to_sql_inserts(records, opts \\ []) do
+    opts = Keyword.validate!(opts, @sql_options)
+    BicExporter.Native.records_to_sql_inserts(records, Map.new(opts))
+  end
+
+  @doc """
+  Renders a page of the PDF as a standalone SVG document.
+
+  The SVG shows every extracted text fragment at its position, the detected
+  column boundaries and a color-coded band for each row showing how it was
+  classified (header, data, continuation or orphan). Text left of the first
+  column boundary, which extraction ignores, is drawn in red.
+
+  `page_number` is 1-based, as shown by PDF viewers (the cover is page 1).
+
+  Returns `{:ok, svg}` on success or `{:error, reason}` on failure.
+
+  ## Example
+
+      pdf_data = File.read!("/path/to/ISOBIC.pdf")
+      {:ok, svg} = BicExporter.render_page_svg(pdf_data, 2)
+      File.write!("page-2.svg", svg)
   """
-  defdelegate extract_table_from_binary(data), to: BicExporter.Native
+  defdelegate render_page_svg(data, page_number), to: BicExporter.Native
 end
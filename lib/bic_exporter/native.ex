@@ -31,11 +31,35 @@ defmodule BicExporter.Native do
   # NIF stubs - these are replaced when the NIF is loaded
 
   def headers, do: :erlang.nif_error(:nif_not_loaded)
+  def bic_country(_bic), do: :erlang.nif_error(:nif_not_loaded)
+  def countries, do: :erlang.nif_error(:nif_not_loaded)
+  def parse_bic(_code), do: :erlang.nif_error(:nif_not_loaded)
+  def load_directory(_records), do: :erlang.nif_error(:nif_not_loaded)
+  def lookup_bic(_directory, _input), do: :erlang.nif_error(:nif_not_loaded)
+  def suggest_bics(_directory, _input, _opts), do: :erlang.nif_error(:nif_not_loaded)
   def extract_table_from_binary(_data), do: :erlang.nif_error(:nif_not_loaded)
+  def extract_table_with_options_from_binary(_data, _opts),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def start_extraction(_data, _opts, _pid), do: :erlang.nif_error(:nif_not_loaded)
+  def cancel_extraction(_handle), do: :erlang.nif_error(:nif_not_loaded)
+  def render_page_svg(_data, _page_number), do: :erlang.nif_error(:nif_not_loaded)
+  def records_to_csv(_records, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def write_csv_file(_records, _path, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def records_to_xlsx(_records, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def write_xlsx_file(_records, _path, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def records_to_fixed_width(_records, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def write_fixed_width_file(_records, _path, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def records_to_json(_records, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def write_json_file(_records, _path, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def json_schema, do: :erlang.nif_error(:nif_not_loaded)
+  def records_to_pg_copy(_records), do: :erlang.nif_error(:nif_not_loaded)
+  def pg_copy_statement(_table), do: :erlang.nif_error(:nif_not_loaded)
+  def records_to_sql_inserts(_records, _opts), do: :erlang.nif_error(:nif_not_loaded)
 
   # Test-only NIFs to verify Rustler catches panics (only available with panic_test feature)
   if Mix.env() == :test do
     def deliberate_panic, do: :erlang.nif_error(:nif_not_loaded)
     def deliberate_unwrap_panic, do: :erlang.nif_error(:nif_not_loaded)
+    def deliberate_thread_panic(_pid), do: :erlang.nif_error(:nif_not_loaded)
   end
 end
@@ -52,6 +52,11 @@ defmodule BicExporter.MixProject do
         "native/bic_exporter/.cargo",
         "native/bic_exporter/src",
         "native/bic_exporter/Cargo.toml",
+        "native/bic_exporter/core/src",
+        "native/bic_exporter/core/schema",
+        "native/bic_exporter/core/Cargo.toml",
+        "native/bic_exporter/cli/src",
+        "native/bic_exporter/cli/Cargo.toml",
         "native/bic_exporter/Cargo.lock",
         "native/bic_exporter/Cross.toml",
         "checksum-*.exs",
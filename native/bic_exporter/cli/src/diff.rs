@@ -0,0 +1,182 @@
+//! Comparing the records of two directory editions.
+//!
+//! Records are matched by their BIC and branch code, which together identify
+//! an institution's office across editions. Rows are compared field by field,
+//! so a changed address shows up as a change rather than a removal and an
+//! addition.
+
+use anyhow::Result;
+use bic_exporter_core::{column, HEADERS};
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// A record that was added, removed or changed between two editions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    Added(Vec<String>),
+    Removed(Vec<String>),
+    Changed {
+        key: String,
+        /// Column index with the old and new values of each changed field.
+        fields: Vec<(usize, String, String)>,
+    },
+}
+
+/// Compare two editions' records, in BIC and branch code order.
+///
+/// If an edition holds the same BIC and branch code more than once, only its
+/// last record is compared.
+pub fn diff(old: &[Vec<String>], new: &[Vec<String>]) -> Vec<Difference> {
+    let old = by_key(old);
+    let mut new = by_key(new);
+    let mut differences = Vec::new();
+
+    for (key, old_record) in old {
+        match new.remove(&key) {
+            None => differences.push((key, Difference::Removed(old_record.clone()))),
+            Some(new_record) => {
+                let fields: Vec<_> = old_record
+                    .iter()
+                    .zip(new_record)
+                    .enumerate()
+                    .filter(|(_, (old_value, new_value))| old_value != new_value)
+                    .map(|(column, (old_value, new_value))| {
+                        (column, old_value.clone(), new_value.clone())
+                    })
+                    .collect();
+                if !fields.is_empty() {
+                    differences.push((key.clone(), Difference::Changed { key, fields }));
+                }
+            }
+        }
+    }
+    differences.extend(
+        new.into_iter()
+            .map(|(key, record)| (key, Difference::Added(record.clone()))),
+    );
+
+    differences.sort_by(|(a, _), (b, _)| a.cmp(b));
+    differences
+        .into_iter()
+        .map(|(_, difference)| difference)
+        .collect()
+}
+
+fn by_key(records: &[Vec<String>]) -> BTreeMap<String, &Vec<String>> {
+    records.iter().map(|record| (key(record), record)).collect()
+}
+
+/// The 11-character BIC of the record's office, e.g. `AAAARSBGXXX`.
+fn key(record: &[String]) -> String {
+    format!("{}{}", record[column::BIC], record[column::BRCH_CODE])
+}
+
+/// Write differences as `+` (added), `-` (removed) and `~` (changed) lines,
+/// followed by a summary.
+pub fn write_diff(differences: &[Difference], mut writer: impl Write) -> Result<()> {
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+
+    for difference in differences {
+        match difference {
+            Difference::Added(record) => {
+                added += 1;
+                writeln!(
+                    writer,
+                    "+ {} {}",
+                    key(record),
+                    record[column::FULL_LEGAL_NAME]
+                )?;
+            }
+            Difference::Removed(record) => {
+                removed += 1;
+                writeln!(
+                    writer,
+                    "- {} {}",
+                    key(record),
+                    record[column::FULL_LEGAL_NAME]
+                )?;
+            }
+            Difference::Changed { key, fields } => {
+                changed += 1;
+                writeln!(writer, "~ {}", key)?;
+                for (column, old_value, new_value) in fields {
+                    writeln!(
+                        writer,
+                        "    {}: {:?} -> {:?}",
+                        HEADERS[*column], old_value, new_value
+                    )?;
+                }
+            }
+        }
+    }
+
+    writeln!(
+        writer,
+        "{} added, {} removed, {} changed",
+        added, removed, changed
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(bic: &str, name: &str) -> Vec<String> {
+        let mut record = vec![String::new(); HEADERS.len()];
+        record[column::BIC] = bic.to_string();
+        record[column::BRCH_CODE] = "XXX".to_string();
+        record[column::FULL_LEGAL_NAME] = name.to_string();
+        record
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = [
+            record("AAAARSBG", "YETTEL BANK AD"),
+            record("AAACKWKW", "AL MUZAINI EXCHANGE CO. KSCC"),
+            record("AAADFRP1", "ABN AMRO INVESTMENT SOLUTIONS S.A."),
+        ];
+        let new = [
+            record("AAADFRP1", "ABN AMRO INVESTMENT SOLUTIONS"),
+            record("AAAARSBG", "YETTEL BANK AD"),
+            record("AAAJBG21", "ARCUS ASSET MANAGEMENT JSC"),
+        ];
+
+        let differences = diff(&old, &new);
+
+        assert_eq!(
+            differences,
+            vec![
+                Difference::Removed(old[1].clone()),
+                Difference::Changed {
+                    key: "AAADFRP1XXX".to_string(),
+                    fields: vec![(
+                        column::FULL_LEGAL_NAME,
+                        "ABN AMRO INVESTMENT SOLUTIONS S.A.".to_string(),
+                        "ABN AMRO INVESTMENT SOLUTIONS".to_string()
+                    )],
+                },
+                Difference::Added(new[2].clone()),
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_write_diff() {
+        let old = [record("AAACKWKW", "OLD"), record("AAADFRP1", "OLD NAME")];
+        let new = [record("AAADFRP1", "NEW NAME"), record("AAAJBG21", "NEW")];
+        let mut output = Vec::new();
+
+        write_diff(&diff(&old, &new), &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "- AAACKWKWXXX OLD\n\
+             ~ AAADFRP1XXX\n    Full legal name: \"OLD NAME\" -> \"NEW NAME\"\n\
+             + AAAJBG21XXX NEW\n\
+             1 added, 1 removed, 1 changed\n"
+        );
+    }
+}
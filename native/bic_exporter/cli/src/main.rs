@@ -0,0 +1,1017 @@
+//! # bic-exporter
+//!
+//! Command-line tool converting the ISO 9362 BIC directory PDF, for use
+//! without an Elixir runtime. Every subcommand reads a PDF path, or `-` for
+//! standard input, which may be gzip-compressed or a zip archive as for the
+//! library.
+//!
+//! Exit codes follow `diff(1)`: 0 when everything is fine, 1 when `validate`
+//! finds quality issues, `extract --strict` rejects the PDF, `diff` finds
+//! differences or `lookup` doesn't find a BIC, and 2 on any error.
+
+mod diff;
+
+use anyhow::{Context, Result};
+use bic_exporter_core::debug::{page_layout_with_options_from_bytes, render_svg, PageLayout};
+use bic_exporter_core::export::arrow::IpcWriter;
+use bic_exporter_core::export::csv::{write_csv, CsvOptions, LineEnding, QuoteStyle};
+use bic_exporter_core::export::fixed_width::{
+    truncated_fields, write_fixed_width, Align, FieldLayout, FixedWidthLayout, Truncation,
+};
+use bic_exporter_core::export::json::{write_json, write_ndjson, JsonHeader, SCHEMA};
+use bic_exporter_core::export::parquet::ParquetWriter;
+use bic_exporter_core::export::postgres::{write_copy, write_inserts, SqlOptions};
+use bic_exporter_core::export::sanitize::formula_fields;
+use bic_exporter_core::export::sqlite::write_sqlite;
+use bic_exporter_core::export::xlsx::{write_xlsx, XlsxOptions};
+use bic_exporter_core::export::FIELDS;
+use bic_exporter_core::{
+    column, extract_table_with_options_from_bytes, metadata_from_bytes, stream_table_from_bytes,
+    Date, Directory, ExtractOptions, ExtractionReport, Limits, Lookup, Metadata, QualityError,
+    SuggestOptions, HEADERS,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+#[command(name = "bic-exporter", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Extract the directory's records
+    Extract {
+        #[command(flatten)]
+        input: InputArgs,
+        #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+        /// File to write to instead of standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Fail instead of writing records when the quality checks find anything
+        #[arg(long)]
+        strict: bool,
+        /// Skip pages that fail to load or parse instead of failing
+        #[arg(long)]
+        recover: bool,
+        /// Start JSON output with the schema version, edition and time of extraction
+        #[arg(long)]
+        metadata: bool,
+        #[command(flatten)]
+        csv: CsvArgs,
+        #[command(flatten)]
+        sql: SqlArgs,
+        #[command(flatten)]
+        fixed_width: FixedWidthArgs,
+    },
+    /// Print the directory's edition, page count and PDF metadata
+    Metadata {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Dump a page's text, column boundaries and row classification
+    DebugPage {
+        #[command(flatten)]
+        input: InputArgs,
+        /// 1-based page number, as shown by PDF viewers
+        page: usize,
+        #[arg(short, long, value_enum, default_value_t = LayoutFormat::Text)]
+        format: LayoutFormat,
+        /// File to write to instead of standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run the quality checks, exiting with 1 if they find anything
+    Validate {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Report pages that fail to load or parse instead of failing
+        #[arg(long)]
+        recover: bool,
+    },
+    /// Compare the records of two directories, exiting with 1 if they differ
+    Diff {
+        /// The older directory, or `-` for standard input
+        old: PathBuf,
+        /// The newer directory, or `-` for standard input
+        new: PathBuf,
+        #[command(flatten)]
+        pdf: PdfArgs,
+    },
+    /// Look up BICs as typed, exiting with 1 if any isn't in the directory
+    Lookup {
+        #[command(flatten)]
+        input: InputArgs,
+        /// BICs to look up, 8 or 11 characters in any case, quoted if they
+        /// have spaces
+        #[arg(required = true)]
+        bics: Vec<String>,
+        /// Only suggest BICs from the same country for those not found
+        #[arg(long)]
+        same_country: bool,
+    },
+    /// Print the JSON Schema of the JSON and JSON Lines output
+    Schema,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Csv,
+    Json,
+    /// JSON Lines, one record per line
+    Ndjson,
+    /// Parquet, written while extracting
+    Parquet,
+    /// Arrow IPC file, written while extracting
+    Arrow,
+    /// SQLite database with indexes and full-text search, needs --output
+    Sqlite,
+    /// PostgreSQL COPY data in text format
+    PgCopy,
+    /// Multi-row SQL INSERT statements
+    Sql,
+    /// Excel workbook, needs --output
+    Xlsx,
+    /// Fixed-width lines for legacy imports
+    FixedWidth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LayoutFormat {
+    Text,
+    Svg,
+}
+
+#[derive(Debug, Args)]
+struct InputArgs {
+    /// The BIC directory PDF, or `-` for standard input
+    input: PathBuf,
+    #[command(flatten)]
+    pdf: PdfArgs,
+}
+
+/// Options for `--format csv`.
+#[derive(Debug, Args)]
+struct CsvArgs {
+    /// Field separator, a single ASCII character
+    #[arg(long, default_value_t = ',')]
+    delimiter: char,
+    /// Quote every field instead of only those that need it
+    #[arg(long)]
+    quote_all: bool,
+    /// End rows with CRLF instead of LF, also for fixed-width output
+    #[arg(long)]
+    crlf: bool,
+    /// Start with a UTF-8 byte order mark, for Excel
+    #[arg(long)]
+    bom: bool,
+    /// Leave out the header row
+    #[arg(long)]
+    no_header: bool,
+    /// Neutralize fields that spreadsheets would evaluate as formulas, also for XLSX
+    #[arg(long)]
+    sanitize: bool,
+}
+
+/// Options for `--format sql`.
+#[derive(Debug, Args)]
+struct SqlArgs {
+    /// Table to insert into, optionally schema-qualified
+    #[arg(long, default_value = "bic_records")]
+    table: String,
+    /// Number of rows in each INSERT statement
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(1..))]
+    rows_per_insert: u64,
+    /// Start with a CREATE TABLE IF NOT EXISTS statement
+    #[arg(long)]
+    create_table: bool,
+}
+
+/// Options for `--format fixed-width`.
+#[derive(Debug, Args)]
+struct FixedWidthArgs {
+    /// Fields of each line in order, named as in JSON output, with their
+    /// width and optionally `right` alignment [default: every field]
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "FIELD:WIDTH[:right]",
+        value_parser = parse_field_layout
+    )]
+    layout: Vec<FieldLayout>,
+    /// Character filling fields up to their width
+    #[arg(long, default_value_t = ' ')]
+    padding: char,
+    /// Fail on values longer than their field instead of cutting them
+    #[arg(long)]
+    no_truncate: bool,
+}
+
+impl FixedWidthArgs {
+    fn layout(&self, crlf: bool) -> FixedWidthLayout {
+        let default = FixedWidthLayout::default();
+        FixedWidthLayout {
+            fields: if self.layout.is_empty() {
+                default.fields
+            } else {
+                self.layout.clone()
+            },
+            padding: self.padding,
+            truncation: if self.no_truncate {
+                Truncation::Error
+            } else {
+                Truncation::Truncate
+            },
+            line_ending: if crlf {
+                LineEnding::CrLf
+            } else {
+                LineEnding::Lf
+            },
+        }
+    }
+}
+
+fn parse_field_layout(spec: &str) -> Result<FieldLayout, String> {
+    let mut parts = spec.split(':');
+    let name = parts.next().unwrap_or_default();
+    let column = FIELDS
+        .iter()
+        .position(|field| *field == name)
+        .ok_or_else(|| {
+            format!(
+                "unknown field {:?}, expected one of {}",
+                name,
+                FIELDS.join(", ")
+            )
+        })?;
+    let width = parts
+        .next()
+        .and_then(|width| width.parse().ok())
+        .ok_or_else(|| format!("expected FIELD:WIDTH[:right], got {:?}", spec))?;
+    let align = match parts.next() {
+        None | Some("left") => Align::Left,
+        Some("right") => Align::Right,
+        Some(align) => return Err(format!("invalid alignment {:?}", align)),
+    };
+    if parts.next().is_some() {
+        return Err(format!("expected FIELD:WIDTH[:right], got {:?}", spec));
+    }
+    Ok(FieldLayout {
+        column,
+        width,
+        align,
+    })
+}
+
+impl SqlArgs {
+    fn options(&self) -> SqlOptions {
+        SqlOptions {
+            table: self.table.clone(),
+            rows_per_insert: self.rows_per_insert as usize,
+            create_table: self.create_table,
+        }
+    }
+}
+
+impl CsvArgs {
+    fn options(&self) -> Result<CsvOptions> {
+        let delimiter = u8::try_from(self.delimiter)
+            .ok()
+            .filter(u8::is_ascii)
+            .with_context(|| format!("Invalid CSV delimiter {:?}", self.delimiter))?;
+
+        Ok(CsvOptions {
+            delimiter,
+            quote_style: if self.quote_all {
+                QuoteStyle::Always
+            } else {
+                QuoteStyle::Necessary
+            },
+            line_ending: if self.crlf {
+                LineEnding::CrLf
+            } else {
+                LineEnding::Lf
+            },
+            bom: self.bom,
+            header: !self.no_header,
+            sanitize: self.sanitize,
+        })
+    }
+}
+
+/// Options for opening the PDF, shared by every subcommand.
+#[derive(Debug, Args)]
+struct PdfArgs {
+    /// Password of an encrypted PDF
+    #[arg(long, env = "BIC_EXPORTER_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+    /// Name of the PDF to extract from a zip archive holding more than one
+    #[arg(long)]
+    archive_entry: Option<String>,
+    /// Rebuild the cross-reference table of a PDF that fails to load
+    #[arg(long)]
+    repair: bool,
+    /// Maximum number of pages, including the cover
+    #[arg(long, value_name = "PAGES")]
+    max_pages: Option<usize>,
+    /// Maximum number of content stream operations on a single page
+    #[arg(long, value_name = "OPERATIONS")]
+    max_operations_per_page: Option<usize>,
+    /// Maximum decoded size in bytes of any stream, including streams that can't be measured
+    #[arg(long, value_name = "BYTES")]
+    max_stream_size: Option<usize>,
+    /// Maximum number of text fragments in the document
+    #[arg(long, value_name = "ELEMENTS")]
+    max_text_elements: Option<usize>,
+    /// Maximum size in bytes of the PDF after decompressing gzip or zip input
+    #[arg(long, value_name = "BYTES")]
+    max_input_size: Option<usize>,
+    /// Maximum extraction time, checked between pages and while parsing each page
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+}
+
+impl PdfArgs {
+    fn options(&self) -> ExtractOptions {
+        ExtractOptions {
+            limits: Limits {
+                max_pages: self.max_pages,
+                max_operations_per_page: self.max_operations_per_page,
+                max_stream_size: self.max_stream_size,
+                max_text_elements: self.max_text_elements,
+                timeout: self.timeout.map(Duration::from_secs),
+                max_input_size: self.max_input_size,
+            },
+            password: self.password.clone(),
+            repair: self.repair,
+            archive_entry: self.archive_entry.clone(),
+            ..ExtractOptions::default()
+        }
+    }
+}
+
+/// How a successful run ended, deciding the exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// Exit code 0.
+    Success,
+    /// Quality issues or differences were found. Exit code 1.
+    Findings,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(Outcome::Success) => ExitCode::SUCCESS,
+        Ok(Outcome::Findings) => ExitCode::from(1),
+        // The reader went away, e.g. output piped to `head`
+        Err(error) if is_broken_pipe(&error) => ExitCode::SUCCESS,
+        Err(error) if error.is::<QualityError>() => {
+            eprintln!("{}", error);
+            ExitCode::from(1)
+        }
+        Err(error) => {
+            eprintln!("error: {:#}", error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(command: Command) -> Result<Outcome> {
+    match command {
+        Command::Extract {
+            input,
+            format,
+            output,
+            strict,
+            recover,
+            metadata,
+            csv,
+            sql,
+            fixed_width,
+        } => {
+            anyhow::ensure!(
+                !metadata || matches!(format, Format::Json | Format::Ndjson),
+                "--metadata only applies to JSON output"
+            );
+            let options = ExtractOptions {
+                strict,
+                recover,
+                ..input.pdf.options()
+            };
+            let data = read_input(&input.input)?;
+            if let Format::Parquet | Format::Arrow = format {
+                let report = write_streamed(&data, &options, format, output.as_deref())
+                    .inspect_err(|_| {
+                        // Don't leave a file without its footer behind
+                        if let Some(output) = &output {
+                            std::fs::remove_file(output).ok();
+                        }
+                    })?;
+                warn_page_errors(&report);
+                return Ok(Outcome::Success);
+            }
+
+            let extraction = extract_table_with_options_from_bytes(&data, &options)?;
+            warn_page_errors(&extraction.report);
+
+            if format == Format::Sqlite {
+                let output = output.context("--format sqlite needs an --output file")?;
+                let metadata = metadata_from_bytes(&data, &options)?;
+                write_sqlite(&extraction.records, &output, Some(&metadata))?;
+                return Ok(Outcome::Success);
+            }
+            if format == Format::Xlsx {
+                let output = output.context("--format xlsx needs an --output file")?;
+                let options = XlsxOptions {
+                    sanitize: csv.sanitize,
+                    ..XlsxOptions::default()
+                };
+                let file = File::create(&output)
+                    .with_context(|| format!("Failed to create {}", output.display()))?;
+                write_xlsx(&extraction.records, BufWriter::new(file), &options)?
+                    .flush()
+                    .context("Failed to write records")?;
+                if options.sanitize {
+                    warn_sanitized(&extraction.records);
+                }
+                return Ok(Outcome::Success);
+            }
+
+            let mut writer = create_output(output.as_deref())?;
+            match format {
+                Format::Csv => {
+                    let options = csv.options()?;
+                    write_csv(&extraction.records, &mut writer, &options)?;
+                    if options.sanitize {
+                        warn_sanitized(&extraction.records);
+                    }
+                }
+                Format::Json | Format::Ndjson => {
+                    let header = if metadata {
+                        let edition = metadata_from_bytes(&data, &options)?.edition;
+                        Some(JsonHeader::new(edition))
+                    } else {
+                        None
+                    };
+                    if format == Format::Json {
+                        write_json(&extraction.records, &mut writer, header.as_ref())?;
+                    } else {
+                        write_ndjson(&extraction.records, &mut writer, header.as_ref())?;
+                    }
+                }
+                Format::PgCopy => {
+                    write_copy(&extraction.records, &mut writer)?;
+                }
+                Format::Sql => {
+                    write_inserts(&extraction.records, &mut writer, &sql.options())?;
+                }
+                Format::FixedWidth => {
+                    let layout = fixed_width.layout(csv.crlf);
+                    write_fixed_width(&extraction.records, &mut writer, &layout)?;
+                    for field in truncated_fields(&extraction.records, &layout) {
+                        eprintln!(
+                            "warning: truncated record {}, {}: {:?}",
+                            field.record, HEADERS[field.column], field.value
+                        );
+                    }
+                }
+                Format::Parquet | Format::Arrow | Format::Sqlite | Format::Xlsx => {
+                    unreachable!("{:?} is written without the output stream", format)
+                }
+            }
+            writer.flush().context("Failed to write records")?;
+            Ok(Outcome::Success)
+        }
+        Command::Metadata { input } => {
+            let metadata = metadata_from_bytes(read_input(&input.input)?, &input.pdf.options())?;
+            write_metadata(&metadata, std::io::stdout().lock())?;
+            Ok(Outcome::Success)
+        }
+        Command::DebugPage {
+            input,
+            page,
+            format,
+            output,
+        } => {
+            let layout = page_layout_with_options_from_bytes(
+                read_input(&input.input)?,
+                page,
+                &input.pdf.options(),
+            )?;
+
+            let mut writer = create_output(output.as_deref())?;
+            match format {
+                LayoutFormat::Text => write_layout(&layout, &mut writer)?,
+                LayoutFormat::Svg => writer.write_all(render_svg(&layout).as_bytes())?,
+            }
+            writer.flush().context("Failed to write layout")?;
+            Ok(Outcome::Success)
+        }
+        Command::Validate { input, recover } => {
+            let options = ExtractOptions {
+                recover,
+                ..input.pdf.options()
+            };
+            let extraction =
+                extract_table_with_options_from_bytes(read_input(&input.input)?, &options)?;
+            let issues = extraction.report.issues();
+
+            let mut stdout = std::io::stdout().lock();
+            for issue in &issues {
+                write!(stdout, "page {}", issue.page)?;
+                if let Some(record) = issue.record {
+                    write!(stdout, ", record {}", record)?;
+                }
+                writeln!(stdout, ": {}", issue.message)?;
+            }
+            writeln!(
+                stdout,
+                "{} records, {} quality issue(s)",
+                extraction.records.len(),
+                issues.len()
+            )?;
+
+            Ok(if issues.is_empty() {
+                Outcome::Success
+            } else {
+                Outcome::Findings
+            })
+        }
+        Command::Diff { old, new, pdf } => {
+            let options = pdf.options();
+            let old_records = extract_table_with_options_from_bytes(read_input(&old)?, &options)
+                .with_context(|| format!("Failed to extract {}", old.display()))?
+                .records;
+            let new_records = extract_table_with_options_from_bytes(read_input(&new)?, &options)
+                .with_context(|| format!("Failed to extract {}", new.display()))?
+                .records;
+
+            let differences = diff::diff(&old_records, &new_records);
+            diff::write_diff(&differences, std::io::stdout().lock())?;
+
+            Ok(if differences.is_empty() {
+                Outcome::Success
+            } else {
+                Outcome::Findings
+            })
+        }
+        Command::Lookup {
+            input,
+            bics,
+            same_country,
+        } => {
+            let records = extract_table_with_options_from_bytes(
+                read_input(&input.input)?,
+                &input.pdf.options(),
+            )?
+            .records;
+            let directory = Directory::new(records);
+            let options = SuggestOptions {
+                same_country,
+                ..SuggestOptions::default()
+            };
+
+            let mut stdout = std::io::stdout().lock();
+            let mut found = true;
+            for input in &bics {
+                let suggest = || {
+                    let suggestions: Vec<String> = directory
+                        .suggest(input, &options)
+                        .iter()
+                        .map(|suggestion| suggestion.bic.to_string())
+                        .collect();
+                    if suggestions.is_empty() {
+                        String::new()
+                    } else {
+                        format!(", did you mean {}?", suggestions.join(", "))
+                    }
+                };
+                match directory.lookup(input) {
+                    Ok(Lookup {
+                        bic,
+                        record: Some(record),
+                    }) => {
+                        let office = if bic.is_primary_office() {
+                            "head office"
+                        } else {
+                            "branch"
+                        };
+                        writeln!(
+                            stdout,
+                            "{}: {}, {}",
+                            bic,
+                            record[column::FULL_LEGAL_NAME],
+                            office
+                        )?;
+                    }
+                    Ok(Lookup { bic, record: None }) => {
+                        found = false;
+                        writeln!(stdout, "{}: not in the directory{}", bic, suggest())?;
+                    }
+                    Err(error) => {
+                        found = false;
+                        writeln!(stdout, "{:?}: {}{}", input, error, suggest())?;
+                    }
+                }
+            }
+
+            Ok(if found {
+                Outcome::Success
+            } else {
+                Outcome::Findings
+            })
+        }
+        Command::Schema => {
+            std::io::stdout().lock().write_all(SCHEMA.as_bytes())?;
+            Ok(Outcome::Success)
+        }
+    }
+}
+
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|error| error.kind() == std::io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Read the whole input, from standard input when `path` is `-`.
+fn read_input(path: &Path) -> Result<Vec<u8>> {
+    if path == Path::new("-") {
+        let mut data = Vec::new();
+        std::io::stdin()
+            .lock()
+            .read_to_end(&mut data)
+            .context("Failed to read standard input")?;
+        Ok(data)
+    } else {
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+}
+
+/// Open `path` for writing, or standard output when there is none.
+fn create_output(path: Option<&Path>) -> Result<Box<dyn Write + Send>> {
+    Ok(match path {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("Failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(std::io::stdout())),
+    })
+}
+
+/// Extract records into a Parquet or Arrow IPC file as each page is processed.
+fn write_streamed(
+    data: &[u8],
+    options: &ExtractOptions,
+    format: Format,
+    output: Option<&Path>,
+) -> Result<ExtractionReport> {
+    let writer = create_output(output)?;
+    let (report, mut writer) = match format {
+        Format::Parquet => {
+            let mut parquet = ParquetWriter::new(writer)?;
+            let report =
+                stream_table_from_bytes(data, options, |record| parquet.write_record(&record))?;
+            (report, parquet.finish()?)
+        }
+        Format::Arrow => {
+            let mut ipc = IpcWriter::new(writer)?;
+            let report =
+                stream_table_from_bytes(data, options, |record| ipc.write_record(&record))?;
+            (report, ipc.finish()?)
+        }
+        _ => unreachable!("{:?} isn't written while extracting", format),
+    };
+    writer.flush().context("Failed to write records")?;
+    Ok(report)
+}
+
+fn warn_page_errors(report: &ExtractionReport) {
+    for error in &report.page_errors {
+        eprintln!("warning: skipped page {}: {}", error.page, error.cause);
+    }
+}
+
+fn warn_sanitized(records: &[Vec<String>]) {
+    for field in formula_fields(records) {
+        eprintln!(
+            "warning: neutralized formula in record {}, {}: {:?}",
+            field.record, HEADERS[field.column], field.value
+        );
+    }
+}
+
+fn write_metadata(metadata: &Metadata, mut writer: impl Write) -> Result<()> {
+    let date = |date: Option<Date>| date.map_or("-".to_string(), |date| date.to_string());
+
+    writeln!(writer, "edition: {}", date(metadata.edition))?;
+    writeln!(writer, "pages: {}", metadata.pages)?;
+    writeln!(
+        writer,
+        "title: {}",
+        metadata.title.as_deref().unwrap_or("-")
+    )?;
+    writeln!(writer, "created: {}", date(metadata.created))?;
+    writeln!(writer, "modified: {}", date(metadata.modified))?;
+    if metadata.repaired {
+        writeln!(writer, "repaired: true")?;
+    }
+    Ok(())
+}
+
+/// Write a page layout as text, one line per row with its classification.
+fn write_layout(layout: &PageLayout, mut writer: impl Write) -> Result<()> {
+    writeln!(
+        writer,
+        "page {} ({} x {})",
+        layout.page_number, layout.width, layout.height
+    )?;
+    let boundaries: Vec<String> = layout
+        .boundaries
+        .iter()
+        .filter(|boundary| **boundary < f32::MAX)
+        .map(|boundary| format!("{:.1}", boundary))
+        .collect();
+    writeln!(writer, "boundaries: {}", boundaries.join(" "))?;
+
+    for row in &layout.rows {
+        let kind = format!("{:?}", row.kind).to_lowercase();
+        writeln!(
+            writer,
+            "{:8.1} {:<12} {}",
+            row.y,
+            kind,
+            row.cells.join(" | ")
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("core")
+            .join("tests")
+            .join("fixtures")
+            .join(name)
+    }
+
+    fn parse(args: &[&str]) -> Command {
+        Cli::try_parse_from(std::iter::once("bic-exporter").chain(args.iter().copied()))
+            .expect("Failed to parse arguments")
+            .command
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_pdf_options() {
+        let Command::Extract { input, format, .. } = parse(&[
+            "extract",
+            "ISOBIC.pdf",
+            "--format",
+            "json",
+            "--password",
+            "secret",
+            "--max-pages",
+            "10",
+            "--timeout",
+            "60",
+        ]) else {
+            panic!("Expected the extract command");
+        };
+
+        assert_eq!(format, Format::Json);
+        let options = input.pdf.options();
+        assert_eq!(options.password.as_deref(), Some("secret"));
+        assert_eq!(options.limits.max_pages, Some(10));
+        assert_eq!(options.limits.timeout, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_parse_csv_options() {
+        let Command::Extract { csv, .. } = parse(&[
+            "extract",
+            "ISOBIC.pdf",
+            "--delimiter",
+            ";",
+            "--crlf",
+            "--bom",
+        ]) else {
+            panic!("Expected the extract command");
+        };
+
+        let options = csv.options().unwrap();
+        assert_eq!(options.delimiter, b';');
+        assert_eq!(options.line_ending, LineEnding::CrLf);
+        assert!(options.bom);
+        assert!(options.header);
+
+        let Command::Extract { csv, .. } = parse(&["extract", "ISOBIC.pdf", "--delimiter", "é"])
+        else {
+            panic!("Expected the extract command");
+        };
+        assert!(csv.options().is_err());
+    }
+
+    #[test]
+    fn test_parse_fixed_width_options() {
+        let Command::Extract { fixed_width, .. } = parse(&[
+            "extract",
+            "ISOBIC.pdf",
+            "--format",
+            "fixed-width",
+            "--layout",
+            "bic:8,branch_code:3,full_legal_name:105:right",
+            "--no-truncate",
+        ]) else {
+            panic!("Expected the extract command");
+        };
+
+        let layout = fixed_width.layout(true);
+        assert_eq!(layout.fields.len(), 3);
+        assert_eq!(layout.fields[2].column, 4);
+        assert_eq!(layout.fields[2].align, Align::Right);
+        assert_eq!(layout.truncation, Truncation::Error);
+        assert_eq!(layout.line_ending, LineEnding::CrLf);
+
+        for layout in ["name:10", "bic", "bic:x", "bic:8:center", "bic:8:right:1"] {
+            let parsed =
+                Cli::try_parse_from(["bic-exporter", "extract", "a.pdf", "--layout", layout]);
+            assert!(parsed.is_err(), "{}", layout);
+        }
+    }
+
+    #[test]
+    fn test_run_on_fixture() {
+        let pdf = fixture("ISOBIC-mini.pdf");
+        let pdf = pdf.to_str().unwrap();
+        let output = std::env::temp_dir().join(format!("bic-exporter-{}.csv", std::process::id()));
+
+        let outcome = run(parse(&["extract", pdf, "-o", output.to_str().unwrap()]));
+        let written = std::fs::read_to_string(&output);
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcome.unwrap(), Outcome::Success);
+        assert_eq!(written.unwrap().lines().count(), 87);
+
+        let outcome = run(parse(&[
+            "extract",
+            pdf,
+            "--format",
+            "ndjson",
+            "--metadata",
+            "-o",
+            output.to_str().unwrap(),
+        ]));
+        let written = std::fs::read_to_string(&output);
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcome.unwrap(), Outcome::Success);
+        let written = written.unwrap();
+        assert!(
+            written.starts_with("{\"metadata\":{\"schema_version\":2,\"edition\":\"2025-12-04\"")
+        );
+        assert_eq!(written.lines().count(), 87);
+        assert!(run(parse(&["extract", pdf, "--metadata"])).is_err());
+
+        let outcome = run(parse(&[
+            "extract",
+            pdf,
+            "--format",
+            "parquet",
+            "-o",
+            output.to_str().unwrap(),
+        ]));
+        let written = std::fs::read(&output);
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcome.unwrap(), Outcome::Success);
+        assert!(written.unwrap().starts_with(b"PAR1"));
+
+        let outcome = run(parse(&[
+            "extract",
+            pdf,
+            "--format",
+            "sqlite",
+            "-o",
+            output.to_str().unwrap(),
+        ]));
+        let written = std::fs::read(&output);
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcome.unwrap(), Outcome::Success);
+        assert!(written.unwrap().starts_with(b"SQLite format 3"));
+        assert!(run(parse(&["extract", pdf, "--format", "sqlite"])).is_err());
+
+        let outcome = run(parse(&[
+            "extract",
+            pdf,
+            "--format",
+            "xlsx",
+            "--sanitize",
+            "-o",
+            output.to_str().unwrap(),
+        ]));
+        let written = std::fs::read(&output);
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcome.unwrap(), Outcome::Success);
+        assert!(written.unwrap().starts_with(b"PK"));
+        assert!(run(parse(&["extract", pdf, "--format", "xlsx"])).is_err());
+
+        let outcome = run(parse(&[
+            "extract",
+            pdf,
+            "--format",
+            "fixed-width",
+            "--layout",
+            "bic:8,full_legal_name:10",
+            "-o",
+            output.to_str().unwrap(),
+        ]));
+        let written = std::fs::read_to_string(&output);
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcome.unwrap(), Outcome::Success);
+        assert!(written.unwrap().starts_with("AAAARSBGYETTEL BAN\n"));
+
+        let outcome = run(parse(&[
+            "extract",
+            pdf,
+            "--format",
+            "sql",
+            "--table",
+            "public.bics",
+            "--rows-per-insert",
+            "50",
+            "-o",
+            output.to_str().unwrap(),
+        ]));
+        let written = std::fs::read_to_string(&output);
+        std::fs::remove_file(&output).ok();
+
+        assert_eq!(outcome.unwrap(), Outcome::Success);
+        let written = written.unwrap();
+        assert_eq!(
+            written.matches("INSERT INTO \"public\".\"bics\"").count(),
+            2
+        );
+
+        assert_eq!(run(parse(&["validate", pdf])).unwrap(), Outcome::Success);
+        assert_eq!(run(parse(&["diff", pdf, pdf])).unwrap(), Outcome::Success);
+        assert_eq!(
+            run(parse(&["lookup", pdf, "aaaa rs bg", "AABAFI22TET"])).unwrap(),
+            Outcome::Success
+        );
+        assert_eq!(
+            run(parse(&["lookup", pdf, "AAAARSBG", "AAAARSBG001"])).unwrap(),
+            Outcome::Findings
+        );
+        assert_eq!(
+            run(parse(&["lookup", pdf, "--same-country", "AAAARSGB"])).unwrap(),
+            Outcome::Findings
+        );
+        assert!(Cli::try_parse_from(["bic-exporter", "lookup", pdf]).is_err());
+        assert!(run(parse(&["metadata", "missing.pdf"])).is_err());
+    }
+
+    #[test]
+    fn test_write_metadata() {
+        let metadata = Metadata {
+            edition: Date::new(2025, 12, 4),
+            pages: 4,
+            title: None,
+            created: None,
+            modified: Date::new(2025, 12, 5),
+            repaired: false,
+        };
+        let mut output = Vec::new();
+
+        write_metadata(&metadata, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "edition: 2025-12-04\npages: 4\ntitle: -\ncreated: -\nmodified: 2025-12-05\n"
+        );
+    }
+}
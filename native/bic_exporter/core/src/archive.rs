@@ -0,0 +1,158 @@
+//! Gzip and zip input.
+//!
+//! Mirrors of the directory are often stored gzip-compressed, and some vendors
+//! distribute it inside a zip archive. Input is recognized by its magic bytes
+//! and decompressed before parsing, subject to
+//! [`Limits::max_input_size`](crate::Limits::max_input_size) since a small
+//! archive can expand to an arbitrarily large file.
+
+use crate::Limits;
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+use std::borrow::Cow;
+use std::io::Cursor;
+use zip::ZipArchive;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Decompress gzip or zip input, passing anything else through unchanged.
+///
+/// From a zip archive, the entry named `entry` is extracted, or else the
+/// archive's only PDF.
+pub(crate) fn decompress<'a>(
+    data: &'a [u8],
+    entry: Option<&str>,
+    limits: &Limits,
+) -> Result<Cow<'a, [u8]>> {
+    if data.starts_with(GZIP_MAGIC) {
+        limits
+            .read_input(MultiGzDecoder::new(data))
+            .map(Cow::Owned)
+            .context("Failed to decompress gzip input")
+    } else if data.starts_with(ZIP_MAGIC) {
+        let mut archive =
+            ZipArchive::new(Cursor::new(data)).context("Failed to read zip archive")?;
+        let name = match entry {
+            Some(name) => name.to_string(),
+            None => only_pdf_entry(&archive)?,
+        };
+        let file = archive
+            .by_name(&name)
+            .with_context(|| format!("Failed to find {:?} in zip archive", name))?;
+        limits
+            .read_input(file)
+            .map(Cow::Owned)
+            .with_context(|| format!("Failed to decompress {:?} from zip archive", name))
+    } else {
+        Ok(Cow::Borrowed(data))
+    }
+}
+
+fn only_pdf_entry(archive: &ZipArchive<Cursor<&[u8]>>) -> Result<String> {
+    let pdfs: Vec<&str> = archive
+        .file_names()
+        .filter(|name| name.to_ascii_lowercase().ends_with(".pdf"))
+        // Resource forks added by macOS archivers
+        .filter(|name| !name.starts_with("__MACOSX/"))
+        .collect();
+
+    match pdfs.as_slice() {
+        [name] => Ok(name.to_string()),
+        [] => anyhow::bail!("Zip archive contains no PDF"),
+        names => anyhow::bail!(
+            "Zip archive contains {} PDFs, select one by name: {}",
+            names.len(),
+            names.join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Limit, LimitExceeded};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    const PDF: &[u8] = b"%PDF-1.4\n...";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn limit_error(result: Result<Cow<[u8]>>) -> LimitExceeded {
+        result
+            .expect_err("Decompression should have failed")
+            .downcast()
+            .expect("Not a limit error")
+    }
+
+    #[test]
+    fn test_decompress_passes_pdf_through() {
+        let data = decompress(PDF, None, &Limits::default()).unwrap();
+        assert!(matches!(data, Cow::Borrowed(PDF)));
+    }
+
+    #[test]
+    fn test_decompress_gzip() {
+        assert_eq!(
+            *decompress(&gzip(PDF), None, &Limits::default()).unwrap(),
+            *PDF
+        );
+        assert!(decompress(&gzip(PDF)[..10], None, &Limits::default()).is_err());
+    }
+
+    #[test]
+    fn test_decompress_zip() {
+        let limits = Limits::default();
+        let single = zip(&[("readme.txt", b"hello"), ("BIC/ISOBIC.PDF", PDF)]);
+        assert_eq!(*decompress(&single, None, &limits).unwrap(), *PDF);
+
+        let several = zip(&[("a.pdf", PDF), ("b.pdf", b"%PDF-1.7")]);
+        let error = decompress(&several, None, &limits).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Zip archive contains 2 PDFs, select one by name: a.pdf, b.pdf"
+        );
+        assert_eq!(
+            *decompress(&several, Some("b.pdf"), &limits).unwrap(),
+            *b"%PDF-1.7"
+        );
+        assert!(decompress(&several, Some("c.pdf"), &limits).is_err());
+
+        let none = zip(&[("readme.txt", b"hello")]);
+        assert!(decompress(&none, None, &limits).is_err());
+    }
+
+    #[test]
+    fn test_decompress_limits_input_size() {
+        let limits = Limits {
+            max_input_size: Some(PDF.len() - 1),
+            ..Limits::default()
+        };
+
+        let error = limit_error(decompress(&gzip(PDF), None, &limits));
+        assert_eq!(error.limit, Limit::MaxInputSize);
+        let error = limit_error(decompress(&zip(&[("a.pdf", PDF)]), None, &limits));
+        assert_eq!(error.limit, Limit::MaxInputSize);
+
+        // Uncompressed input is already in memory, so isn't limited
+        assert!(decompress(PDF, None, &limits).is_ok());
+    }
+}
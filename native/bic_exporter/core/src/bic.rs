@@ -0,0 +1,280 @@
+//! ISO 9362 Business Identifier Codes.
+//!
+//! A BIC is 8 or 11 characters: a 4-character business party prefix, the
+//! 2-letter ISO 3166 country code, a 2-character location code and an
+//! optional 3-character branch code. An 8-character BIC is the same as the
+//! 11-character one with the `XXX` branch code of the primary office.
+
+use crate::Country;
+use std::str::FromStr;
+
+/// Branch code of a party's primary office, implied by an 8-character BIC.
+pub const PRIMARY_OFFICE: &str = "XXX";
+
+/// A structurally valid BIC, held as its 11 characters.
+///
+/// BICs order by their 11 characters, which is the order the directory is
+/// published in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bic(String);
+
+/// Why a code isn't a valid BIC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum BicError {
+    /// The code isn't 8 or 11 characters long.
+    Length,
+    /// The first 4 characters aren't uppercase letters or digits.
+    BusinessPartyPrefix,
+    /// The 5th and 6th characters aren't uppercase letters.
+    CountryCode,
+    /// The 7th and 8th characters aren't uppercase letters or digits.
+    LocationCode,
+    /// The last 3 characters aren't uppercase letters or digits.
+    BranchCode,
+}
+
+impl std::fmt::Display for BicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let problem = match self {
+            BicError::Length => "must be 8 or 11 characters long",
+            BicError::BusinessPartyPrefix => {
+                "has a business party prefix that isn't 4 uppercase letters or digits"
+            }
+            BicError::CountryCode => "has a country code that isn't 2 uppercase letters",
+            BicError::LocationCode => {
+                "has a location code that isn't 2 uppercase letters or digits"
+            }
+            BicError::BranchCode => "has a branch code that isn't 3 uppercase letters or digits",
+        };
+        write!(f, "BIC {}", problem)
+    }
+}
+
+impl std::error::Error for BicError {}
+
+impl Bic {
+    /// Parse an 8 or 11-character BIC.
+    ///
+    /// Parsing is strict: lowercase letters and spaces are rejected rather
+    /// than cleaned up, see [`Bic::normalize`] for that.
+    pub fn parse(code: &str) -> Result<Bic, BicError> {
+        match code.len() {
+            8 => Bic::from_parts(code, PRIMARY_OFFICE),
+            11 if code.is_char_boundary(8) => Bic::from_parts(&code[..8], &code[8..]),
+            _ => Err(BicError::Length),
+        }
+    }
+
+    /// Parse a BIC as people type it, ignoring case and whitespace anywhere,
+    /// as in `" aaaa rs bg 001"`.
+    pub fn normalize(input: &str) -> Result<Bic, BicError> {
+        let code: String = input
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        Bic::parse(&code)
+    }
+
+    /// Build a BIC from its first 8 characters and its branch code, as in the
+    /// directory's `BIC` and `Brch Code` columns.
+    pub fn from_parts(bic8: &str, branch_code: &str) -> Result<Bic, BicError> {
+        let bytes = bic8.as_bytes();
+        if bytes.len() != 8 || branch_code.len() != 3 {
+            return Err(BicError::Length);
+        }
+        if !bytes[..4].iter().all(is_alphanumeric) {
+            return Err(BicError::BusinessPartyPrefix);
+        }
+        if !bytes[4..6].iter().all(u8::is_ascii_uppercase) {
+            return Err(BicError::CountryCode);
+        }
+        if !bytes[6..].iter().all(is_alphanumeric) {
+            return Err(BicError::LocationCode);
+        }
+        if !branch_code.bytes().all(|b| is_alphanumeric(&b)) {
+            return Err(BicError::BranchCode);
+        }
+
+        Ok(Bic(format!("{}{}", bic8, branch_code)))
+    }
+
+    /// The 11 characters.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The first 8 characters, identifying the party without its branch.
+    pub fn bic8(&self) -> &str {
+        &self.0[..8]
+    }
+
+    /// The 4 characters identifying the party, letters only before ISO
+    /// 9362:2014 and letters or digits since.
+    pub fn business_party_prefix(&self) -> &str {
+        &self.0[..4]
+    }
+
+    pub fn country_code(&self) -> &str {
+        &self.0[4..6]
+    }
+
+    /// The country, unless the country code isn't in ISO 3166.
+    pub fn country(&self) -> Option<&'static Country> {
+        Country::from_code(self.country_code())
+    }
+
+    pub fn location_code(&self) -> &str {
+        &self.0[6..8]
+    }
+
+    pub fn branch_code(&self) -> &str {
+        &self.0[8..]
+    }
+
+    /// Whether the BIC identifies the party's primary office, with the
+    /// `XXX` branch code, rather than a branch.
+    pub fn is_primary_office(&self) -> bool {
+        self.branch_code() == PRIMARY_OFFICE
+    }
+
+    /// Whether the BIC is for testing and training, with `0` as the location
+    /// code's second character. Such BICs can't be used for live messages.
+    pub fn is_test(&self) -> bool {
+        self.location_character() == b'0'
+    }
+
+    /// Whether the BIC belongs to a passive participant, with `1` as the
+    /// location code's second character, which isn't connected to SWIFT and
+    /// is reached through another institution.
+    pub fn is_passive(&self) -> bool {
+        self.location_character() == b'1'
+    }
+
+    /// Whether the BIC is a reverse billing one, with `2` as the location
+    /// code's second character, where the receiver pays for messages.
+    pub fn is_reverse_billing(&self) -> bool {
+        self.location_character() == b'2'
+    }
+
+    fn location_character(&self) -> u8 {
+        self.0.as_bytes()[7]
+    }
+}
+
+impl FromStr for Bic {
+    type Err = BicError;
+
+    fn from_str(code: &str) -> Result<Bic, BicError> {
+        Bic::parse(code)
+    }
+}
+
+impl std::fmt::Display for Bic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+fn is_alphanumeric(b: &u8) -> bool {
+    b.is_ascii_uppercase() || b.is_ascii_digit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let bic = Bic::parse("AAAARSBG").unwrap();
+        assert_eq!(bic.as_str(), "AAAARSBGXXX");
+        assert_eq!(bic.bic8(), "AAAARSBG");
+        assert_eq!(bic.business_party_prefix(), "AAAA");
+        assert_eq!(bic.country_code(), "RS");
+        assert_eq!(bic.country().unwrap().name, "Serbia");
+        assert_eq!(bic.location_code(), "BG");
+        assert_eq!(bic.branch_code(), "XXX");
+        assert!(bic.is_primary_office());
+        assert_eq!(bic, "AAAARSBGXXX".parse().unwrap());
+
+        let bic = Bic::parse("1AAAFRP1ABC").unwrap();
+        assert_eq!(bic.to_string(), "1AAAFRP1ABC");
+        assert!(!bic.is_primary_office());
+        assert!(bic.is_passive());
+        assert!(!bic.is_test() && !bic.is_reverse_billing());
+        assert!(Bic::parse("AAAAQQ20").unwrap().country().is_none());
+    }
+
+    #[test]
+    fn test_flags() {
+        assert!(Bic::parse("AAAADEF0").unwrap().is_test());
+        assert!(Bic::parse("AAAADEF2").unwrap().is_reverse_billing());
+        let live = Bic::parse("AAAADEFF").unwrap();
+        assert!(!live.is_test() && !live.is_passive() && !live.is_reverse_billing());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for (code, error) in [
+            ("", BicError::Length),
+            ("AAAARSB", BicError::Length),
+            ("AAAARSBGXX", BicError::Length),
+            ("AAAARSBGXXXX", BicError::Length),
+            ("AAAARSBÉXX", BicError::Length),
+            ("aAAARSBG", BicError::BusinessPartyPrefix),
+            ("AAA-RSBG", BicError::BusinessPartyPrefix),
+            ("AAAAR1BG", BicError::CountryCode),
+            ("AAAArsBG", BicError::CountryCode),
+            ("AAAARSB ", BicError::LocationCode),
+            ("AAAARSBGxxx", BicError::BranchCode),
+            ("AAAARSBG X1", BicError::BranchCode),
+        ] {
+            assert_eq!(Bic::parse(code), Err(error), "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let bic = Bic::parse("AAAARSBG001").unwrap();
+        for input in [
+            "AAAARSBG001",
+            " aaaarsbg001\n",
+            "AAAA RS BG 001",
+            "aaaa\u{a0}rsbg\t001",
+        ] {
+            assert_eq!(Bic::normalize(input).as_ref(), Ok(&bic), "{:?}", input);
+        }
+        assert_eq!(
+            Bic::normalize(" aaaarsbg ").unwrap(),
+            Bic::parse("AAAARSBGXXX").unwrap()
+        );
+        assert_eq!(Bic::normalize("AAAA-RS-BG"), Err(BicError::Length));
+        assert_eq!(Bic::normalize("ÀAAARSBG"), Err(BicError::Length));
+        assert_eq!(
+            Bic::normalize("AAAÀRSB"),
+            Err(BicError::BusinessPartyPrefix)
+        );
+    }
+
+    #[test]
+    fn test_from_parts() {
+        assert_eq!(
+            Bic::from_parts("AAAARSBG", "XXX").unwrap(),
+            Bic::parse("AAAARSBG").unwrap()
+        );
+        assert_eq!(Bic::from_parts("AAAARSBGX", "XX"), Err(BicError::Length));
+        assert_eq!(Bic::from_parts("AAAARSBG", ""), Err(BicError::Length));
+    }
+
+    #[test]
+    fn test_ordering_follows_the_directory() {
+        let mut bics: Vec<Bic> = ["AAAARSBGXXX", "AAAARSBG001", "AAACKWKW"]
+            .iter()
+            .map(|code| code.parse().unwrap())
+            .collect();
+        bics.sort();
+        let codes: Vec<&str> = bics.iter().map(Bic::as_str).collect();
+        assert_eq!(codes, ["AAAARSBG001", "AAAARSBGXXX", "AAACKWKWXXX"]);
+    }
+}
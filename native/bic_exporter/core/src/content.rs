@@ -0,0 +1,270 @@
+//! Page content parsing, checked against the limits as it goes.
+//!
+//! `pdf::content::parse_ops` parses a whole content stream before returning,
+//! so a page with millions of operations would only be caught afterwards, and
+//! the deadline not at all. This parses the same syntax in a single pass,
+//! stopping as soon as the page has too many operations or time runs out.
+//! Only the operations extraction reads are kept.
+
+use crate::limits::Limits;
+use anyhow::{bail, Result};
+use pdf::content::{Matrix, Op, Point, TextDrawAdjusted};
+use pdf::object::NoResolve;
+use pdf::parser::{parse_with_lexer, Lexer, ParseFlags};
+use pdf::primitive::Primitive;
+use std::time::Instant;
+
+/// Tokens parsed between deadline checks.
+const TOKENS_PER_DEADLINE_CHECK: usize = 1024;
+
+/// Parse a page's content into the text and line operations extraction reads.
+///
+/// Every operator counts towards `max_operations_per_page`, kept or not.
+pub(crate) fn parse_ops(
+    content: &[u8],
+    limits: &Limits,
+    started: Instant,
+    page: usize,
+) -> Result<Vec<Op>> {
+    let mut lexer = Lexer::new(content);
+    let mut operands = Vec::new();
+    let mut ops = Vec::new();
+    let mut operators = 0;
+
+    for tokens in 1.. {
+        if tokens % TOKENS_PER_DEADLINE_CHECK == 0 {
+            limits.check_timeout(started, page)?;
+        }
+
+        let position = lexer.get_pos();
+        match parse_with_lexer(&mut lexer, &NoResolve, ParseFlags::ANY) {
+            Ok(operand) => operands.push(operand),
+            Err(error) if error.is_eof() => break,
+            Err(_) => {
+                // Not an operand, so an operator, as the pdf crate parses it
+                lexer.set_pos(position);
+                let token = lexer.next()?;
+                let operator = token.as_str()?;
+                // Inline image data up to "EI" belongs to the "BI" operation
+                if operator == "ID" {
+                    if lexer.seek_substr("\nEI").is_none() {
+                        bail!("inline image exceeds expected data range");
+                    }
+                } else {
+                    operators += 1;
+                    limits.check_operations(operators, page)?;
+                    let _ = push_op(&mut ops, operator, &mut operands.drain(..));
+                }
+                operands.clear();
+            }
+        }
+        if lexer.get_pos() >= content.len() {
+            break;
+        }
+    }
+
+    limits.check_timeout(started, page)?;
+    Ok(ops)
+}
+
+/// Push the operations for `operator`, if extraction reads them.
+///
+/// Like the `pdf` crate, an operator with operands of the wrong type is
+/// skipped, after any operations it pushed before reaching them.
+fn push_op(
+    ops: &mut Vec<Op>,
+    operator: &str,
+    operands: &mut impl Iterator<Item = Primitive>,
+) -> Option<()> {
+    let mut number = || operands.next()?.as_number().ok();
+    match operator {
+        "BT" => ops.push(Op::BeginText),
+        "Tm" => {
+            let matrix = Matrix {
+                a: number()?,
+                b: number()?,
+                c: number()?,
+                d: number()?,
+                e: number()?,
+                f: number()?,
+            };
+            ops.push(Op::SetTextMatrix { matrix });
+        }
+        "Td" | "TD" => {
+            let translation = Point {
+                x: number()?,
+                y: number()?,
+            };
+            ops.push(Op::MoveTextPosition { translation });
+        }
+        "m" | "l" => {
+            let p = Point {
+                x: number()?,
+                y: number()?,
+            };
+            ops.push(match operator {
+                "m" => Op::MoveTo { p },
+                _ => Op::LineTo { p },
+            });
+        }
+        "T*" => ops.push(Op::TextNewline),
+        "Tj" => ops.push(Op::TextDraw {
+            text: operands.next()?.into_string().ok()?,
+        }),
+        "'" => {
+            ops.push(Op::TextNewline);
+            ops.push(Op::TextDraw {
+                text: operands.next()?.into_string().ok()?,
+            });
+        }
+        "\"" => {
+            // Word and character spacing, which extraction doesn't track
+            number()?;
+            number()?;
+            ops.push(Op::TextNewline);
+            ops.push(Op::TextDraw {
+                text: operands.next()?.into_string().ok()?,
+            });
+        }
+        "TJ" => {
+            let array = match operands.next() {
+                Some(Primitive::Array(array)) => array,
+                None => Vec::new(),
+                Some(_) => return None,
+            };
+            let array = array
+                .into_iter()
+                .map(|item| match item {
+                    Primitive::Integer(spacing) => Some(TextDrawAdjusted::Spacing(spacing as f32)),
+                    Primitive::Number(spacing) => Some(TextDrawAdjusted::Spacing(spacing)),
+                    Primitive::String(text) => Some(TextDrawAdjusted::Text(text)),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?;
+            ops.push(Op::TextDrawAdjusted { array });
+        }
+        _ => {}
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::{Limit, LimitExceeded};
+    use std::time::Duration;
+
+    fn parse(content: &[u8]) -> Vec<Op> {
+        parse_ops(content, &Limits::default(), Instant::now(), 2).unwrap()
+    }
+
+    /// The operations the pdf crate parses from `content` that extraction reads.
+    fn expected(content: &[u8], resolver: &impl pdf::object::Resolve) -> String {
+        let ops = pdf::content::parse_ops(content, resolver).unwrap();
+        let kept: Vec<_> = ops
+            .iter()
+            .filter(|op| {
+                matches!(
+                    op,
+                    Op::BeginText
+                        | Op::SetTextMatrix { .. }
+                        | Op::MoveTextPosition { .. }
+                        | Op::MoveTo { .. }
+                        | Op::LineTo { .. }
+                        | Op::TextNewline
+                        | Op::TextDraw { .. }
+                        | Op::TextDrawAdjusted { .. }
+                )
+            })
+            .collect();
+        format!("{:?}", kept)
+    }
+
+    #[test]
+    fn test_parse_ops() {
+        let content = b"BT /F1 8 Tf 1 0 0 1 10 20 Tm (A) Tj 0 -8 TD [(B) -300 (C) 2.5] TJ \
+            T* (D) ' 1 2 (E) \" ET 0 0 m 0 100 l S q 1 0 0 1 0 0 cm Q";
+        assert_eq!(
+            format!("{:?}", parse(content)),
+            expected(content, &NoResolve)
+        );
+        assert_eq!(parse(content).len(), 12);
+
+        // Operands of the wrong type skip the operation
+        let content = b"10 m (x) 20 l /F1 Tj [/F1] TJ";
+        assert_eq!(
+            format!("{:?}", parse(content)),
+            expected(content, &NoResolve)
+        );
+        assert!(parse(content).is_empty());
+    }
+
+    #[test]
+    fn test_parse_ops_matches_the_pdf_crate_with_fixture() {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        let pdf_bytes = std::fs::read(path).expect("Failed to read PDF file");
+        let file = pdf::file::FileOptions::cached().load(pdf_bytes).unwrap();
+        let resolver = file.resolver();
+
+        for page in file.pages() {
+            let Some(contents) = &page.unwrap().contents else {
+                continue;
+            };
+            let mut content = Vec::new();
+            for part in &contents.parts {
+                content.extend_from_slice(&part.data(&resolver).unwrap());
+            }
+            assert_eq!(
+                format!("{:?}", parse(&content)),
+                expected(&content, &resolver)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_ops_checks_operation_limit() {
+        let limits = Limits {
+            max_operations_per_page: Some(100),
+            ..Limits::default()
+        };
+        let content = "BT /F1 8 Tf 10 20 Td (AAAAGB2L) Tj ET\n".repeat(20);
+        assert!(parse_ops(content.as_bytes(), &limits, Instant::now(), 2).is_ok());
+
+        let content = content.repeat(2);
+        assert_eq!(
+            parse_ops(content.as_bytes(), &limits, Instant::now(), 2)
+                .unwrap_err()
+                .downcast::<LimitExceeded>()
+                .unwrap(),
+            LimitExceeded {
+                limit: Limit::MaxOperationsPerPage,
+                max: 100,
+                page: Some(2),
+            }
+        );
+
+        // The inline image is a single operation, whatever its data looks like
+        let image = "BI /W 4 /H 1 /BPC 8 /CS /G ID\nm m m m\nEI\n".repeat(100);
+        assert!(parse_ops(image.as_bytes(), &limits, Instant::now(), 2).is_ok());
+    }
+
+    #[test]
+    fn test_parse_ops_checks_deadline() {
+        let limits = Limits {
+            timeout: Some(Duration::from_millis(5)),
+            ..Limits::default()
+        };
+        let started = Instant::now() - Duration::from_millis(10);
+        assert_eq!(
+            parse_ops(b"0 0 m", &limits, started, 2)
+                .unwrap_err()
+                .downcast::<LimitExceeded>()
+                .unwrap()
+                .limit,
+            Limit::Timeout
+        );
+    }
+}
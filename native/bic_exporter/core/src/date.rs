@@ -0,0 +1,161 @@
+//! Minimal calendar date handling for the directory's `YYYY-MM-DD` columns.
+
+/// A calendar date parsed from the PDF's `YYYY-MM-DD` format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Parse a strict `YYYY-MM-DD` date, rejecting impossible days such as `2023-02-29`.
+    pub fn parse(value: &str) -> Option<Date> {
+        let bytes = value.as_bytes();
+        if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+            return None;
+        }
+
+        let year = parse_digits(&value[0..4])? as i32;
+        let month = parse_digits(&value[5..7])?;
+        let day = parse_digits(&value[8..10])?;
+
+        Date::new(year, month, day)
+    }
+
+    /// The date `days` days after 1970-01-01 (before it, when negative).
+    pub fn from_days_since_epoch(days: i64) -> Date {
+        // Howard Hinnant's civil_from_days, counting in 400-year eras of
+        // 146097 days that start on March 1st
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Date {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// Number of days from 1970-01-01 to this date, negative before it.
+    pub fn days_since_epoch(self) -> i64 {
+        // Howard Hinnant's days_from_civil, the inverse of the above
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month_from_march = (i64::from(self.month) + 9) % 12;
+        let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Build a date, rejecting impossible ones such as February 30th.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Date { year, month, day })
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn parse_digits(value: &str) -> Option<u32> {
+    if value.bytes().all(|b| b.is_ascii_digit()) {
+        value.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_dates() {
+        assert_eq!(
+            Date::parse("1997-03-01"),
+            Some(Date {
+                year: 1997,
+                month: 3,
+                day: 1
+            })
+        );
+        assert!(Date::parse("2024-02-29").is_some());
+        assert!(Date::parse("2000-02-29").is_some());
+    }
+
+    #[test]
+    fn test_from_days_since_epoch() {
+        assert_eq!(
+            Date::from_days_since_epoch(0),
+            Date::new(1970, 1, 1).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(-1),
+            Date::new(1969, 12, 31).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(11_016),
+            Date::new(2000, 2, 29).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(20_426),
+            Date::new(2025, 12, 4).unwrap()
+        );
+
+        for days in -800_000..800_000 {
+            assert_eq!(Date::from_days_since_epoch(days).days_since_epoch(), days);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let date = Date::new(2025, 12, 4).unwrap();
+        assert_eq!(date.to_string(), "2025-12-04");
+        assert_eq!(Date::parse(&date.to_string()), Some(date));
+    }
+
+    #[test]
+    fn test_parse_invalid_dates() {
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("1900-02-29"), None);
+        assert_eq!(Date::parse("2024-13-01"), None);
+        assert_eq!(Date::parse("2024-04-31"), None);
+        assert_eq!(Date::parse("2024-1-01"), None);
+        assert_eq!(Date::parse("2024-01-01 X"), None);
+        assert_eq!(Date::parse("+024-01-01"), None);
+        assert_eq!(Date::parse(""), None);
+    }
+}
@@ -0,0 +1,363 @@
+//! Visual debugging of the table reconstruction.
+//!
+//! When a fragment ends up in the wrong column, the quickest way to see why
+//! is to look at the page: where each text fragment sits, where the detected
+//! column boundaries are, and how each row was classified. This module
+//! captures that as a [`PageLayout`] and renders it to a standalone SVG,
+//! so no rasterizer or external tool is needed to inspect it.
+
+use crate::{
+    classify_rows, detect_column_boundaries, extract_text_from_ops, group_into_rows, with_pdf,
+    ExtractOptions, RowKind, TextElement, Y_TOLERANCE,
+};
+use anyhow::{Context, Result};
+use std::fmt::Write;
+
+/// Distance above a row's baseline covered by its highlight band.
+const ROW_BAND_ASCENT: f32 = 7.0;
+
+/// Distance below a row's baseline covered by its highlight band.
+const ROW_BAND_DESCENT: f32 = 2.0;
+
+/// Font size used to draw text fragments in the SVG.
+const SVG_FONT_SIZE: f32 = 6.0;
+
+/// A positioned text fragment as extracted from the page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A grouped row, its column-assigned cells and how it was classified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutRow {
+    pub y: f32,
+    pub kind: RowKind,
+    pub cells: Vec<String>,
+}
+
+/// Everything the extractor sees on a single page.
+///
+/// Coordinates are in PDF user space (origin at the bottom-left corner).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLayout {
+    /// 1-based page number, as shown by PDF viewers (the cover is page 1).
+    pub page_number: usize,
+    pub width: f32,
+    pub height: f32,
+    /// Column boundaries used for extraction, ending with `f32::MAX`.
+    pub boundaries: Vec<f32>,
+    pub fragments: Vec<Fragment>,
+    pub rows: Vec<LayoutRow>,
+}
+
+impl From<TextElement> for Fragment {
+    fn from(element: TextElement) -> Self {
+        Fragment {
+            text: element.text,
+            x: element.x,
+            y: element.y,
+        }
+    }
+}
+
+/// Capture the layout of a single page.
+///
+/// Column boundaries are detected from the first page after the cover, exactly
+/// as the extraction does, so the layout shows what extraction would produce.
+pub fn page_layout_from_bytes(data: impl AsRef<[u8]>, page_number: usize) -> Result<PageLayout> {
+    page_layout_with_options_from_bytes(data, page_number, &ExtractOptions::default())
+}
+
+/// Capture the layout of a single page, decompressing, decrypting and
+/// repairing the PDF as `options` ask.
+pub fn page_layout_with_options_from_bytes(
+    data: impl AsRef<[u8]>,
+    page_number: usize,
+    options: &ExtractOptions,
+) -> Result<PageLayout> {
+    let (layout, _) = with_pdf(data.as_ref(), options, |file| {
+        page_layout(file, page_number)
+    })?;
+    Ok(layout)
+}
+
+fn page_layout(file: pdf::file::CachedFile<&[u8]>, page_number: usize) -> Result<PageLayout> {
+    let resolver = file.resolver();
+
+    let num_pages = file.num_pages() as usize;
+    if page_number == 0 || page_number > num_pages {
+        anyhow::bail!(
+            "Page {} is out of range, the document has {} pages",
+            page_number,
+            num_pages
+        );
+    }
+
+    let mut boundaries = None;
+    for page_index in 1..num_pages {
+        let page = file
+            .get_page(page_index as u32)
+            .context(format!("Failed to get page {}", page_index + 1))?;
+        if let Some(contents) = &page.contents {
+            let ops = contents.operations(&resolver).context(format!(
+                "Failed to parse operations on page {}",
+                page_index + 1
+            ))?;
+            boundaries = Some(detect_column_boundaries(&ops)?);
+            break;
+        }
+    }
+    let boundaries = boundaries.context("Failed to find a page with content after the cover")?;
+
+    let page_index = page_number - 1;
+    let page = file
+        .get_page(page_index as u32)
+        .context(format!("Failed to get page {}", page_number))?;
+    let media_box = page
+        .media_box()
+        .context(format!("Failed to get media box of page {}", page_number))?;
+
+    let ops = match &page.contents {
+        Some(contents) => contents.operations(&resolver).context(format!(
+            "Failed to parse operations on page {}",
+            page_number
+        ))?,
+        None => Vec::new(),
+    };
+
+    let elements = extract_text_from_ops(&ops);
+    let fragments = elements.iter().cloned().map(Fragment::from).collect();
+    let rows = classify_rows(group_into_rows(elements, Y_TOLERANCE), &boundaries)
+        .into_iter()
+        .map(|classified| LayoutRow {
+            y: classified.row.y,
+            kind: classified.kind,
+            cells: classified.cells,
+        })
+        .collect();
+
+    Ok(PageLayout {
+        page_number,
+        width: media_box.right - media_box.left,
+        height: media_box.top - media_box.bottom,
+        boundaries,
+        fragments,
+        rows,
+    })
+}
+
+/// Render a page of the PDF as an SVG overlay of the detected table grid.
+pub fn render_page_svg_from_bytes(data: impl AsRef<[u8]>, page_number: usize) -> Result<String> {
+    let layout = page_layout_from_bytes(data, page_number)?;
+    Ok(render_svg(&layout))
+}
+
+/// Render a page layout as a standalone SVG document.
+///
+/// Row bands are color-coded by [`RowKind`], column boundaries are drawn as
+/// dashed lines and text left of the first boundary (which extraction ignores)
+/// is drawn in red.
+pub fn render_svg(layout: &PageLayout) -> String {
+    let width = layout.width;
+    let height = layout.height;
+    let mut svg = String::new();
+
+    // Writing to a String can't fail, so the fmt::Result values are ignored.
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.2}" height="{h:.2}" viewBox="0 0 {w:.2} {h:.2}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(svg, r#"<title>Page {} layout</title>"#, layout.page_number);
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+
+    svg.push_str("<g id=\"rows\">\n");
+    for row in &layout.rows {
+        let Some(color) = row_color(row.kind) else {
+            continue;
+        };
+        let _ = writeln!(
+            svg,
+            r#"<rect x="0" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}" fill-opacity="0.2"><title>{:?}</title></rect>"#,
+            height - (row.y + ROW_BAND_ASCENT),
+            width,
+            ROW_BAND_ASCENT + ROW_BAND_DESCENT,
+            color,
+            row.kind
+        );
+    }
+    svg.push_str("</g>\n");
+
+    svg.push_str("<g id=\"boundaries\" stroke=\"#6a1b9a\" stroke-dasharray=\"4 2\">\n");
+    for x in layout.boundaries.iter().filter(|x| **x < width) {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x:.2}" y1="0" x2="{x:.2}" y2="{:.2}"/>"#,
+            height
+        );
+    }
+    svg.push_str("</g>\n");
+
+    let first_boundary = layout.boundaries.first().copied().unwrap_or(f32::MIN);
+    let _ = writeln!(
+        svg,
+        r#"<g id="fragments" font-family="monospace" font-size="{}">"#,
+        SVG_FONT_SIZE
+    );
+    for fragment in &layout.fragments {
+        let fill = if fragment.x < first_boundary {
+            "#c62828"
+        } else {
+            "black"
+        };
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{:.2}" fill="{}">{}</text>"#,
+            fragment.x,
+            height - fragment.y,
+            fill,
+            escape_xml(&fragment.text)
+        );
+    }
+    svg.push_str("</g>\n");
+
+    svg.push_str(&legend());
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Band color for each row classification. Empty rows are not highlighted.
+fn row_color(kind: RowKind) -> Option<&'static str> {
+    match kind {
+        RowKind::Empty => None,
+        RowKind::Header => Some("#9e9e9e"),
+        RowKind::Data => Some("#2e7d32"),
+        RowKind::Continuation => Some("#1565c0"),
+        RowKind::Orphan => Some("#c62828"),
+    }
+}
+
+fn legend() -> String {
+    let mut legend = String::from("<g id=\"legend\" font-family=\"sans-serif\" font-size=\"8\">\n");
+    let kinds = [
+        RowKind::Header,
+        RowKind::Data,
+        RowKind::Continuation,
+        RowKind::Orphan,
+    ];
+    for (i, kind) in kinds.into_iter().enumerate() {
+        let x = 4.0 + i as f32 * 70.0;
+        let _ = writeln!(
+            legend,
+            r#"<rect x="{x:.2}" y="2" width="8" height="8" fill="{}" fill-opacity="0.4"/><text x="{:.2}" y="9">{:?}</text>"#,
+            row_color(kind).unwrap_or("white"),
+            x + 10.0,
+            kind
+        );
+    }
+    legend.push_str("</g>\n");
+    legend
+}
+
+/// Escape `text` for SVG, replacing control characters XML can't hold with
+/// U+FFFD so the odd bytes a fragment decoded to still show.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\0'..='\x1F' | '\u{FFFE}' | '\u{FFFF}' => escaped.push(char::REPLACEMENT_CHARACTER),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_bytes() -> Vec<u8> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        std::fs::read(path).expect("Failed to read PDF file")
+    }
+
+    #[test]
+    fn test_page_layout_classifies_rows() {
+        let layout = page_layout_from_bytes(fixture_bytes(), 2).expect("Failed to get layout");
+
+        assert_eq!(layout.page_number, 2);
+        assert_eq!(layout.boundaries.len(), 11);
+        assert!(layout.width > layout.height, "Data pages are landscape");
+        assert!(layout.rows.iter().any(|r| r.kind == RowKind::Header));
+        assert!(layout.rows.iter().any(|r| r.kind == RowKind::Data));
+        assert!(layout.rows.iter().any(|r| r.kind == RowKind::Continuation));
+    }
+
+    #[test]
+    fn test_page_layout_out_of_range() {
+        assert!(page_layout_from_bytes(fixture_bytes(), 0).is_err());
+        assert!(page_layout_from_bytes(fixture_bytes(), 100).is_err());
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let layout = PageLayout {
+            page_number: 2,
+            width: 200.0,
+            height: 100.0,
+            boundaries: vec![10.0, 100.0, f32::MAX],
+            fragments: vec![
+                Fragment {
+                    text: "A&B".to_string(),
+                    x: 20.0,
+                    y: 80.0,
+                },
+                Fragment {
+                    text: "stray".to_string(),
+                    x: 2.0,
+                    y: 80.0,
+                },
+            ],
+            rows: vec![LayoutRow {
+                y: 80.0,
+                kind: RowKind::Orphan,
+                cells: vec!["A&B".to_string(), String::new()],
+            }],
+        };
+
+        let svg = render_svg(&layout);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(r##"<text x="20.00" y="20.00" fill="black">A&amp;B</text>"##));
+        assert!(svg.contains(r##"<text x="2.00" y="20.00" fill="#c62828">stray</text>"##));
+        assert_eq!(svg.matches("<line ").count(), 2, "End marker isn't drawn");
+        assert!(svg.contains("<title>Orphan</title>"));
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape_xml("A&B <\"x\">\t'y'"),
+            "A&amp;B &lt;&quot;x&quot;&gt;\t&apos;y&apos;"
+        );
+        assert_eq!(
+            escape_xml("a\0b\x1Fc\u{FFFF}"),
+            "a\u{FFFD}b\u{FFFD}c\u{FFFD}"
+        );
+    }
+}
@@ -0,0 +1,543 @@
+//! Looking up BICs in an extracted directory, such as those customers type
+//! into payment forms.
+//!
+//! A [`Directory`] indexes records by the BIC their `BIC` and `Brch Code`
+//! columns make up. Lookups take input the way [`Bic::normalize`] does, so
+//! `"aaaa rs bg"` finds the record of `AAAARSBGXXX`.
+//!
+//! When a BIC isn't found, [`Directory::suggest`] finds the closest ones by
+//! an edit distance where the usual typing mistakes cost less than other
+//! edits: swapping two neighbouring characters, and mixing up `O` with `0`
+//! or `I` with `1`. The BICs are kept sorted and searched like a trie: the
+//! distance to a prefix is computed once for all the BICs starting with it,
+//! and they're all skipped as soon as it's too far from the input, so a
+//! search of the full directory takes a few milliseconds.
+
+use crate::{column, Bic, BicError};
+use std::collections::HashMap;
+
+/// Cost of inserting, deleting or replacing a character.
+const EDIT_COST: f32 = 1.0;
+
+/// Cost of swapping two neighbouring characters.
+const TRANSPOSITION_COST: f32 = 0.5;
+
+/// Cost of replacing a character with one that looks the same.
+const CONFUSION_COST: f32 = 0.25;
+
+/// Characters that look alike, in both orders.
+const CONFUSABLE: [(u8, u8); 2] = [(b'O', b'0'), (b'I', b'1')];
+
+/// Extracted records indexed by BIC.
+#[derive(Debug, Clone, Default)]
+pub struct Directory {
+    records: Vec<Vec<String>>,
+    index: HashMap<Bic, usize>,
+    /// What [`Directory::suggest`] searches, sorted.
+    entries: Vec<Entry>,
+}
+
+/// A BIC as searched by [`Directory::suggest`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Entry {
+    /// The BIC, or a head office's first 8 characters followed by zeros, so
+    /// that 8-character input matches them.
+    code: [u8; 11],
+    len: usize,
+    /// Position of the BIC's record.
+    record: usize,
+}
+
+impl Entry {
+    fn new(code: &str, record: usize) -> Self {
+        let mut entry = Entry {
+            code: [0; 11],
+            len: code.len(),
+            record,
+        };
+        entry.code[..code.len()].copy_from_slice(code.as_bytes());
+        entry
+    }
+
+    fn code(&self) -> &[u8] {
+        &self.code[..self.len]
+    }
+}
+
+/// Options for [`Directory::suggest`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestOptions {
+    /// Maximum number of suggestions.
+    pub limit: usize,
+    /// Maximum distance from the input, where an edit costs 1, swapping two
+    /// characters 0.5 and mixing up `O`/`0` or `I`/`1` 0.25.
+    pub max_distance: f32,
+    /// Only suggest BICs with the input's country code, its 5th and 6th
+    /// characters.
+    pub same_country: bool,
+}
+
+impl Default for SuggestOptions {
+    fn default() -> Self {
+        SuggestOptions {
+            limit: 5,
+            max_distance: 2.0,
+            same_country: false,
+        }
+    }
+}
+
+/// A BIC close to the one looked for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion<'a> {
+    pub bic: Bic,
+    /// Weighted edit distance from the input, see
+    /// [`SuggestOptions::max_distance`].
+    pub distance: f32,
+    pub record: &'a [String],
+}
+
+/// The result of looking up a well-formed BIC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lookup<'a> {
+    /// The BIC looked up, with the `XXX` branch code when 8 characters were
+    /// given.
+    pub bic: Bic,
+    /// The BIC's record, unless it isn't in the directory.
+    pub record: Option<&'a [String]>,
+}
+
+impl Lookup<'_> {
+    /// Whether the BIC is in the directory.
+    pub fn exists(&self) -> bool {
+        self.record.is_some()
+    }
+
+    /// Whether the BIC is a branch's rather than the party's head office.
+    pub fn is_branch(&self) -> bool {
+        !self.bic.is_primary_office()
+    }
+}
+
+impl Directory {
+    /// Index `records`, as extracted.
+    ///
+    /// Records whose BIC or branch code isn't valid, which the quality
+    /// report lists, can't be looked up. When a BIC appears more than once,
+    /// lookups find its first record.
+    pub fn new(records: Vec<Vec<String>>) -> Self {
+        let mut index = HashMap::with_capacity(records.len());
+        for (position, record) in records.iter().enumerate() {
+            let field = |column: usize| record.get(column).map_or("", String::as_str);
+            if let Ok(bic) = Bic::from_parts(field(column::BIC), field(column::BRCH_CODE)) {
+                index.entry(bic).or_insert(position);
+            }
+        }
+
+        let mut entries = Vec::with_capacity(index.len());
+        for (bic, &position) in &index {
+            entries.push(Entry::new(bic.as_str(), position));
+            if bic.is_primary_office() {
+                entries.push(Entry::new(bic.bic8(), position));
+            }
+        }
+        entries.sort_unstable();
+
+        Directory {
+            records,
+            index,
+            entries,
+        }
+    }
+
+    /// All records, in the order they were given.
+    pub fn records(&self) -> &[Vec<String>] {
+        &self.records
+    }
+
+    /// Number of BICs that can be looked up.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The record of `bic`.
+    pub fn get(&self, bic: &Bic) -> Option<&[String]> {
+        self.index
+            .get(bic)
+            .map(|&position| self.records[position].as_slice())
+    }
+
+    /// Look up a BIC as typed, ignoring case and whitespace.
+    ///
+    /// Fails when the input isn't a well-formed BIC, whether or not it's in
+    /// the directory.
+    pub fn lookup(&self, input: &str) -> Result<Lookup<'_>, BicError> {
+        let bic = Bic::normalize(input)?;
+        let record = self.get(&bic);
+        Ok(Lookup { bic, record })
+    }
+
+    /// Find the BICs closest to `input`, closest first, then in BIC order.
+    ///
+    /// The input is normalized as for [`Directory::lookup`] but needn't be a
+    /// well-formed BIC, since typos often make it invalid. A head office's
+    /// BIC is matched with and without its `XXX` branch code, so 8 characters
+    /// are enough to find it. A BIC in the directory is its own closest
+    /// suggestion, at distance 0.
+    pub fn suggest(&self, input: &str, options: &SuggestOptions) -> Vec<Suggestion<'_>> {
+        let input: Vec<u8> = input
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            // Other characters can't be in a BIC, so they never match
+            .map(|c| {
+                if c.is_ascii() {
+                    c.to_ascii_uppercase() as u8
+                } else {
+                    0
+                }
+            })
+            .collect();
+
+        let same_country: Vec<Entry>;
+        let entries = if options.same_country {
+            let country = input.get(4..6);
+            same_country = self
+                .entries
+                .iter()
+                .filter(|entry| country == Some(&entry.code[4..6]))
+                .cloned()
+                .collect();
+            &same_country
+        } else {
+            &self.entries
+        };
+
+        let mut found = search(entries, &input, options.max_distance);
+        // Keep the closest of a head office's two entries
+        found.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.total_cmp(&b.0)));
+        found.dedup_by_key(|(_, record)| *record);
+
+        let mut suggestions: Vec<Suggestion> = found
+            .into_iter()
+            .map(|(distance, position)| {
+                let record = &self.records[position];
+                Suggestion {
+                    bic: Bic::from_parts(&record[column::BIC], &record[column::BRCH_CODE])
+                        .expect("Indexed BICs are valid"),
+                    distance,
+                    record,
+                }
+            })
+            .collect();
+        suggestions.sort_by(|a, b| a.distance.total_cmp(&b.distance).then(a.bic.cmp(&b.bic)));
+        suggestions.truncate(options.limit);
+        suggestions
+    }
+}
+
+/// Find the sorted `entries` within `max` of `input`, as their distance and
+/// record.
+///
+/// The distance is the optimal string alignment distance, the Levenshtein
+/// distance with swaps of neighbouring characters, with cheaper swaps and
+/// confusions. Its table has a row per character of the entry's code, and a
+/// row only depends on the code up to that character. Neighbouring entries
+/// share prefixes, so the rows for the prefix an entry shares with the one
+/// before are kept.
+fn search(entries: &[Entry], input: &[u8], max: f32) -> Vec<(f32, usize)> {
+    // Moving off the diagonal takes insertions or deletions, so cells further
+    // from it than this are out of reach
+    let band = (max / EDIT_COST) as usize;
+    if input.len() > band.saturating_add(11) {
+        return Vec::new();
+    }
+
+    // rows[i][j] is the distance between the code's first i characters and
+    // the input's first j, and row_mins[i] the smallest in rows[i]
+    let mut rows = vec![vec![f32::INFINITY; input.len() + 1]; 12];
+    let mut row_mins = [0.0; 12];
+    for (j, cost) in rows[0].iter_mut().enumerate().take(band.saturating_add(1)) {
+        *cost = j as f32 * EDIT_COST;
+    }
+
+    let mut found = Vec::new();
+    let mut previous: &[u8] = &[];
+    let mut computed = 0;
+    let mut index = 0;
+    while let Some(entry) = entries.get(index) {
+        let code = entry.code();
+        let shared = code
+            .iter()
+            .zip(previous)
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(computed);
+        computed = shared;
+        previous = code;
+
+        let mut out_of_reach = None;
+        for i in shared + 1..=code.len() {
+            let (done, rest) = rows.split_at_mut(i);
+            row_mins[i] = fill_row(
+                &mut rest[0],
+                &done[i - 1],
+                &done[i.saturating_sub(2)],
+                code,
+                i,
+                input,
+                band,
+            );
+            computed = i;
+            // Each row only builds on the two before it, and costs never
+            // decrease, so no entry starting with this prefix is in reach
+            if row_mins[i] > max && row_mins[i - 1] > max {
+                out_of_reach = Some(i);
+                break;
+            }
+        }
+
+        match out_of_reach {
+            Some(i) => {
+                index += skip(&entries[index..], |entry| {
+                    entry.len >= i && entry.code[..i] == code[..i]
+                });
+            }
+            None => {
+                let distance = rows[code.len()][input.len()];
+                if distance <= max {
+                    found.push((distance, entry.record));
+                }
+                index += 1;
+            }
+        }
+    }
+    found
+}
+
+/// Count the entries at the start of `entries` that `starts_with` holds for,
+/// galloping since they're usually few.
+fn skip(entries: &[Entry], starts_with: impl Fn(&Entry) -> bool) -> usize {
+    let mut end = 1;
+    while end < entries.len() && starts_with(&entries[end]) {
+        end *= 2;
+    }
+    let start = end / 2;
+    let end = end.min(entries.len());
+    start + entries[start..end].partition_point(&starts_with)
+}
+
+/// Fill `row`, the distances between `code[..i]` and each prefix of `input`,
+/// from the two rows before it, returning the smallest.
+fn fill_row(
+    row: &mut [f32],
+    previous: &[f32],
+    before_previous: &[f32],
+    code: &[u8],
+    i: usize,
+    input: &[u8],
+    band: usize,
+) -> f32 {
+    row.fill(f32::INFINITY);
+    if i <= band {
+        row[0] = i as f32 * EDIT_COST;
+    }
+    let mut min = row[0];
+    for j in i.saturating_sub(band).max(1)..=i.saturating_add(band).min(input.len()) {
+        let (a, b) = (code[i - 1], input[j - 1]);
+        let replace = if a == b {
+            0.0
+        } else if is_confusable(a, b) {
+            CONFUSION_COST
+        } else {
+            EDIT_COST
+        };
+        let mut cost = (previous[j - 1] + replace)
+            .min(previous[j] + EDIT_COST)
+            .min(row[j - 1] + EDIT_COST);
+        if i > 1 && j > 1 && a == input[j - 2] && code[i - 2] == b && a != b {
+            cost = cost.min(before_previous[j - 2] + TRANSPOSITION_COST);
+        }
+        row[j] = cost;
+        min = min.min(cost);
+    }
+    min
+}
+
+fn is_confusable(a: u8, b: u8) -> bool {
+    CONFUSABLE.contains(&(a, b)) || CONFUSABLE.contains(&(b, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract_table_from_bytes;
+    use std::path::PathBuf;
+
+    fn directory() -> Directory {
+        let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        let pdf = std::fs::read(fixture).expect("Failed to read PDF");
+        Directory::new(extract_table_from_bytes(&pdf).expect("Failed to extract table"))
+    }
+
+    #[test]
+    fn test_lookup() {
+        let directory = directory();
+        assert_eq!(directory.len(), 86);
+
+        let lookup = directory.lookup(" aaaa rs bg ").unwrap();
+        assert!(lookup.exists());
+        assert!(!lookup.is_branch());
+        assert_eq!(lookup.bic.as_str(), "AAAARSBGXXX");
+        assert_eq!(
+            lookup.record.unwrap()[column::FULL_LEGAL_NAME],
+            "YETTEL BANK AD"
+        );
+        assert_eq!(directory.lookup("AAAARSBGXXX").unwrap(), lookup);
+
+        let lookup = directory.lookup("aabafi22tet").unwrap();
+        assert!(lookup.exists());
+        assert!(lookup.is_branch());
+        assert_eq!(lookup.record.unwrap()[column::BRCH_CODE], "TET");
+    }
+
+    #[test]
+    fn test_lookup_unknown() {
+        let directory = directory();
+
+        let lookup = directory.lookup("AAAARSBG001").unwrap();
+        assert!(!lookup.exists());
+        assert!(lookup.is_branch());
+        assert_eq!(directory.lookup("AAAARSB"), Err(BicError::Length));
+        assert_eq!(directory.lookup("AAAA1SBG"), Err(BicError::CountryCode));
+    }
+
+    #[test]
+    fn test_distance() {
+        let distance = |input: &str, code: &str, max: f32| {
+            let found = search(&[Entry::new(code, 0)], input.as_bytes(), max);
+            found.first().map(|&(distance, _)| distance)
+        };
+
+        assert_eq!(distance("AAAARSBG", "AAAARSBG", 9.0), Some(0.0));
+        assert_eq!(distance("AAAARSGB", "AAAARSBG", 9.0), Some(0.5));
+        assert_eq!(distance("AAAARSBO", "AAAARSB0", 9.0), Some(0.25));
+        assert_eq!(distance("AAAAR5BG", "AAAARSBG", 9.0), Some(1.0));
+        assert_eq!(distance("AAAARSB", "AAAARSBG", 9.0), Some(1.0));
+        assert_eq!(distance("AAAASRBG", "AAAARSGB", 9.0), Some(1.0));
+        assert_eq!(distance("", "AAAARSBG", 9.0), Some(8.0));
+        assert_eq!(distance("AAAASRGB", "AAAARSBG", 1.0), Some(1.0));
+        assert_eq!(distance("AAAARSBG", "AAAARSBGXXX", 2.0), None);
+        assert_eq!(distance("AAAA", "AAAARSBG", 3.5), None);
+        assert_eq!(distance("BBBBRSBG", "AAAARSBG", 3.5), None);
+    }
+
+    #[test]
+    fn test_search_shares_prefixes() {
+        let codes = [
+            "AAAARSBG",
+            "AAAARSBG001",
+            "AAAARSBGXXX",
+            "AAABRSBG",
+            "BBBBRSBG",
+        ];
+        let entries: Vec<Entry> = codes
+            .iter()
+            .enumerate()
+            .map(|(record, code)| Entry::new(code, record))
+            .collect();
+
+        assert_eq!(search(&entries, b"AAAARSBG00", 1.0), [(1.0, 1)]);
+        assert_eq!(search(&entries, b"AAAARSBG", 1.0), [(0.0, 0), (1.0, 3)]);
+        assert_eq!(search(&entries, b"BBBBRSBG", 0.0), [(0.0, 4)]);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let directory = directory();
+        let bics = |suggestions: Vec<Suggestion>| -> Vec<(String, f32)> {
+            suggestions
+                .into_iter()
+                .map(|suggestion| (suggestion.bic.to_string(), suggestion.distance))
+                .collect()
+        };
+
+        let suggestions = directory.suggest("aaaa rs gb", &SuggestOptions::default());
+        assert_eq!(suggestions[0].bic.as_str(), "AAAARSBGXXX");
+        assert_eq!(suggestions[0].distance, 0.5);
+        assert_eq!(
+            suggestions[0].record[column::FULL_LEGAL_NAME],
+            "YETTEL BANK AD"
+        );
+        assert!(suggestions.len() <= 5);
+        assert!(suggestions
+            .windows(2)
+            .all(|pair| pair[0].distance <= pair[1].distance));
+
+        let options = SuggestOptions {
+            limit: 1,
+            ..SuggestOptions::default()
+        };
+        assert_eq!(
+            bics(directory.suggest("AABAFI22TET", &options)),
+            [("AABAFI22TET".to_string(), 0.0)]
+        );
+        assert_eq!(
+            bics(directory.suggest("AABAF122TET", &options)),
+            [("AABAFI22TET".to_string(), 0.25)]
+        );
+        // An invalid country code
+        assert_eq!(
+            bics(directory.suggest("AAAAR5BG", &options)),
+            [("AAAARSBGXXX".to_string(), 1.0)]
+        );
+        assert!(directory
+            .suggest("ZZZZZZZZ", &SuggestOptions::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_suggest_same_country() {
+        let directory = directory();
+        let options = SuggestOptions {
+            limit: 100,
+            max_distance: 4.0,
+            same_country: true,
+        };
+
+        let suggestions = directory.suggest("AAAAKWBG", &options);
+        assert!(!suggestions.is_empty());
+        assert!(suggestions
+            .iter()
+            .all(|suggestion| suggestion.bic.country_code() == "KW"));
+        assert!(directory.suggest("AAAA", &options).is_empty());
+    }
+
+    #[test]
+    fn test_skips_invalid_and_duplicate_bics() {
+        let record = |bic: &str, branch_code: &str, name: &str| {
+            let mut record = vec![String::new(); crate::HEADERS.len()];
+            record[column::BIC] = bic.to_string();
+            record[column::BRCH_CODE] = branch_code.to_string();
+            record[column::FULL_LEGAL_NAME] = name.to_string();
+            record
+        };
+        let directory = Directory::new(vec![
+            record("AAAARSBG", "XXX", "FIRST"),
+            record("AAAARSBG", "XXX", "SECOND"),
+            record("AAAARSB", "XXX", "INVALID"),
+            vec![],
+        ]);
+
+        assert_eq!(directory.len(), 1);
+        assert_eq!(directory.records().len(), 4);
+        let record = directory.lookup("AAAARSBG").unwrap().record.unwrap();
+        assert_eq!(record[column::FULL_LEGAL_NAME], "FIRST");
+    }
+}
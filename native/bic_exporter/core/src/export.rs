@@ -0,0 +1,53 @@
+//! Writing extracted records to other formats.
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod csv;
+pub mod fixed_width;
+pub mod json;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod postgres;
+pub mod sanitize;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod xlsx;
+
+use std::io::{self, Write};
+
+/// Names of the fields in a record for formats with named fields, matching
+/// [`crate::HEADERS`]. Unlike the headers, they are stable identifiers.
+pub const FIELDS: [&str; 10] = [
+    "record_creation_date",
+    "last_update_date",
+    "bic",
+    "branch_code",
+    "full_legal_name",
+    "registered_address",
+    "operational_address",
+    "branch_description",
+    "branch_address",
+    "institution_type",
+];
+
+/// Number of bytes `write` writes, so its destination can be allocated up
+/// front.
+pub fn output_len(write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<usize> {
+    let mut counter = ByteCounter(0);
+    write(&mut counter)?;
+    Ok(counter.0)
+}
+
+/// A writer that only counts what is written to it.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+//! Typed Arrow record batches and Arrow IPC files.
+//!
+//! Records become columns named by [`super::FIELDS`], with the dates as
+//! `Date32` and the institution type as a dictionary-encoded string. A
+//! dictionary-encoded `country` column, the ISO 3166 code in the BIC, follows
+//! `branch_code`. Empty fields, dates that don't parse and country codes that
+//! aren't in [`Country::all`] are null.
+//!
+//! Records are buffered into batches of [`BATCH_SIZE`] rows, so a directory
+//! streamed with [`crate::stream_table_from_bytes`] is never held in memory
+//! whole. Dictionaries keep their keys from one batch to the next, so each
+//! batch only adds the values it introduces.
+
+use super::FIELDS;
+use crate::{column, Country, Date};
+use anyhow::Result;
+use arrow_array::builder::{Date32Builder, StringBuilder, StringDictionaryBuilder};
+use arrow_array::types::Int16Type;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_ipc::writer::{DictionaryHandling, FileWriter, IpcWriteOptions};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Number of records in each batch written by [`IpcWriter`] and
+/// [`super::parquet::ParquetWriter`].
+pub const BATCH_SIZE: usize = 8192;
+
+/// Name of the column holding the country code from the BIC.
+pub const COUNTRY: &str = "country";
+
+/// Columns holding the record's fields as plain strings, in schema order.
+const TEXT_COLUMNS: [usize; 7] = [
+    column::BIC,
+    column::BRCH_CODE,
+    column::FULL_LEGAL_NAME,
+    column::REGISTERED_ADDRESS,
+    column::OPERATIONAL_ADDRESS,
+    column::BRANCH_DESCRIPTION,
+    column::BRANCH_ADDRESS,
+];
+
+/// The schema of the batches [`BatchBuilder`] builds.
+pub fn schema() -> SchemaRef {
+    let dictionary = || DataType::Dictionary(Box::new(DataType::Int16), Box::new(DataType::Utf8));
+    let field = |column: usize, data_type| Field::new(FIELDS[column], data_type, true);
+
+    Arc::new(Schema::new(vec![
+        field(column::RECORD_CREATION_DATE, DataType::Date32),
+        field(column::LAST_UPDATE_DATE, DataType::Date32),
+        field(column::BIC, DataType::Utf8),
+        field(column::BRCH_CODE, DataType::Utf8),
+        Field::new(COUNTRY, dictionary(), true),
+        field(column::FULL_LEGAL_NAME, DataType::Utf8),
+        field(column::REGISTERED_ADDRESS, DataType::Utf8),
+        field(column::OPERATIONAL_ADDRESS, DataType::Utf8),
+        field(column::BRANCH_DESCRIPTION, DataType::Utf8),
+        field(column::BRANCH_ADDRESS, DataType::Utf8),
+        field(column::INSTIT_TYPE, dictionary()),
+    ]))
+}
+
+/// Accumulates records into Arrow columns.
+pub struct BatchBuilder {
+    schema: SchemaRef,
+    dates: [Date32Builder; 2],
+    text: [StringBuilder; TEXT_COLUMNS.len()],
+    country: StringDictionaryBuilder<Int16Type>,
+    institution_type: StringDictionaryBuilder<Int16Type>,
+    len: usize,
+}
+
+impl Default for BatchBuilder {
+    fn default() -> Self {
+        BatchBuilder {
+            schema: schema(),
+            dates: Default::default(),
+            text: Default::default(),
+            country: StringDictionaryBuilder::new(),
+            institution_type: StringDictionaryBuilder::new(),
+            len: 0,
+        }
+    }
+}
+
+impl BatchBuilder {
+    pub fn new() -> Self {
+        BatchBuilder::default()
+    }
+
+    /// Add a record to the current batch.
+    ///
+    /// Fails if a dictionary column exceeds 32767 distinct values.
+    pub fn append<S: AsRef<str>>(&mut self, record: &[S]) -> Result<()> {
+        let field = |column: usize| record.get(column).map_or("", |field| field.as_ref());
+
+        for (builder, column) in self
+            .dates
+            .iter_mut()
+            .zip([column::RECORD_CREATION_DATE, column::LAST_UPDATE_DATE])
+        {
+            let days = Date::parse(field(column)).map(|date| date.days_since_epoch() as i32);
+            builder.append_option(days);
+        }
+        for (builder, column) in self.text.iter_mut().zip(TEXT_COLUMNS) {
+            builder.append_option(Some(field(column)).filter(|value| !value.is_empty()));
+        }
+        match Country::from_bic(field(column::BIC)) {
+            Some(country) => {
+                self.country.append(country.code)?;
+            }
+            None => self.country.append_null(),
+        }
+        match field(column::INSTIT_TYPE) {
+            "" => self.institution_type.append_null(),
+            value => {
+                self.institution_type.append(value)?;
+            }
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Number of records in the current batch.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Build the current batch and start a new one.
+    pub fn finish(&mut self) -> Result<RecordBatch> {
+        let [creation_date, update_date] = &mut self.dates;
+        let [bic, branch_code, rest @ ..] = &mut self.text;
+
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(creation_date.finish()),
+            Arc::new(update_date.finish()),
+            Arc::new(bic.finish()),
+            Arc::new(branch_code.finish()),
+            Arc::new(self.country.finish_preserve_values()),
+        ];
+        columns.extend(
+            rest.iter_mut()
+                .map(|builder| Arc::new(builder.finish()) as ArrayRef),
+        );
+        columns.push(Arc::new(self.institution_type.finish_preserve_values()));
+
+        self.len = 0;
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+/// Build a single batch holding `records`.
+pub fn records_to_batch<S: AsRef<str>>(records: &[Vec<S>]) -> Result<RecordBatch> {
+    let mut builder = BatchBuilder::new();
+    for record in records {
+        builder.append(record)?;
+    }
+    builder.finish()
+}
+
+/// Writes records to an Arrow IPC file as they are given, a batch at a time.
+pub struct IpcWriter<W: Write> {
+    builder: BatchBuilder,
+    writer: FileWriter<W>,
+}
+
+impl<W: Write> IpcWriter<W> {
+    /// Start an Arrow IPC file, writing its schema.
+    pub fn new(writer: W) -> Result<Self> {
+        let builder = BatchBuilder::new();
+        let options =
+            IpcWriteOptions::default().with_dictionary_handling(DictionaryHandling::Delta);
+        let writer = FileWriter::try_new_with_options(writer, &builder.schema, options)?;
+        Ok(IpcWriter { builder, writer })
+    }
+
+    /// Add a record, writing a batch once [`BATCH_SIZE`] records are buffered.
+    pub fn write_record<S: AsRef<str>>(&mut self, record: &[S]) -> Result<()> {
+        self.builder.append(record)?;
+        if self.builder.len() >= BATCH_SIZE {
+            self.writer.write(&self.builder.finish()?)?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffered records and the file's footer, and return the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        if !self.builder.is_empty() {
+            self.writer.write(&self.builder.finish()?)?;
+        }
+        self.writer.finish()?;
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+/// Write `records` as an Arrow IPC file to `writer`, returning the writer.
+pub fn write_ipc<W: Write, S: AsRef<str>>(records: &[Vec<S>], writer: W) -> Result<W> {
+    let mut ipc = IpcWriter::new(writer)?;
+    for record in records {
+        ipc.write_record(record)?;
+    }
+    ipc.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Date32Type;
+    use arrow_array::{Array, ArrayAccessor};
+    use arrow_ipc::reader::FileReader;
+    use std::io::Cursor;
+
+    fn record(bic: &str, created: &str, institution_type: &str) -> Vec<String> {
+        let mut record = vec![String::new(); FIELDS.len()];
+        record[column::RECORD_CREATION_DATE] = created.to_string();
+        record[column::LAST_UPDATE_DATE] = "2024-06-06".to_string();
+        record[column::BIC] = bic.to_string();
+        record[column::BRCH_CODE] = "XXX".to_string();
+        record[column::FULL_LEGAL_NAME] = format!("{} BANK", bic);
+        record[column::INSTIT_TYPE] = institution_type.to_string();
+        record
+    }
+
+    #[test]
+    fn test_records_to_batch() {
+        let records = [
+            record("AAAARSBG", "1997-03-01", "FIIN"),
+            record("AAACKWKW", "not a date", ""),
+            record("aaa", "", "CORP"),
+        ];
+
+        let batch = records_to_batch(&records).unwrap();
+
+        assert_eq!(batch.schema(), schema());
+        assert_eq!(batch.num_rows(), 3);
+
+        let created = batch.column(0).as_primitive::<Date32Type>();
+        assert_eq!(
+            created.value(0) as i64,
+            Date::new(1997, 3, 1).unwrap().days_since_epoch()
+        );
+        assert!(created.is_null(1) && created.is_null(2));
+
+        let country = batch
+            .column_by_name(COUNTRY)
+            .unwrap()
+            .as_dictionary::<Int16Type>();
+        let countries = country.downcast_dict::<arrow_array::StringArray>().unwrap();
+        assert_eq!(countries.value(0), "RS");
+        assert_eq!(countries.value(1), "KW");
+        assert!(country.is_null(2));
+
+        let institution_type = batch.column(10).as_dictionary::<Int16Type>();
+        assert_eq!(institution_type.values().len(), 2);
+        assert!(institution_type.is_null(1));
+
+        let description = batch.column_by_name("branch_description").unwrap();
+        assert_eq!(description.null_count(), 3);
+    }
+
+    #[test]
+    fn test_write_ipc_round_trips_across_batches() {
+        // Each batch brings countries and institution types the earlier ones
+        // didn't have, which are written as dictionary deltas
+        let countries = ["RS", "KW", "FR", "BG"];
+        let institution_types = ["FIIN", "CORP", "BANK"];
+        let records: Vec<Vec<String>> = (0..BATCH_SIZE * 2 + 10)
+            .map(|index| {
+                let batch = index / BATCH_SIZE;
+                let country = countries[index % (batch + 2)];
+                let bic = format!("AAAA{}{:02}", country, index % 100);
+                record(&bic, "2014-12-06", institution_types[index % (batch + 1)])
+            })
+            .collect();
+
+        let file = write_ipc(&records, Vec::new()).unwrap();
+        let batches: Vec<RecordBatch> = FileReader::try_new(Cursor::new(file), None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            batches
+                .iter()
+                .map(|batch| batch.num_rows())
+                .collect::<Vec<_>>(),
+            [BATCH_SIZE, BATCH_SIZE, 10]
+        );
+        let last = batches[2].column(2).as_string::<i32>();
+        assert_eq!(last.value(9), records.last().unwrap()[column::BIC]);
+
+        // A file's reader applies every delta before the first batch
+        let mut records = records.iter();
+        for batch in &batches {
+            let country = batch.column(4).as_dictionary::<Int16Type>();
+            assert_eq!(country.values().len(), 4);
+            let institution_type = batch.column(10).as_dictionary::<Int16Type>();
+            assert_eq!(institution_type.values().len(), 3);
+
+            let country = country.downcast_dict::<arrow_array::StringArray>().unwrap();
+            let institution_type = institution_type
+                .downcast_dict::<arrow_array::StringArray>()
+                .unwrap();
+            for row in 0..batch.num_rows() {
+                let record = records.next().unwrap();
+                assert_eq!(country.value(row), &record[column::BIC][4..6]);
+                assert_eq!(institution_type.value(row), record[column::INSTIT_TYPE]);
+            }
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! CSV export with [`HEADERS`] as the header row.
+//!
+//! Fields are written straight to the underlying writer as records come in,
+//! without formatting a record or the whole document in memory first. With the
+//! default options the output matches the directory's reference CSV: comma
+//! separated, quoted only where needed, with `\n` line endings and no byte
+//! order mark.
+
+use super::output_len;
+use super::sanitize::sanitize_field;
+use crate::HEADERS;
+use std::io::{self, Write};
+
+/// UTF-8 byte order mark, which Excel needs to read a CSV file as UTF-8.
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// When fields are enclosed in double quotes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum QuoteStyle {
+    /// Only fields containing the delimiter, a quote or a line break.
+    #[default]
+    Necessary,
+    /// Every field.
+    Always,
+}
+
+/// The sequence ending each row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub(crate) fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// How records are written as CSV.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field separator, which must be an ASCII character other than a quote
+    /// or line break. `;` suits Excel in locales with a decimal comma.
+    pub delimiter: u8,
+    pub quote_style: QuoteStyle,
+    pub line_ending: LineEnding,
+    /// Start with a UTF-8 byte order mark, so Excel detects the encoding.
+    pub bom: bool,
+    /// Write [`HEADERS`] as the first row.
+    pub header: bool,
+    /// Neutralize fields a spreadsheet could evaluate as formulas, see
+    /// [`super::sanitize`]. [`super::sanitize::formula_fields`] lists them.
+    pub sanitize: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            quote_style: QuoteStyle::default(),
+            line_ending: LineEnding::default(),
+            bom: false,
+            header: true,
+            sanitize: false,
+        }
+    }
+}
+
+/// Writes records as CSV rows to `W` as they are given.
+///
+/// Each field is written with its own call, so wrap files and sockets in a
+/// [`std::io::BufWriter`].
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    options: CsvOptions,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Start a CSV document, writing the byte order mark and header row when
+    /// `options` ask for them.
+    pub fn new(writer: W, options: CsvOptions) -> io::Result<Self> {
+        if !options.delimiter.is_ascii() || matches!(options.delimiter, b'"' | b'\r' | b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid CSV delimiter {:?}", options.delimiter as char),
+            ));
+        }
+
+        let mut csv = CsvWriter { writer, options };
+        if csv.options.bom {
+            csv.writer.write_all(BOM)?;
+        }
+        if csv.options.header {
+            csv.write_record(&HEADERS)?;
+        }
+        Ok(csv)
+    }
+
+    /// Write one record as a row.
+    pub fn write_record<S: AsRef<str>>(&mut self, record: &[S]) -> io::Result<()> {
+        for (index, field) in record.iter().enumerate() {
+            if index > 0 {
+                self.writer.write_all(&[self.options.delimiter])?;
+            }
+            self.write_field(field.as_ref())?;
+        }
+        self.writer.write_all(self.options.line_ending.as_bytes())
+    }
+
+    fn write_field(&mut self, field: &str) -> io::Result<()> {
+        let field = if self.options.sanitize {
+            sanitize_field(field)
+        } else {
+            field.into()
+        };
+        let delimiter = self.options.delimiter;
+        let quote = match self.options.quote_style {
+            QuoteStyle::Always => true,
+            QuoteStyle::Necessary => field
+                .bytes()
+                .any(|b| b == delimiter || matches!(b, b'"' | b'\r' | b'\n')),
+        };
+        if !quote {
+            return self.writer.write_all(field.as_bytes());
+        }
+
+        self.writer.write_all(b"\"")?;
+        for (index, part) in field.split('"').enumerate() {
+            if index > 0 {
+                self.writer.write_all(b"\"\"")?;
+            }
+            self.writer.write_all(part.as_bytes())?;
+        }
+        self.writer.write_all(b"\"")
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Write `records` as a CSV document to `writer`, returning the writer.
+///
+/// Fields may be borrowed, e.g. `Vec<Vec<&str>>`, as well as owned.
+pub fn write_csv<W: Write, S: AsRef<str>>(
+    records: &[Vec<S>],
+    writer: W,
+    options: &CsvOptions,
+) -> io::Result<W> {
+    let mut csv = CsvWriter::new(writer, options.clone())?;
+    for record in records {
+        csv.write_record(record)?;
+    }
+    csv.into_inner()
+}
+
+/// Size in bytes of the document [`write_csv`] writes, so its destination
+/// can be allocated up front.
+pub fn csv_len<S: AsRef<str>>(records: &[Vec<S>], options: &CsvOptions) -> io::Result<usize> {
+    output_len(|writer| write_csv(records, writer, options).map(drop))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn to_string<S: AsRef<str>>(records: &[Vec<S>], options: &CsvOptions) -> String {
+        String::from_utf8(write_csv(records, Vec::new(), options).unwrap()).unwrap()
+    }
+
+    fn record(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    #[test]
+    fn test_write_csv_matches_expected_fixture() {
+        let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures");
+        let pdf = std::fs::read(fixtures.join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        let expected =
+            std::fs::read(fixtures.join("ISOBIC-mini-expected.csv")).expect("Failed to read CSV");
+
+        let records = crate::extract_table_from_bytes(pdf).expect("Failed to extract table");
+        let options = CsvOptions::default();
+        let actual = write_csv(&records, Vec::new(), &options).unwrap();
+
+        assert!(actual == expected, "CSV differs from the expected fixture");
+        assert_eq!(csv_len(&records, &options).unwrap(), expected.len());
+    }
+
+    #[test]
+    fn test_quoting() {
+        let options = CsvOptions {
+            header: false,
+            ..CsvOptions::default()
+        };
+        let records = [record(&["plain", "a,b", "say \"hi\"", "two\nlines", ""])];
+
+        assert_eq!(
+            to_string(&records, &options),
+            "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\n"
+        );
+
+        let always = CsvOptions {
+            quote_style: QuoteStyle::Always,
+            ..options
+        };
+        assert_eq!(to_string(&[record(&["a", ""])], &always), "\"a\",\"\"\n");
+    }
+
+    #[test]
+    fn test_delimiter_line_ending_and_bom() {
+        let options = CsvOptions {
+            delimiter: b';',
+            line_ending: LineEnding::CrLf,
+            bom: true,
+            header: false,
+            ..CsvOptions::default()
+        };
+
+        assert_eq!(
+            to_string(&[record(&["a,b", "c;d"]), record(&["e"])], &options),
+            "\u{FEFF}a,b;\"c;d\"\r\ne\r\n"
+        );
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let records = [record(&["=1+1", "-", "\t@x", "a=b"])];
+        let options = CsvOptions {
+            header: false,
+            ..CsvOptions::default()
+        };
+
+        assert_eq!(to_string(&records, &options), "=1+1,-,\t@x,a=b\n");
+
+        let sanitized = CsvOptions {
+            sanitize: true,
+            ..options
+        };
+        assert_eq!(to_string(&records, &sanitized), "'=1+1,'-,'\t@x,a=b\n");
+    }
+
+    #[test]
+    fn test_header_row() {
+        let csv = to_string(&[] as &[Vec<String>], &CsvOptions::default());
+        assert_eq!(csv, format!("{}\n", HEADERS.join(",")));
+    }
+
+    #[test]
+    fn test_invalid_delimiter() {
+        for delimiter in [b'"', b'\n', 0xE9] {
+            let options = CsvOptions {
+                delimiter,
+                ..CsvOptions::default()
+            };
+            let error = write_csv(&[] as &[Vec<&str>], Vec::new(), &options).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Fixed-width text records for legacy systems that import the older BIC
+//! directory text files.
+//!
+//! Each record is a line of fields padded to their width, in the order and
+//! with the widths a [`FixedWidthLayout`] gives, without separators or a
+//! header. Widths count characters, and control characters in values are
+//! written as spaces so a line break can't split a record.
+//!
+//! Values longer than their field are cut or rejected depending on the
+//! layout's [`Truncation`]. [`truncated_fields`] lists the values that are
+//! cut, so they can be checked against the full records.
+
+use super::csv::LineEnding;
+use super::{output_len, FIELDS};
+use crate::column;
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// The side of a field its padding goes on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum Align {
+    /// Values start at the field's first character, padded on the right.
+    #[default]
+    Left,
+    /// Values end at the field's last character, padded on the left.
+    Right,
+}
+
+/// What happens to a value longer than its field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum Truncation {
+    /// Keep the value's first characters, as many as fit.
+    #[default]
+    Truncate,
+    /// Fail, writing nothing more.
+    Error,
+}
+
+/// A field of the fixed-width record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    /// The record's field written here, as in [`crate::column`].
+    pub column: usize,
+    /// Width in characters.
+    pub width: usize,
+    pub align: Align,
+}
+
+impl FieldLayout {
+    pub fn new(column: usize, width: usize) -> Self {
+        FieldLayout {
+            column,
+            width,
+            align: Align::Left,
+        }
+    }
+}
+
+/// How records are laid out as fixed-width lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedWidthLayout {
+    /// The fields of each line, in order. A record's field may be left out
+    /// or appear more than once.
+    pub fields: Vec<FieldLayout>,
+    /// Character filling fields up to their width.
+    pub padding: char,
+    pub truncation: Truncation,
+    pub line_ending: LineEnding,
+}
+
+impl Default for FixedWidthLayout {
+    /// Every field in [`crate::HEADERS`] order, wide enough for the values
+    /// of recent editions, padded with spaces.
+    fn default() -> Self {
+        FixedWidthLayout {
+            fields: vec![
+                FieldLayout::new(column::RECORD_CREATION_DATE, 10),
+                FieldLayout::new(column::LAST_UPDATE_DATE, 10),
+                FieldLayout::new(column::BIC, 8),
+                FieldLayout::new(column::BRCH_CODE, 3),
+                FieldLayout::new(column::FULL_LEGAL_NAME, 105),
+                FieldLayout::new(column::REGISTERED_ADDRESS, 140),
+                FieldLayout::new(column::OPERATIONAL_ADDRESS, 140),
+                FieldLayout::new(column::BRANCH_DESCRIPTION, 70),
+                FieldLayout::new(column::BRANCH_ADDRESS, 140),
+                FieldLayout::new(column::INSTIT_TYPE, 4),
+            ],
+            padding: ' ',
+            truncation: Truncation::default(),
+            line_ending: LineEnding::default(),
+        }
+    }
+}
+
+impl FixedWidthLayout {
+    /// Length in characters of each line, without the line ending.
+    pub fn line_width(&self) -> usize {
+        self.fields.iter().map(|field| field.width).sum()
+    }
+
+    fn validate(&self) -> io::Result<()> {
+        let invalid = |message: String| Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+
+        if self.fields.is_empty() {
+            return invalid("The fixed-width layout has no fields".to_string());
+        }
+        if let Some(field) = self
+            .fields
+            .iter()
+            .find(|field| field.column >= FIELDS.len())
+        {
+            return invalid(format!(
+                "Invalid column {} in fixed-width layout",
+                field.column
+            ));
+        }
+        if self.padding.is_control() {
+            return invalid(format!("Invalid fixed-width padding {:?}", self.padding));
+        }
+        Ok(())
+    }
+}
+
+/// A value cut to fit its field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct TruncatedField {
+    /// 0-based index of the record.
+    pub record: usize,
+    /// 0-based index of the column, matching [`crate::HEADERS`].
+    pub column: usize,
+    /// The field's value as extracted.
+    pub value: String,
+}
+
+/// Write `records` as fixed-width lines to `writer`, returning the writer.
+///
+/// Fails with [`io::ErrorKind::InvalidData`] on a value longer than its
+/// field when the layout's truncation is [`Truncation::Error`].
+pub fn write_fixed_width<W: Write, S: AsRef<str>>(
+    records: &[Vec<S>],
+    mut writer: W,
+    layout: &FixedWidthLayout,
+) -> io::Result<W> {
+    layout.validate()?;
+    let mut padding = [0; 4];
+    let padding = layout.padding.encode_utf8(&mut padding).as_bytes();
+
+    for (index, record) in records.iter().enumerate() {
+        for field in &layout.fields {
+            let value = record.get(field.column).map_or("", |value| value.as_ref());
+            let len = value.chars().count();
+            if len > field.width && layout.truncation == Truncation::Error {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Record {}'s {} is longer than {} characters: {:?}",
+                        index, FIELDS[field.column], field.width, value
+                    ),
+                ));
+            }
+
+            let fill = field.width.saturating_sub(len);
+            if field.align == Align::Right {
+                write_repeated(&mut writer, padding, fill)?;
+            }
+            let end = value
+                .char_indices()
+                .nth(field.width)
+                .map_or(value.len(), |(end, _)| end);
+            writer.write_all(without_controls(&value[..end]).as_bytes())?;
+            if field.align == Align::Left {
+                write_repeated(&mut writer, padding, fill)?;
+            }
+        }
+        writer.write_all(layout.line_ending.as_bytes())?;
+    }
+    writer.flush()?;
+    Ok(writer)
+}
+
+/// Size in bytes of the lines [`write_fixed_width`] writes.
+pub fn fixed_width_len<S: AsRef<str>>(
+    records: &[Vec<S>],
+    layout: &FixedWidthLayout,
+) -> io::Result<usize> {
+    output_len(|writer| write_fixed_width(records, writer, layout).map(drop))
+}
+
+/// Find the values too long for their field in `layout`, in record order.
+pub fn truncated_fields<S: AsRef<str>>(
+    records: &[Vec<S>],
+    layout: &FixedWidthLayout,
+) -> Vec<TruncatedField> {
+    records
+        .iter()
+        .enumerate()
+        .flat_map(|(record, fields)| {
+            layout.fields.iter().filter_map(move |field| {
+                let value = fields.get(field.column)?.as_ref();
+                (value.chars().count() > field.width).then(|| TruncatedField {
+                    record,
+                    column: field.column,
+                    value: value.to_string(),
+                })
+            })
+        })
+        .collect()
+}
+
+fn without_controls(value: &str) -> Cow<'_, str> {
+    if value.contains(char::is_control) {
+        Cow::Owned(
+            value
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+fn write_repeated(writer: &mut impl Write, bytes: &[u8], count: usize) -> io::Result<()> {
+    for _ in 0..count {
+        writer.write_all(bytes)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract_table_from_bytes;
+    use std::path::PathBuf;
+
+    fn layout(truncation: Truncation) -> FixedWidthLayout {
+        FixedWidthLayout {
+            fields: vec![
+                FieldLayout::new(column::BIC, 8),
+                FieldLayout::new(column::BRCH_CODE, 3),
+                FieldLayout {
+                    align: Align::Right,
+                    ..FieldLayout::new(column::FULL_LEGAL_NAME, 12)
+                },
+                FieldLayout::new(column::INSTIT_TYPE, 5),
+            ],
+            padding: '.',
+            truncation,
+            line_ending: LineEnding::CrLf,
+        }
+    }
+
+    fn record(name: &str) -> Vec<String> {
+        let mut record = vec![String::new(); FIELDS.len()];
+        record[column::BIC] = "AAAARSBG".to_string();
+        record[column::BRCH_CODE] = "XXX".to_string();
+        record[column::FULL_LEGAL_NAME] = name.to_string();
+        record[column::INSTIT_TYPE] = "FIIN".to_string();
+        record
+    }
+
+    #[test]
+    fn test_write_fixed_width() {
+        let records = [
+            record("YETTEL BANK"),
+            record("ÉLÉ\nBANK"),
+            record("BANK OF EXAMPLE"),
+        ];
+        let layout = layout(Truncation::Truncate);
+
+        let lines = write_fixed_width(&records, Vec::new(), &layout).unwrap();
+
+        assert_eq!(
+            String::from_utf8(lines.clone()).unwrap(),
+            "AAAARSBGXXX.YETTEL BANKFIIN.\r\n\
+             AAAARSBGXXX....ÉLÉ BANKFIIN.\r\n\
+             AAAARSBGXXXBANK OF EXAMFIIN.\r\n"
+        );
+        assert_eq!(fixed_width_len(&records, &layout).unwrap(), lines.len());
+        assert_eq!(
+            truncated_fields(&records, &layout),
+            vec![TruncatedField {
+                record: 2,
+                column: column::FULL_LEGAL_NAME,
+                value: "BANK OF EXAMPLE".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_truncation_error() {
+        let layout = layout(Truncation::Error);
+
+        assert!(write_fixed_width(&[record("YETTEL BANK")], Vec::new(), &layout).is_ok());
+        let error = write_fixed_width(&[record("BANK OF EXAMPLE")], Vec::new(), &layout)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("full_legal_name"), "{}", error);
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        let records = [record("YETTEL BANK")];
+        for layout in [
+            FixedWidthLayout {
+                fields: Vec::new(),
+                ..FixedWidthLayout::default()
+            },
+            FixedWidthLayout {
+                fields: vec![FieldLayout::new(FIELDS.len(), 1)],
+                ..FixedWidthLayout::default()
+            },
+            FixedWidthLayout {
+                padding: '\n',
+                ..FixedWidthLayout::default()
+            },
+        ] {
+            let error = write_fixed_width(&records, Vec::new(), &layout)
+                .err()
+                .unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_default_layout_fits_fixture() {
+        let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        let pdf = std::fs::read(fixture).expect("Failed to read PDF");
+        let records = extract_table_from_bytes(&pdf).expect("Failed to extract table");
+        let layout = FixedWidthLayout::default();
+
+        let lines = write_fixed_width(&records, Vec::new(), &layout).unwrap();
+
+        assert!(truncated_fields(&records, &layout).is_empty());
+        let lines = String::from_utf8(lines).unwrap();
+        assert_eq!(lines.lines().count(), 86);
+        assert!(lines
+            .lines()
+            .all(|line| line.chars().count() == layout.line_width()));
+        assert!(lines.starts_with("1997-03-012024-06-06AAAARSBGXXXYETTEL BANK AD "));
+    }
+}
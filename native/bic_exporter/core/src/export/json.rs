@@ -0,0 +1,359 @@
+//! JSON and JSON Lines export, with records keyed by field name.
+//!
+//! Each record becomes an object keyed by [`FIELDS`], which unlike
+//! [`crate::HEADERS`] are stable identifiers rather than the directory's column
+//! titles, followed by the [`DERIVED_FIELDS`] naming the BIC's country, which
+//! are `null` for a country code that isn't in ISO 3166. The optional [`JsonHeader`] tells consumers which edition the
+//! records come from and which [`SCHEMA_VERSION`] they follow. [`SCHEMA`]
+//! describes both formats as JSON Schema.
+//!
+//! As with CSV, records are written to the underlying writer as they come in.
+
+use super::{output_len, FIELDS};
+use crate::{column, Country, Date};
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the field names and types, bumped whenever either changes.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Names of the fields derived from the BIC, written after [`FIELDS`]: the
+/// ISO 3166 country code and the country's English name.
+pub const DERIVED_FIELDS: [&str; 2] = ["country_code", "country_name"];
+
+/// JSON Schema of the documents [`write_json`] writes. Its `$defs` describe
+/// the lines [`write_ndjson`] writes.
+pub const SCHEMA: &str = include_str!("../../schema/bic-records.schema.json");
+
+/// Where and when records were extracted, written ahead of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonHeader {
+    /// Publication date of the directory, see [`crate::Metadata::edition`].
+    pub edition: Option<Date>,
+    /// When the records were extracted, written in UTC to the second.
+    pub extracted_at: SystemTime,
+}
+
+impl JsonHeader {
+    /// A header for records from `edition` extracted now.
+    pub fn new(edition: Option<Date>) -> Self {
+        JsonHeader {
+            edition,
+            extracted_at: SystemTime::now(),
+        }
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(
+            writer,
+            "{{\"schema_version\":{},\"edition\":",
+            SCHEMA_VERSION
+        )?;
+        match self.edition {
+            Some(edition) => write!(writer, "\"{}\"", edition)?,
+            None => writer.write_all(b"null")?,
+        }
+        write!(
+            writer,
+            ",\"extracted_at\":\"{}\"}}",
+            format_timestamp(self.extracted_at)
+        )
+    }
+}
+
+/// Writes records as JSON Lines to `W` as they are given, one object per line.
+///
+/// Wrap files and sockets in a [`std::io::BufWriter`].
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Start a JSON Lines stream, writing `header` as the first line as
+    /// `{"metadata":{...}}` when given.
+    pub fn new(mut writer: W, header: Option<&JsonHeader>) -> io::Result<Self> {
+        if let Some(header) = header {
+            writer.write_all(b"{\"metadata\":")?;
+            header.write(&mut writer)?;
+            writer.write_all(b"}\n")?;
+        }
+        Ok(NdjsonWriter { writer })
+    }
+
+    /// Write one record as a line.
+    pub fn write_record<S: AsRef<str>>(&mut self, record: &[S]) -> io::Result<()> {
+        write_record(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Write `records` as JSON Lines to `writer`, returning the writer.
+pub fn write_ndjson<W: Write, S: AsRef<str>>(
+    records: &[Vec<S>],
+    writer: W,
+    header: Option<&JsonHeader>,
+) -> io::Result<W> {
+    let mut ndjson = NdjsonWriter::new(writer, header)?;
+    for record in records {
+        ndjson.write_record(record)?;
+    }
+    ndjson.into_inner()
+}
+
+/// Write `records` as a JSON document to `writer`, returning the writer.
+///
+/// The document is an object with the `metadata` header, when given, and the
+/// `records` array, with each record on its own line.
+pub fn write_json<W: Write, S: AsRef<str>>(
+    records: &[Vec<S>],
+    mut writer: W,
+    header: Option<&JsonHeader>,
+) -> io::Result<W> {
+    writer.write_all(b"{")?;
+    if let Some(header) = header {
+        writer.write_all(b"\"metadata\":")?;
+        header.write(&mut writer)?;
+        writer.write_all(b",")?;
+    }
+    writer.write_all(b"\"records\":[")?;
+    for (index, record) in records.iter().enumerate() {
+        writer.write_all(if index > 0 { b",\n" } else { b"\n" })?;
+        write_record(&mut writer, record)?;
+    }
+    writer.write_all(b"\n]}\n")?;
+    writer.flush()?;
+    Ok(writer)
+}
+
+/// Size in bytes of the document [`write_json`] writes.
+pub fn json_len<S: AsRef<str>>(
+    records: &[Vec<S>],
+    header: Option<&JsonHeader>,
+) -> io::Result<usize> {
+    output_len(|writer| write_json(records, writer, header).map(drop))
+}
+
+/// Size in bytes of the stream [`write_ndjson`] writes.
+pub fn ndjson_len<S: AsRef<str>>(
+    records: &[Vec<S>],
+    header: Option<&JsonHeader>,
+) -> io::Result<usize> {
+    output_len(|writer| write_ndjson(records, writer, header).map(drop))
+}
+
+fn write_record<S: AsRef<str>>(writer: &mut impl Write, record: &[S]) -> io::Result<()> {
+    writer.write_all(b"{")?;
+    for (index, (name, field)) in FIELDS.iter().zip(record).enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        write!(writer, "\"{}\":", name)?;
+        write_string(writer, field.as_ref())?;
+    }
+
+    let bic = record.get(column::BIC).map_or("", |bic| bic.as_ref());
+    let country = Country::from_bic(bic);
+    let values = [
+        country.map(|country| country.code),
+        country.map(|country| country.name),
+    ];
+    for (name, value) in DERIVED_FIELDS.iter().zip(values) {
+        write!(writer, ",\"{}\":", name)?;
+        match value {
+            Some(value) => write_string(writer, value)?,
+            None => writer.write_all(b"null")?,
+        }
+    }
+    writer.write_all(b"}")
+}
+
+/// Write `value` as a JSON string, escaping quotes, backslashes and control
+/// characters.
+fn write_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    writer.write_all(b"\"")?;
+    let mut start = 0;
+    for (index, byte) in value.bytes().enumerate() {
+        let escape: Option<&[u8]> = match byte {
+            b'"' => Some(b"\\\""),
+            b'\\' => Some(b"\\\\"),
+            b'\n' => Some(b"\\n"),
+            b'\r' => Some(b"\\r"),
+            b'\t' => Some(b"\\t"),
+            0x00..=0x1F => None,
+            _ => continue,
+        };
+        writer.write_all(&value.as_bytes()[start..index])?;
+        match escape {
+            Some(escape) => writer.write_all(escape)?,
+            None => write!(writer, "\\u{:04x}", byte)?,
+        }
+        start = index + 1;
+    }
+    writer.write_all(&value.as_bytes()[start..])?;
+    writer.write_all(b"\"")
+}
+
+/// Format `time` as an RFC 3339 timestamp in UTC, e.g. `2025-12-05T09:30:00Z`.
+fn format_timestamp(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    };
+    let date = Date::from_days_since_epoch(seconds.div_euclid(86_400));
+    let time_of_day = seconds.rem_euclid(86_400);
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        date,
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn header() -> JsonHeader {
+        JsonHeader {
+            edition: Date::new(2025, 12, 4),
+            // 2025-12-05T09:30:00Z
+            extracted_at: UNIX_EPOCH + Duration::from_secs(1_764_927_000),
+        }
+    }
+
+    fn record(bic: &str, name: &str) -> Vec<String> {
+        let mut record = vec![String::new(); FIELDS.len()];
+        record[crate::column::BIC] = bic.to_string();
+        record[crate::column::FULL_LEGAL_NAME] = name.to_string();
+        record
+    }
+
+    #[test]
+    fn test_write_ndjson() {
+        let records = [record("AAAARSBG", "YETTEL BANK AD")];
+        let output = write_ndjson(&records, Vec::new(), Some(&header())).unwrap();
+        let lines: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(
+            lines[0],
+            json!({"metadata": {
+                "schema_version": 2,
+                "edition": "2025-12-04",
+                "extracted_at": "2025-12-05T09:30:00Z",
+            }})
+        );
+        assert_eq!(lines[1]["bic"], "AAAARSBG");
+        assert_eq!(lines[1]["full_legal_name"], "YETTEL BANK AD");
+        assert_eq!(lines[1]["branch_description"], "");
+        assert_eq!(lines[1]["country_code"], "RS");
+        assert_eq!(lines[1]["country_name"], "Serbia");
+        assert_eq!(lines.len(), 2);
+
+        let output = write_ndjson(&records, Vec::new(), None).unwrap();
+        assert!(output.starts_with(b"{\"record_creation_date\":\"\",\"last_update_date\""));
+    }
+
+    #[test]
+    fn test_write_json() {
+        let records = [record("AAAARSBG", "A"), record("AAACKWKW", "B")];
+
+        let output = write_json(&records, Vec::new(), None).unwrap();
+        let document: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(document.as_object().unwrap().len(), 1);
+        assert_eq!(document["records"][1]["bic"], "AAACKWKW");
+        assert_eq!(json_len(&records, None).unwrap(), output.len());
+
+        let header = JsonHeader {
+            edition: None,
+            ..header()
+        };
+        let output = write_json(&records, Vec::new(), Some(&header)).unwrap();
+        let document: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(document["metadata"]["edition"], Value::Null);
+        assert_eq!(document["records"].as_array().unwrap().len(), 2);
+
+        // Not a country in ISO 3166
+        let output = write_json(&[record("AAAAQQBG", "C")], Vec::new(), None).unwrap();
+        let document: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(document["records"][0]["country_code"], Value::Null);
+        assert_eq!(document["records"][0]["country_name"], Value::Null);
+
+        let empty = write_json(&[] as &[Vec<&str>], Vec::new(), None).unwrap();
+        assert_eq!(empty, b"{\"records\":[\n]}\n");
+    }
+
+    #[test]
+    fn test_escaping() {
+        let value = "say \"hi\"\\\n\t\u{1}é";
+        let mut output = Vec::new();
+        write_string(&mut output, value).unwrap();
+
+        assert_eq!(output, "\"say \\\"hi\\\"\\\\\\n\\t\\u0001é\"".as_bytes());
+        assert_eq!(serde_json::from_slice::<String>(&output).unwrap(), value);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(header().extracted_at),
+            "2025-12-05T09:30:00Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH - Duration::from_secs(1)),
+            "1969-12-31T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn test_schema_matches_output() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        let record = &schema["$defs"]["record"];
+        let required: Vec<&str> = record["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|name| name.as_str().unwrap())
+            .collect();
+        assert_eq!(required, [&FIELDS[..], &DERIVED_FIELDS].concat());
+        assert_eq!(
+            schema["$defs"]["metadata"]["properties"]["schema_version"]["const"],
+            SCHEMA_VERSION
+        );
+
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        let records = crate::extract_table_from_path(path).expect("Failed to extract table");
+        let output = write_json(&records, Vec::new(), Some(&header())).unwrap();
+        let document: Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(document["records"].as_array().unwrap().len(), 86);
+        for record in document["records"].as_array().unwrap() {
+            let names: Vec<&str> = record
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(|k| k.as_str())
+                .collect();
+            let mut expected = [&FIELDS[..], &DERIVED_FIELDS].concat();
+            expected.sort();
+            assert_eq!(names, expected);
+        }
+    }
+}
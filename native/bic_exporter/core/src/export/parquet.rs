@@ -0,0 +1,102 @@
+//! Parquet files, written from the Arrow record batches of [`super::arrow`].
+//!
+//! Column types are those of [`super::arrow::schema`], which is also stored
+//! in the file so Arrow readers get the dictionary-encoded columns back.
+//! Pages are compressed with Snappy, which warehouses read natively.
+
+use super::arrow::{schema, BatchBuilder, BATCH_SIZE};
+use anyhow::Result;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+
+/// Writes records to a Parquet file as they are given, a batch at a time.
+pub struct ParquetWriter<W: Write + Send> {
+    builder: BatchBuilder,
+    writer: ArrowWriter<W>,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Start a Parquet file.
+    pub fn new(writer: W) -> Result<Self> {
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(writer, schema(), Some(properties))?;
+        Ok(ParquetWriter {
+            builder: BatchBuilder::new(),
+            writer,
+        })
+    }
+
+    /// Add a record, writing a batch once [`BATCH_SIZE`] records are buffered.
+    pub fn write_record<S: AsRef<str>>(&mut self, record: &[S]) -> Result<()> {
+        self.builder.append(record)?;
+        if self.builder.len() >= BATCH_SIZE {
+            self.writer.write(&self.builder.finish()?)?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffered records and the file's footer, and return the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        if !self.builder.is_empty() {
+            self.writer.write(&self.builder.finish()?)?;
+        }
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+/// Write `records` as a Parquet file to `writer`, returning the writer.
+pub fn write_parquet<W: Write + Send, S: AsRef<str>>(records: &[Vec<S>], writer: W) -> Result<W> {
+    let mut parquet = ParquetWriter::new(writer)?;
+    for record in records {
+        parquet.write_record(record)?;
+    }
+    parquet.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{stream_table_from_bytes, ExtractOptions};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int16Type;
+    use arrow_array::{ArrayAccessor, RecordBatch};
+    use bytes::Bytes;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_stream_fixture_to_parquet() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        let pdf = std::fs::read(path).expect("Failed to read PDF");
+
+        let mut parquet = ParquetWriter::new(Vec::new()).unwrap();
+        stream_table_from_bytes(&pdf, &ExtractOptions::default(), |record| {
+            parquet.write_record(&record)
+        })
+        .unwrap();
+        let file = parquet.finish().unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(file))
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.schema(), schema());
+        assert_eq!(batch.num_rows(), 86);
+        assert_eq!(batch.column(2).as_string::<i32>().value(0), "AAAARSBG");
+        let country = batch.column(4).as_dictionary::<Int16Type>();
+        let countries = country.downcast_dict::<arrow_array::StringArray>().unwrap();
+        assert_eq!(countries.value(0), "RS");
+    }
+}
@@ -0,0 +1,273 @@
+//! PostgreSQL bulk loading, as `COPY ... FROM STDIN` data or an `INSERT`
+//! script.
+//!
+//! Both load the table [`create_table_sql`] defines, with columns named by
+//! [`FIELDS`] and the two dates typed `date`. As in the other typed exports,
+//! empty fields and dates that don't parse are null, so a damaged record
+//! can't make the whole load fail.
+
+use super::{output_len, FIELDS};
+use crate::{column, Date};
+use std::io::{self, Write};
+
+/// How an `INSERT` script is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlOptions {
+    /// Table to insert into, optionally schema-qualified as `schema.table`.
+    pub table: String,
+    /// Number of rows in each `INSERT` statement.
+    pub rows_per_insert: usize,
+    /// Start the script with [`create_table_sql`].
+    pub create_table: bool,
+}
+
+impl Default for SqlOptions {
+    fn default() -> Self {
+        SqlOptions {
+            table: "bic_records".to_string(),
+            rows_per_insert: 1000,
+            create_table: false,
+        }
+    }
+}
+
+/// `CREATE TABLE IF NOT EXISTS` statement for a table the exports load.
+pub fn create_table_sql(table: &str) -> String {
+    let columns: Vec<String> = FIELDS
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let data_type = match index {
+                column::RECORD_CREATION_DATE | column::LAST_UPDATE_DATE => "date",
+                _ => "text",
+            };
+            format!("  {} {}", quote_identifier(name), data_type)
+        })
+        .collect();
+
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n{}\n);\n",
+        quote_table(table),
+        columns.join(",\n")
+    )
+}
+
+/// The `COPY ... FROM STDIN` statement to send ahead of [`write_copy`] data.
+pub fn copy_statement(table: &str) -> String {
+    format!("COPY {} ({}) FROM STDIN", quote_table(table), column_list())
+}
+
+/// Write `records` as `COPY` data in PostgreSQL's text format to `writer`,
+/// returning the writer.
+///
+/// Fields are separated by tabs and nulls written as `\N`, with backslashes
+/// and control characters escaped.
+pub fn write_copy<W: Write, S: AsRef<str>>(records: &[Vec<S>], mut writer: W) -> io::Result<W> {
+    for record in records {
+        for (index, value) in values(record).enumerate() {
+            if index > 0 {
+                writer.write_all(b"\t")?;
+            }
+            match value {
+                Some(value) => write_copy_field(&mut writer, value)?,
+                None => writer.write_all(b"\\N")?,
+            }
+        }
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(writer)
+}
+
+/// Size in bytes of the data [`write_copy`] writes.
+pub fn copy_len<S: AsRef<str>>(records: &[Vec<S>]) -> io::Result<usize> {
+    output_len(|writer| write_copy(records, writer).map(drop))
+}
+
+/// Write `records` as a script of multi-row `INSERT` statements to `writer`,
+/// returning the writer.
+pub fn write_inserts<W: Write, S: AsRef<str>>(
+    records: &[Vec<S>],
+    mut writer: W,
+    options: &SqlOptions,
+) -> io::Result<W> {
+    if options.rows_per_insert == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "rows_per_insert must be at least 1",
+        ));
+    }
+
+    if options.create_table {
+        writer.write_all(create_table_sql(&options.table).as_bytes())?;
+    }
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES\n",
+        quote_table(&options.table),
+        column_list()
+    );
+    for statement in records.chunks(options.rows_per_insert) {
+        writer.write_all(insert.as_bytes())?;
+        for (row, record) in statement.iter().enumerate() {
+            writer.write_all(if row > 0 { b",\n(" } else { b"(" })?;
+            for (index, value) in values(record).enumerate() {
+                if index > 0 {
+                    writer.write_all(b", ")?;
+                }
+                match value {
+                    Some(value) => write_literal(&mut writer, value)?,
+                    None => writer.write_all(b"NULL")?,
+                }
+            }
+            writer.write_all(b")")?;
+        }
+        writer.write_all(b";\n")?;
+    }
+    writer.flush()?;
+    Ok(writer)
+}
+
+/// Size in bytes of the script [`write_inserts`] writes.
+pub fn inserts_len<S: AsRef<str>>(records: &[Vec<S>], options: &SqlOptions) -> io::Result<usize> {
+    output_len(|writer| write_inserts(records, writer, options).map(drop))
+}
+
+/// A record's values in [`FIELDS`] order, `None` where they load as null.
+fn values<S: AsRef<str>>(record: &[S]) -> impl Iterator<Item = Option<&str>> {
+    (0..FIELDS.len()).map(move |index| {
+        let value = record.get(index).map_or("", |field| field.as_ref());
+        let is_date = matches!(
+            index,
+            column::RECORD_CREATION_DATE | column::LAST_UPDATE_DATE
+        );
+        if value.is_empty() || (is_date && Date::parse(value).is_none()) {
+            None
+        } else {
+            Some(value)
+        }
+    })
+}
+
+fn write_copy_field(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    let mut start = 0;
+    for (index, byte) in value.bytes().enumerate() {
+        let escape: &[u8] = match byte {
+            b'\\' => b"\\\\",
+            b'\t' => b"\\t",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            0x08 => b"\\b",
+            0x0B => b"\\v",
+            0x0C => b"\\f",
+            _ => continue,
+        };
+        writer.write_all(&value.as_bytes()[start..index])?;
+        writer.write_all(escape)?;
+        start = index + 1;
+    }
+    writer.write_all(&value.as_bytes()[start..])
+}
+
+/// Write `value` as a string literal, for the default
+/// `standard_conforming_strings`, where only quotes need escaping.
+fn write_literal(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    writer.write_all(b"'")?;
+    for (index, part) in value.split('\'').enumerate() {
+        if index > 0 {
+            writer.write_all(b"''")?;
+        }
+        writer.write_all(part.as_bytes())?;
+    }
+    writer.write_all(b"'")
+}
+
+fn column_list() -> String {
+    FIELDS
+        .iter()
+        .map(|name| quote_identifier(name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Quote each part of a possibly schema-qualified table name.
+fn quote_table(table: &str) -> String {
+    table
+        .split('.')
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str) -> Vec<String> {
+        let mut record = vec![String::new(); FIELDS.len()];
+        record[column::RECORD_CREATION_DATE] = "1997-03-01".to_string();
+        record[column::LAST_UPDATE_DATE] = "2024-02-30".to_string();
+        record[column::BIC] = "AAAARSBG".to_string();
+        record[column::BRCH_CODE] = "XXX".to_string();
+        record[column::FULL_LEGAL_NAME] = name.to_string();
+        record[column::INSTIT_TYPE] = "FIIN".to_string();
+        record
+    }
+
+    #[test]
+    fn test_write_copy() {
+        let records = [record("YETTEL BANK AD"), record("A\\B\tC\nD\rE")];
+
+        let copy = write_copy(&records, Vec::new()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(copy.clone()).unwrap(),
+            "1997-03-01\t\\N\tAAAARSBG\tXXX\tYETTEL BANK AD\t\\N\t\\N\t\\N\t\\N\tFIIN\n\
+             1997-03-01\t\\N\tAAAARSBG\tXXX\tA\\\\B\\tC\\nD\\rE\t\\N\t\\N\t\\N\t\\N\tFIIN\n"
+        );
+        assert_eq!(copy_len(&records).unwrap(), copy.len());
+    }
+
+    #[test]
+    fn test_copy_statement() {
+        assert_eq!(
+            copy_statement("public.bic_records"),
+            "COPY \"public\".\"bic_records\" (\"record_creation_date\", \"last_update_date\", \
+             \"bic\", \"branch_code\", \"full_legal_name\", \"registered_address\", \
+             \"operational_address\", \"branch_description\", \"branch_address\", \
+             \"institution_type\") FROM STDIN"
+        );
+    }
+
+    #[test]
+    fn test_write_inserts() {
+        let records = [record("O'BRIEN BANK"), record("B"), record("C")];
+        let options = SqlOptions {
+            table: "bics".to_string(),
+            rows_per_insert: 2,
+            create_table: true,
+        };
+
+        let script =
+            String::from_utf8(write_inserts(&records, Vec::new(), &options).unwrap()).unwrap();
+
+        assert!(script.starts_with(&create_table_sql("bics")));
+        assert!(create_table_sql("bics").contains("  \"last_update_date\" date,\n"));
+        assert_eq!(script.matches("INSERT INTO \"bics\" (").count(), 2);
+        assert!(script.contains(
+            "VALUES\n('1997-03-01', NULL, 'AAAARSBG', 'XXX', 'O''BRIEN BANK', \
+             NULL, NULL, NULL, NULL, 'FIIN'),\n('1997-03-01'"
+        ));
+        assert!(script.ends_with("'C', NULL, NULL, NULL, NULL, 'FIIN');\n"));
+        assert_eq!(inserts_len(&records, &options).unwrap(), script.len());
+
+        let options = SqlOptions {
+            rows_per_insert: 0,
+            ..SqlOptions::default()
+        };
+        assert!(write_inserts(&records, Vec::new(), &options).is_err());
+    }
+}
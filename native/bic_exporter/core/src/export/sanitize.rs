@@ -0,0 +1,94 @@
+//! Protection against formula injection in spreadsheet exports.
+//!
+//! Names and addresses come from an external PDF. Spreadsheet applications
+//! evaluate a cell starting with `=`, `+`, `-` or `@` as a formula, and a
+//! leading tab or carriage return can hide such a prefix from a quick look, so
+//! a hostile entry could run a formula when an exported file is opened.
+//! Prefixing those cells with `'` makes spreadsheets treat them as text. XLSX
+//! exports give them Excel's quote prefix format instead, to the same effect.
+
+use std::borrow::Cow;
+
+/// Characters that make a spreadsheet evaluate a cell.
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// A field that was neutralized because it could be evaluated as a formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct SanitizedField {
+    /// 0-based index of the record.
+    pub record: usize,
+    /// 0-based index of the column, matching [`crate::HEADERS`].
+    pub column: usize,
+    /// The field's value as extracted.
+    pub value: String,
+}
+
+/// Whether a spreadsheet could evaluate `field` as a formula.
+pub fn is_formula(field: &str) -> bool {
+    field.starts_with(FORMULA_PREFIXES)
+}
+
+/// Neutralize `field` if a spreadsheet could evaluate it as a formula.
+pub fn sanitize_field(field: &str) -> Cow<'_, str> {
+    if is_formula(field) {
+        Cow::Owned(format!("'{}", field))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Find the fields that sanitizing neutralizes, in record order.
+pub fn formula_fields<S: AsRef<str>>(records: &[Vec<S>]) -> Vec<SanitizedField> {
+    records
+        .iter()
+        .enumerate()
+        .flat_map(|(record, fields)| {
+            fields
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| is_formula(field.as_ref()))
+                .map(move |(column, field)| SanitizedField {
+                    record,
+                    column,
+                    value: field.as_ref().to_string(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_field() {
+        for field in ["=1+1", "+1", "-1", "@SUM(A1)", "\t=1", "\r=1"] {
+            assert_eq!(sanitize_field(field), format!("'{}", field));
+        }
+        for field in ["", "ABN AMRO", "1-2 MAIN ST", " =1", "'=1"] {
+            assert!(
+                matches!(sanitize_field(field), Cow::Borrowed(_)),
+                "{:?}",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn test_formula_fields() {
+        let records = vec![
+            vec!["AAAARSBG", "YETTEL BANK AD"],
+            vec!["AAACKWKW", "=HYPERLINK(\"http://example.com\")"],
+        ];
+
+        assert_eq!(
+            formula_fields(&records),
+            vec![SanitizedField {
+                record: 1,
+                column: 1,
+                value: "=HYPERLINK(\"http://example.com\")".to_string(),
+            }]
+        );
+    }
+}
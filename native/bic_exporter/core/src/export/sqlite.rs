@@ -0,0 +1,253 @@
+//! SQLite databases of the directory, a single file to query offline.
+//!
+//! Records go into the `bic_records` table, with columns named by
+//! [`super::FIELDS`] plus the 11-character `bic11` and the `country` code from
+//! the BIC. As in the Arrow export, empty fields, dates that don't parse and
+//! unknown country codes are null. Dates are `YYYY-MM-DD` text, which sorts
+//! and compares as dates.
+//!
+//! `bic_records_fts` is an FTS5 index over the name and addresses, and the
+//! one-row `metadata` table describes the edition the records come from:
+//!
+//! ```sql
+//! SELECT bic11, full_legal_name FROM bic_records
+//! WHERE id IN (
+//!     SELECT rowid FROM bic_records_fts WHERE bic_records_fts MATCH 'yettel'
+//! );
+//! ```
+
+use crate::{column, Country, Date, Metadata};
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// Version of the database's tables, stored in `metadata.schema_version`
+/// and bumped whenever they change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const TABLES: &str = "
+CREATE TABLE bic_records (
+    id INTEGER PRIMARY KEY,
+    record_creation_date TEXT,
+    last_update_date TEXT,
+    bic TEXT,
+    branch_code TEXT,
+    bic11 TEXT GENERATED ALWAYS AS (bic || branch_code) STORED,
+    country TEXT,
+    full_legal_name TEXT,
+    registered_address TEXT,
+    operational_address TEXT,
+    branch_description TEXT,
+    branch_address TEXT,
+    institution_type TEXT
+);
+
+CREATE TABLE metadata (
+    schema_version INTEGER NOT NULL,
+    edition TEXT,
+    pages INTEGER,
+    title TEXT,
+    created TEXT,
+    modified TEXT,
+    extracted_at TEXT NOT NULL,
+    records INTEGER NOT NULL
+);
+";
+
+/// Built once the records are in, which is faster than updating them row
+/// by row.
+const INDEXES: &str = "
+CREATE INDEX bic_records_bic ON bic_records (bic);
+CREATE INDEX bic_records_bic11 ON bic_records (bic11);
+CREATE INDEX bic_records_country ON bic_records (country);
+CREATE INDEX bic_records_full_legal_name ON bic_records (full_legal_name COLLATE NOCASE);
+
+CREATE VIRTUAL TABLE bic_records_fts USING fts5 (
+    full_legal_name,
+    registered_address,
+    operational_address,
+    branch_address,
+    content = 'bic_records',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+INSERT INTO bic_records_fts (bic_records_fts) VALUES ('rebuild');
+";
+
+/// Write `records` to a new SQLite database at `path`, replacing any file
+/// already there.
+///
+/// `metadata`, as read by [`crate::metadata_from_bytes`], fills in the
+/// `metadata` table's edition and PDF details; without it they are null.
+pub fn write_sqlite<S: AsRef<str>>(
+    records: &[Vec<S>],
+    path: impl AsRef<Path>,
+    metadata: Option<&Metadata>,
+) -> Result<()> {
+    let path = path.as_ref();
+    match std::fs::remove_file(path) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+            return Err(error).with_context(|| format!("Failed to replace {}", path.display()));
+        }
+        _ => {}
+    }
+
+    let mut connection =
+        Connection::open(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(TABLES)?;
+
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO bic_records (
+                record_creation_date, last_update_date, bic, branch_code, country,
+                full_legal_name, registered_address, operational_address,
+                branch_description, branch_address, institution_type
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?;
+        for record in records {
+            let field = |column: usize| {
+                record
+                    .get(column)
+                    .map(|field| field.as_ref())
+                    .filter(|field| !field.is_empty())
+            };
+            let date = |column: usize| field(column).filter(|field| Date::parse(field).is_some());
+
+            insert.execute(params![
+                date(column::RECORD_CREATION_DATE),
+                date(column::LAST_UPDATE_DATE),
+                field(column::BIC),
+                field(column::BRCH_CODE),
+                field(column::BIC)
+                    .and_then(Country::from_bic)
+                    .map(|country| country.code),
+                field(column::FULL_LEGAL_NAME),
+                field(column::REGISTERED_ADDRESS),
+                field(column::OPERATIONAL_ADDRESS),
+                field(column::BRANCH_DESCRIPTION),
+                field(column::BRANCH_ADDRESS),
+                field(column::INSTIT_TYPE),
+            ])?;
+        }
+    }
+
+    transaction.execute_batch(INDEXES)?;
+    let date = |date: Option<Date>| date.map(|date| date.to_string());
+    transaction.execute(
+        "INSERT INTO metadata VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), ?7
+        )",
+        params![
+            SCHEMA_VERSION,
+            date(metadata.and_then(|metadata| metadata.edition)),
+            metadata.map(|metadata| metadata.pages as i64),
+            metadata.and_then(|metadata| metadata.title.as_deref()),
+            date(metadata.and_then(|metadata| metadata.created)),
+            date(metadata.and_then(|metadata| metadata.modified)),
+            records.len() as i64,
+        ],
+    )?;
+    transaction.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract_table_from_bytes, metadata_from_bytes, ExtractOptions};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_write_sqlite_from_fixture() {
+        let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        let pdf = std::fs::read(fixture).expect("Failed to read PDF");
+        let records = extract_table_from_bytes(&pdf).expect("Failed to extract table");
+        let metadata = metadata_from_bytes(&pdf, &ExtractOptions::default()).unwrap();
+        let path = std::env::temp_dir().join(format!("bic-exporter-{}.sqlite", std::process::id()));
+
+        std::fs::write(&path, b"replaced").unwrap();
+        let written = write_sqlite(&records, &path, Some(&metadata));
+        let connection = Connection::open(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        written.unwrap();
+
+        let count: i64 = connection
+            .query_row("SELECT COUNT(*) FROM bic_records", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 86);
+
+        let (name, country, created): (String, String, String) = connection
+            .query_row(
+                "SELECT full_legal_name, country, record_creation_date
+                 FROM bic_records WHERE bic11 = 'AAAARSBGXXX'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (name.as_str(), country.as_str(), created.as_str()),
+            ("YETTEL BANK AD", "RS", "1997-03-01")
+        );
+
+        let plan: String = connection
+            .query_row(
+                "EXPLAIN QUERY PLAN SELECT * FROM bic_records WHERE bic11 = 'AAAARSBGXXX'",
+                [],
+                |row| row.get(3),
+            )
+            .unwrap();
+        assert!(plan.contains("bic_records_bic11"), "{}", plan);
+
+        let bic: String = connection
+            .query_row(
+                "SELECT bic FROM bic_records WHERE id IN
+                 (SELECT rowid FROM bic_records_fts WHERE bic_records_fts MATCH 'yettel')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(bic, "AAAARSBG");
+
+        let (version, edition, records): (u32, String, i64) = connection
+            .query_row(
+                "SELECT schema_version, edition, records FROM metadata",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (version, edition.as_str(), records),
+            (SCHEMA_VERSION, "2025-12-04", 86)
+        );
+    }
+
+    #[test]
+    fn test_empty_fields_and_invalid_dates_are_null() {
+        let path =
+            std::env::temp_dir().join(format!("bic-exporter-{}-nulls.sqlite", std::process::id()));
+        let record = ["2025-02-30", "", "aaaa", "XXX", "NAME", "", "", "", "", ""];
+
+        let written = write_sqlite(&[record.to_vec()], &path, None);
+        let connection = Connection::open(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        written.unwrap();
+
+        let nulls: (Option<String>, Option<String>, Option<String>) = connection
+            .query_row(
+                "SELECT record_creation_date, country, branch_description FROM bic_records",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(nulls, (None, None, None));
+
+        let edition: Option<String> = connection
+            .query_row("SELECT edition FROM metadata", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(edition, None);
+    }
+}
@@ -0,0 +1,525 @@
+//! XLSX workbooks, written as SpreadsheetML parts in a zip archive without
+//! needing an office suite.
+//!
+//! Each sheet starts with [`HEADERS`] as a bold header row, frozen in place
+//! and with an auto-filter over the records. Dates are date-formatted serial
+//! numbers, so they sort and filter as dates, and other fields are inline
+//! text. Empty fields leave their cell out, and dates that don't parse are
+//! kept as text.
+//!
+//! A sheet holds at most [`MAX_ROWS`] rows, Excel's limit. Records past it
+//! continue on another sheet with its own header row.
+
+use super::sanitize::is_formula;
+use crate::{column, Date, HEADERS};
+use anyhow::{ensure, Result};
+use std::io::{Seek, Write};
+use zip::write::{SimpleFileOptions, ZipWriter};
+use zip::CompressionMethod;
+
+/// Maximum number of rows in a sheet, header included.
+pub const MAX_ROWS: usize = 1_048_576;
+
+/// Name of the first sheet. Later sheets are numbered from 2.
+const SHEET_NAME: &str = "BIC directory";
+
+/// Column widths in characters, wide enough for the headers and most values.
+const WIDTHS: [u32; HEADERS.len()] = [22, 18, 12, 12, 45, 50, 50, 30, 50, 13];
+
+/// Days from Excel's day 0, 1899-12-31 counted as 1900-01-00, to 1970-01-01,
+/// including the nonexistent 1900-02-29 Excel keeps for Lotus compatibility.
+const EPOCH_SERIAL: i64 = 25_569;
+
+/// Serial number of 1900-03-01, the first date after that phantom leap day.
+/// Earlier dates are written as text rather than off by one.
+const FIRST_SERIAL: i64 = 61;
+
+/// Indexes of the cell formats in [`STYLES`].
+mod style {
+    pub const DATE: u32 = 1;
+    pub const HEADER: u32 = 2;
+    /// Text entered with a leading `'`, which Excel never evaluates.
+    pub const QUOTED: u32 = 3;
+}
+
+const CONTENT_TYPE_WORKSHEET: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml";
+const RELATIONSHIP_TYPES: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+
+const ROOT_RELATIONSHIPS: &str = "\
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" \
+Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" \
+Target=\"xl/workbook.xml\"/>\
+</Relationships>";
+
+const STYLES: &str = "\
+<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+<numFmts count=\"1\"><numFmt numFmtId=\"164\" formatCode=\"yyyy\\-mm\\-dd\"/></numFmts>\
+<fonts count=\"2\">\
+<font><sz val=\"11\"/><name val=\"Calibri\"/><family val=\"2\"/></font>\
+<font><b/><sz val=\"11\"/><name val=\"Calibri\"/><family val=\"2\"/></font>\
+</fonts>\
+<fills count=\"2\">\
+<fill><patternFill patternType=\"none\"/></fill>\
+<fill><patternFill patternType=\"gray125\"/></fill>\
+</fills>\
+<borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>\
+<cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>\
+<cellXfs count=\"4\">\
+<xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>\
+<xf numFmtId=\"164\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyNumberFormat=\"1\"/>\
+<xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/>\
+<xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" quotePrefix=\"1\"/>\
+</cellXfs>\
+<cellStyles count=\"1\"><cellStyle name=\"Normal\" xfId=\"0\" builtinId=\"0\"/></cellStyles>\
+</styleSheet>";
+
+/// Everything in a sheet ahead of its rows: the frozen header pane and the
+/// column widths.
+const SHEET_START: &str = "\
+<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+<sheetViews><sheetView workbookViewId=\"0\">\
+<pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>\
+<selection pane=\"bottomLeft\" activeCell=\"A2\" sqref=\"A2\"/>\
+</sheetView></sheetViews>";
+
+/// How records are written as XLSX.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XlsxOptions {
+    /// Mark fields a spreadsheet could evaluate as formulas as text, see
+    /// [`super::sanitize`]. Instead of prefixing them with `'`, their cells
+    /// get Excel's quote prefix format, which keeps the value unchanged.
+    pub sanitize: bool,
+    /// Maximum number of rows in each sheet, header included, from 2 to
+    /// [`MAX_ROWS`].
+    pub rows_per_sheet: usize,
+}
+
+impl Default for XlsxOptions {
+    fn default() -> Self {
+        XlsxOptions {
+            sanitize: false,
+            rows_per_sheet: MAX_ROWS,
+        }
+    }
+}
+
+/// Writes records to an XLSX workbook as they are given.
+///
+/// Sheets are compressed as they are written, so only the workbook's list of
+/// sheets is held until [`XlsxWriter::finish`].
+pub struct XlsxWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    options: XlsxOptions,
+    /// Number of rows written to each sheet, header included.
+    sheets: Vec<usize>,
+}
+
+impl<W: Write + Seek> XlsxWriter<W> {
+    /// Start a workbook, writing the first sheet's header row.
+    pub fn new(writer: W, options: &XlsxOptions) -> Result<Self> {
+        ensure!(
+            (2..=MAX_ROWS).contains(&options.rows_per_sheet),
+            "rows_per_sheet must be from 2 to {}",
+            MAX_ROWS
+        );
+
+        let mut xlsx = XlsxWriter {
+            zip: ZipWriter::new(writer),
+            options: options.clone(),
+            sheets: Vec::new(),
+        };
+        xlsx.start_sheet()?;
+        Ok(xlsx)
+    }
+
+    /// Add a record as a row, starting a new sheet when the current one is
+    /// full.
+    pub fn write_record<S: AsRef<str>>(&mut self, record: &[S]) -> Result<()> {
+        if self.sheets.last() == Some(&self.options.rows_per_sheet) {
+            self.end_sheet()?;
+            self.start_sheet()?;
+        }
+        let row = self.sheets.last().unwrap() + 1;
+
+        write!(self.zip, "<row r=\"{}\">", row)?;
+        for (index, field) in record.iter().take(HEADERS.len()).enumerate() {
+            let field = field.as_ref();
+            if field.is_empty() {
+                continue;
+            }
+            let is_date = matches!(
+                index,
+                column::RECORD_CREATION_DATE | column::LAST_UPDATE_DATE
+            );
+            let serial = Date::parse(field)
+                .filter(|_| is_date)
+                .map(|date| date.days_since_epoch() + EPOCH_SERIAL)
+                .filter(|serial| *serial >= FIRST_SERIAL);
+
+            match serial {
+                Some(serial) => write!(
+                    self.zip,
+                    "<c r=\"{}{}\" s=\"{}\"><v>{}</v></c>",
+                    column_name(index),
+                    row,
+                    style::DATE,
+                    serial
+                )?,
+                None => {
+                    let style = if self.options.sanitize && is_formula(field) {
+                        Some(style::QUOTED)
+                    } else {
+                        None
+                    };
+                    write_text_cell(&mut self.zip, index, row, field, style)?;
+                }
+            }
+        }
+        self.zip.write_all(b"</row>")?;
+
+        *self.sheets.last_mut().unwrap() = row;
+        Ok(())
+    }
+
+    /// Complete the last sheet and the workbook's other parts, and return the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.end_sheet()?;
+        let options = file_options();
+
+        self.zip.start_file("[Content_Types].xml", options)?;
+        self.zip.write_all(XML_DECLARATION.as_bytes())?;
+        self.zip.write_all(
+            b"<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+              <Default Extension=\"rels\" \
+              ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+              <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+              <Override PartName=\"/xl/workbook.xml\" \
+              ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+              <Override PartName=\"/xl/styles.xml\" \
+              ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+        )?;
+        for sheet in 1..=self.sheets.len() {
+            write!(
+                self.zip,
+                "<Override PartName=\"/xl/worksheets/sheet{}.xml\" ContentType=\"{}\"/>",
+                sheet, CONTENT_TYPE_WORKSHEET
+            )?;
+        }
+        self.zip.write_all(b"</Types>")?;
+
+        self.zip.start_file("_rels/.rels", options)?;
+        self.zip.write_all(XML_DECLARATION.as_bytes())?;
+        self.zip.write_all(ROOT_RELATIONSHIPS.as_bytes())?;
+
+        self.zip.start_file("xl/_rels/workbook.xml.rels", options)?;
+        self.zip.write_all(XML_DECLARATION.as_bytes())?;
+        self.zip.write_all(
+            b"<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+        )?;
+        for sheet in 1..=self.sheets.len() {
+            write!(
+                self.zip,
+                "<Relationship Id=\"rId{0}\" Type=\"{1}/worksheet\" \
+                 Target=\"worksheets/sheet{0}.xml\"/>",
+                sheet, RELATIONSHIP_TYPES
+            )?;
+        }
+        write!(
+            self.zip,
+            "<Relationship Id=\"rId{}\" Type=\"{}/styles\" Target=\"styles.xml\"/>\
+             </Relationships>",
+            self.sheets.len() + 1,
+            RELATIONSHIP_TYPES
+        )?;
+
+        self.zip.start_file("xl/workbook.xml", options)?;
+        self.zip.write_all(XML_DECLARATION.as_bytes())?;
+        write!(
+            self.zip,
+            "<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+             xmlns:r=\"{}\"><bookViews><workbookView/></bookViews><sheets>",
+            RELATIONSHIP_TYPES
+        )?;
+        for sheet in 1..=self.sheets.len() {
+            write!(
+                self.zip,
+                "<sheet name=\"{}\" sheetId=\"{1}\" r:id=\"rId{1}\"/>",
+                sheet_name(sheet),
+                sheet
+            )?;
+        }
+        // Excel keeps each auto-filter's range in a hidden name too
+        self.zip.write_all(b"</sheets><definedNames>")?;
+        for (index, rows) in self.sheets.iter().enumerate() {
+            write!(
+                self.zip,
+                "<definedName name=\"_xlnm._FilterDatabase\" localSheetId=\"{}\" hidden=\"1\">\
+                 '{}'!$A$1:${}${}</definedName>",
+                index,
+                sheet_name(index + 1),
+                column_name(HEADERS.len() - 1),
+                rows
+            )?;
+        }
+        self.zip.write_all(b"</definedNames></workbook>")?;
+
+        self.zip.start_file("xl/styles.xml", options)?;
+        self.zip.write_all(XML_DECLARATION.as_bytes())?;
+        self.zip.write_all(STYLES.as_bytes())?;
+
+        Ok(self.zip.finish()?)
+    }
+
+    fn start_sheet(&mut self) -> Result<()> {
+        let name = format!("xl/worksheets/sheet{}.xml", self.sheets.len() + 1);
+        self.zip.start_file(name, file_options())?;
+        self.zip.write_all(XML_DECLARATION.as_bytes())?;
+        self.zip.write_all(SHEET_START.as_bytes())?;
+
+        self.zip.write_all(b"<cols>")?;
+        for (index, width) in WIDTHS.iter().enumerate() {
+            write!(
+                self.zip,
+                "<col min=\"{0}\" max=\"{0}\" width=\"{1}\" customWidth=\"1\"/>",
+                index + 1,
+                width
+            )?;
+        }
+        self.zip.write_all(b"</cols><sheetData><row r=\"1\">")?;
+        for (index, header) in HEADERS.iter().enumerate() {
+            write_text_cell(&mut self.zip, index, 1, header, Some(style::HEADER))?;
+        }
+        self.zip.write_all(b"</row>")?;
+
+        self.sheets.push(1);
+        Ok(())
+    }
+
+    fn end_sheet(&mut self) -> Result<()> {
+        let rows = *self.sheets.last().unwrap();
+        write!(
+            self.zip,
+            "</sheetData><autoFilter ref=\"A1:{}{}\"/></worksheet>",
+            column_name(HEADERS.len() - 1),
+            rows
+        )?;
+        Ok(())
+    }
+}
+
+/// Write `records` as an XLSX workbook to `writer`, returning the writer.
+pub fn write_xlsx<W: Write + Seek, S: AsRef<str>>(
+    records: &[Vec<S>],
+    writer: W,
+    options: &XlsxOptions,
+) -> Result<W> {
+    let mut xlsx = XlsxWriter::new(writer, options)?;
+    for record in records {
+        xlsx.write_record(record)?;
+    }
+    xlsx.finish()
+}
+
+fn file_options() -> SimpleFileOptions {
+    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated)
+}
+
+/// The 1-based `sheet`'s name.
+fn sheet_name(sheet: usize) -> String {
+    if sheet == 1 {
+        SHEET_NAME.to_string()
+    } else {
+        format!("{} {}", SHEET_NAME, sheet)
+    }
+}
+
+/// Letter naming the column at `index`, which is below 26.
+fn column_name(index: usize) -> char {
+    char::from(b'A' + index as u8)
+}
+
+fn write_text_cell(
+    writer: &mut impl Write,
+    index: usize,
+    row: usize,
+    value: &str,
+    style: Option<u32>,
+) -> Result<()> {
+    write!(writer, "<c r=\"{}{}\"", column_name(index), row)?;
+    if let Some(style) = style {
+        write!(writer, " s=\"{}\"", style)?;
+    }
+    // Without xml:space, leading and trailing whitespace would be dropped
+    let space = if value.starts_with(char::is_whitespace) || value.ends_with(char::is_whitespace) {
+        " xml:space=\"preserve\""
+    } else {
+        ""
+    };
+    write!(writer, " t=\"inlineStr\"><is><t{}>", space)?;
+    write_text(writer, value)?;
+    writer.write_all(b"</t></is></c>")?;
+    Ok(())
+}
+
+/// Write `value` as XML text.
+///
+/// Control characters XML can't hold are written as SpreadsheetML's
+/// `_xHHHH_` escapes, which means an `_` starting something that reads like
+/// one has to be escaped as well.
+fn write_text(writer: &mut impl Write, value: &str) -> std::io::Result<()> {
+    let mut start = 0;
+    for (index, char) in value.char_indices() {
+        let escape = match char {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '\t' | '\n' | '\r' => continue,
+            '\0'..='\x1F' | '\u{FFFE}' | '\u{FFFF}' => format!("_x{:04X}_", u32::from(char)),
+            '_' if is_escape(&value[index..]) => "_x005F_".to_string(),
+            _ => continue,
+        };
+        writer.write_all(&value.as_bytes()[start..index])?;
+        writer.write_all(escape.as_bytes())?;
+        start = index + char.len_utf8();
+    }
+    writer.write_all(&value.as_bytes()[start..])
+}
+
+/// Whether `value` starts with an `_xHHHH_` escape.
+fn is_escape(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() >= 7
+        && bytes[1] == b'x'
+        && bytes[2..6].iter().all(u8::is_ascii_hexdigit)
+        && bytes[6] == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract_table_from_bytes;
+    use std::io::{Cursor, Read};
+    use std::path::PathBuf;
+    use zip::ZipArchive;
+
+    fn read_part(xlsx: &[u8], name: &str) -> String {
+        let mut archive = ZipArchive::new(Cursor::new(xlsx)).unwrap();
+        let mut part = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut part)
+            .unwrap();
+        part
+    }
+
+    fn record(name: &str) -> Vec<String> {
+        let mut record = vec![String::new(); HEADERS.len()];
+        record[column::RECORD_CREATION_DATE] = "1997-03-01".to_string();
+        record[column::LAST_UPDATE_DATE] = "2024-02-30".to_string();
+        record[column::BIC] = "AAAARSBG".to_string();
+        record[column::BRCH_CODE] = "XXX".to_string();
+        record[column::FULL_LEGAL_NAME] = name.to_string();
+        record
+    }
+
+    #[test]
+    fn test_write_xlsx_from_fixture() {
+        let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("ISOBIC-mini.pdf");
+        let pdf = std::fs::read(fixture).expect("Failed to read PDF");
+        let records = extract_table_from_bytes(&pdf).expect("Failed to extract table");
+
+        let xlsx = write_xlsx(&records, Cursor::new(Vec::new()), &XlsxOptions::default())
+            .unwrap()
+            .into_inner();
+
+        let sheet = read_part(&xlsx, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(
+            "<row r=\"1\"><c r=\"A1\" s=\"2\" t=\"inlineStr\"><is><t>Record creation date</t>"
+        ));
+        assert!(sheet.contains("state=\"frozen\""));
+        assert!(sheet.contains("<col min=\"5\" max=\"5\" width=\"45\" customWidth=\"1\"/>"));
+        assert!(sheet.ends_with("</sheetData><autoFilter ref=\"A1:J87\"/></worksheet>"));
+        assert_eq!(sheet.matches("<row ").count(), 87);
+        // 1997-03-01, the first record's creation date
+        assert!(sheet.contains("<c r=\"A2\" s=\"1\"><v>35490</v></c>"));
+
+        let workbook = read_part(&xlsx, "xl/workbook.xml");
+        assert!(workbook.contains("<sheet name=\"BIC directory\" sheetId=\"1\" r:id=\"rId1\"/>"));
+        assert!(workbook.contains("'BIC directory'!$A$1:$J$87"));
+        assert!(read_part(&xlsx, "[Content_Types].xml").contains("/xl/worksheets/sheet1.xml"));
+    }
+
+    #[test]
+    fn test_write_xlsx_splits_sheets() {
+        let records: Vec<Vec<String>> = (0..5).map(|index| record(&index.to_string())).collect();
+        let options = XlsxOptions {
+            rows_per_sheet: 3,
+            ..XlsxOptions::default()
+        };
+
+        let xlsx = write_xlsx(&records, Cursor::new(Vec::new()), &options)
+            .unwrap()
+            .into_inner();
+
+        for (sheet, rows) in [(1, 3), (2, 3), (3, 2)] {
+            let part = read_part(&xlsx, &format!("xl/worksheets/sheet{}.xml", sheet));
+            assert_eq!(part.matches("<row ").count(), rows);
+            assert!(part.contains(">Record creation date<"));
+            assert!(part.contains(&format!("<autoFilter ref=\"A1:J{}\"/>", rows)));
+        }
+        let third = read_part(&xlsx, "xl/worksheets/sheet3.xml");
+        assert!(third.contains("<c r=\"E2\" t=\"inlineStr\"><is><t>4</t></is></c>"));
+
+        let workbook = read_part(&xlsx, "xl/workbook.xml");
+        assert!(workbook.contains("<sheet name=\"BIC directory 3\" sheetId=\"3\" r:id=\"rId3\"/>"));
+        let relationships = read_part(&xlsx, "xl/_rels/workbook.xml.rels");
+        assert!(relationships.contains("Id=\"rId4\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\""));
+
+        let options = XlsxOptions {
+            rows_per_sheet: MAX_ROWS + 1,
+            ..XlsxOptions::default()
+        };
+        assert!(write_xlsx(&records, Cursor::new(Vec::new()), &options).is_err());
+    }
+
+    #[test]
+    fn test_cells() {
+        let records = [
+            record("=HYPERLINK(\"http://example.com\")"),
+            record(" A & B <C>\u{1}_x0041_"),
+        ];
+        let sanitized = XlsxOptions {
+            sanitize: true,
+            ..XlsxOptions::default()
+        };
+
+        let xlsx = write_xlsx(&records, Cursor::new(Vec::new()), &XlsxOptions::default())
+            .unwrap()
+            .into_inner();
+        let sheet = read_part(&xlsx, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains("<c r=\"E2\" t=\"inlineStr\"><is><t>=HYPERLINK("));
+        // An impossible date stays text, and empty fields have no cell
+        assert!(sheet.contains("<c r=\"B2\" t=\"inlineStr\"><is><t>2024-02-30</t>"));
+        assert!(!sheet.contains("r=\"F2\""));
+        assert!(sheet.contains(
+            "<c r=\"E3\" t=\"inlineStr\"><is><t xml:space=\"preserve\"> \
+             A &amp; B &lt;C&gt;_x0001__x005F_x0041_</t></is></c>"
+        ));
+
+        let xlsx = write_xlsx(&records, Cursor::new(Vec::new()), &sanitized)
+            .unwrap()
+            .into_inner();
+        let sheet = read_part(&xlsx, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains("<c r=\"E2\" s=\"3\" t=\"inlineStr\"><is><t>=HYPERLINK("));
+    }
+}
@@ -0,0 +1,1749 @@
+//! # BIC Exporter
+//!
+//! Extracts BIC (Bank Identifier Code) directory data from ISO 9362 PDF files.
+//!
+//! This crate holds the extraction itself and has no dependency on the BEAM.
+//! The `bic_exporter` crate wraps it as an Erlang NIF. With the `rustler`
+//! feature, the public report and error types also derive rustler's encoders.
+//!
+//! ## Why Position-Based Extraction?
+//!
+//! The ISO BIC directory PDF doesn't use standard PDF table structures that
+//! libraries can easily parse. Instead, it renders text at specific X/Y
+//! coordinates. We must:
+//!
+//! 1. Extract each text fragment with its position
+//! 2. Group fragments into rows by Y coordinate (with tolerance for slight variations)
+//! 3. Assign fragments to columns based on X position and table line boundaries
+//!
+//! ## Multi-Line Record Handling
+//!
+//! Address fields (registered address, operational address, branch address) often
+//! span multiple lines in the PDF. A single BIC record may occupy 2-4 visual rows.
+//! We detect record boundaries by looking for the creation date (YYYY-MM-DD format)
+//! in the first column - only new records start with a date. Rows without a date
+//! are continuation rows that get merged into the current record.
+
+use anyhow::{Context, Result};
+use pdf::content::{Op, TextDrawAdjusted};
+use pdf::file::FileOptions;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::time::Instant;
+
+mod archive;
+mod bic;
+mod content;
+mod country;
+mod date;
+pub mod debug;
+mod directory;
+pub mod export;
+mod limits;
+mod metadata;
+mod repair;
+mod report;
+
+pub use bic::{Bic, BicError, PRIMARY_OFFICE};
+pub use country::Country;
+pub use date::Date;
+pub use directory::{Directory, Lookup, SuggestOptions, Suggestion};
+pub use limits::{Limit, LimitExceeded, Limits};
+pub use metadata::{metadata_from_bytes, Metadata};
+pub use report::{
+    ColumnDrift, ExtractionReport, InvalidBic, InvalidDate, IssueKind, MissingFields, OrphanRow,
+    OutOfGridText, PageError, PageRecordCount, QualityError, QualityIssue, SortViolation,
+    SortViolationKind, UnknownCountry,
+};
+
+// PDF text extraction constants
+//
+// These values are tuned for the ISO BIC directory PDF format.
+// The PDF uses specific spacing and layout that these constants accommodate.
+
+/// Default line height when TextNewline operator doesn't specify leading.
+const DEFAULT_LINE_HEIGHT: f32 = 12.0;
+
+/// Threshold for detecting word spaces in TJ operator arrays.
+/// PDF TJ arrays use negative values for kerning; values below this threshold
+/// indicate intentional word spacing rather than letter kerning.
+const SPACE_THRESHOLD: f32 = -100.0;
+
+/// PDF text spacing is specified in thousandths of the text space unit.
+const SPACING_DIVISOR: f32 = 1000.0;
+
+/// Tolerance for grouping text elements into the same row.
+/// Text fragments within this Y-distance are considered part of the same line.
+const Y_TOLERANCE: f32 = 3.0;
+
+/// Tolerance for detecting vertical lines (table column separators).
+const VERTICAL_LINE_TOLERANCE: f32 = 1.0;
+
+/// Tolerance for deduplicating detected vertical lines.
+const LINE_DEDUP_TOLERANCE: f32 = 2.0;
+
+/// Required number of column boundaries (10 columns = 11 boundaries including end marker)
+const REQUIRED_BOUNDARIES: usize = 11;
+
+pub const HEADERS: [&str; 10] = [
+    "Record creation date",
+    "Last Update date",
+    "BIC",
+    "Brch Code",
+    "Full legal name",
+    "Registered address",
+    "Operational address",
+    "Branch description",
+    "Branch address",
+    "Instit. Type",
+];
+
+/// Positions of the fields within a record, matching [`HEADERS`].
+pub mod column {
+    pub const RECORD_CREATION_DATE: usize = 0;
+    pub const LAST_UPDATE_DATE: usize = 1;
+    pub const BIC: usize = 2;
+    pub const BRCH_CODE: usize = 3;
+    pub const FULL_LEGAL_NAME: usize = 4;
+    pub const REGISTERED_ADDRESS: usize = 5;
+    pub const OPERATIONAL_ADDRESS: usize = 6;
+    pub const BRANCH_DESCRIPTION: usize = 7;
+    pub const BRANCH_ADDRESS: usize = 8;
+    pub const INSTIT_TYPE: usize = 9;
+}
+
+/// A text element extracted from PDF with its page position.
+///
+/// PDFs don't have a concept of "cells" or "rows" - they just place text
+/// at specific coordinates. We extract each text fragment with its position
+/// so we can later reconstruct the table structure.
+#[derive(Debug, Clone)]
+struct TextElement {
+    text: String,
+    x: f32,
+    y: f32,
+}
+
+/// A reconstructed row of text elements, grouped by Y coordinate.
+///
+/// After extracting individual text elements, we group them by their Y position
+/// (with tolerance) to form logical rows. Within each row, cells are sorted
+/// by X position for left-to-right reading order.
+#[derive(Debug, Clone)]
+struct TableRow {
+    y: f32,
+    cells: Vec<(f32, String)>, // (x_position, text)
+}
+
+/// Decode a PDF string to UTF-8.
+///
+/// PDF strings can be encoded as UTF-16BE (with BOM) or PDFDocEncoding/Latin-1.
+/// The BIC directory uses UTF-16BE for text with special characters
+/// (accented names, non-ASCII addresses).
+fn decode_pdf_string(text: &pdf::primitive::PdfString) -> String {
+    let bytes = text.as_bytes();
+
+    if bytes.len() >= 2 && bytes[0] == 0xFE && bytes[1] == 0xFF {
+        // UTF-16BE with BOM
+        let chars: Vec<u16> = bytes[2..]
+            .chunks(2)
+            .filter_map(|chunk| {
+                if chunk.len() == 2 {
+                    Some(u16::from_be_bytes([chunk[0], chunk[1]]))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        String::from_utf16_lossy(&chars)
+    } else {
+        // Try as Latin-1 / PDFDocEncoding
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+/// Extract text elements with positions from PDF content stream operations.
+///
+/// PDF content streams contain operators that draw text at specific positions.
+/// We track the current text position through operators (BT, Tm, Td, Tj, TJ)
+/// to capture each text fragment with its X/Y coordinates.
+fn extract_text_from_ops(ops: &[Op]) -> Vec<TextElement> {
+    let mut elements = Vec::new();
+
+    // Current transformation matrix state
+    let mut current_x: f32 = 0.0;
+    let mut current_y: f32 = 0.0;
+    let mut text_matrix_x: f32 = 0.0;
+    let mut _text_matrix_y: f32 = 0.0;
+
+    for op in ops {
+        match op {
+            // Text positioning operators
+            Op::TextNewline => {
+                // Move to next line (Td with default leading)
+                current_y -= DEFAULT_LINE_HEIGHT;
+                current_x = text_matrix_x;
+            }
+            Op::MoveTextPosition { translation } => {
+                // Td operator - move text position
+                current_x += translation.x;
+                current_y += translation.y;
+            }
+            Op::SetTextMatrix { matrix } => {
+                // Tm operator - set text matrix
+                current_x = matrix.e;
+                current_y = matrix.f;
+                text_matrix_x = matrix.e;
+                _text_matrix_y = matrix.f;
+            }
+            Op::BeginText => {
+                // BT operator - reset text position
+                current_x = 0.0;
+                current_y = 0.0;
+            }
+            // Text showing operators
+            Op::TextDraw { text } => {
+                let decoded = decode_pdf_string(text);
+                if !decoded.trim().is_empty() {
+                    elements.push(TextElement {
+                        text: decoded,
+                        x: current_x,
+                        y: current_y,
+                    });
+                }
+            }
+            Op::TextDrawAdjusted { array } => {
+                let mut combined_text = String::new();
+                let start_x = current_x;
+
+                for item in array.iter() {
+                    match item {
+                        TextDrawAdjusted::Text(text) => {
+                            combined_text.push_str(&decode_pdf_string(text));
+                        }
+                        TextDrawAdjusted::Spacing(spacing) => {
+                            // Large negative spacing often indicates a space
+                            if *spacing < SPACE_THRESHOLD {
+                                combined_text.push(' ');
+                            }
+                            // Adjust x position (spacing is in thousandths of text space unit)
+                            current_x -= spacing / SPACING_DIVISOR;
+                        }
+                    }
+                }
+
+                if !combined_text.trim().is_empty() {
+                    elements.push(TextElement {
+                        text: combined_text,
+                        x: start_x,
+                        y: current_y,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    elements
+}
+
+/// Group text elements into rows based on Y coordinate.
+///
+/// PDF Y coordinates increase upward (origin at bottom-left), so we sort
+/// rows in descending Y order to get top-to-bottom reading order.
+/// Elements within `y_tolerance` are grouped into the same row.
+fn group_into_rows(elements: Vec<TextElement>, y_tolerance: f32) -> Vec<TableRow> {
+    if elements.is_empty() {
+        return Vec::new();
+    }
+
+    // Group by Y coordinate with tolerance
+    let mut rows_map: BTreeMap<i32, Vec<(f32, String)>> = BTreeMap::new();
+
+    for elem in elements {
+        // Round Y to nearest tolerance unit for grouping
+        let y_key = (elem.y / y_tolerance).round() as i32;
+        rows_map.entry(y_key).or_default().push((elem.x, elem.text));
+    }
+
+    // Convert to TableRow and sort cells by X position
+    let mut rows: Vec<TableRow> = rows_map
+        .into_iter()
+        .map(|(y_key, mut cells)| {
+            cells.sort_by(|a, b| a.0.total_cmp(&b.0));
+            TableRow {
+                y: y_key as f32 * y_tolerance,
+                cells,
+            }
+        })
+        .collect();
+
+    // Sort rows by Y (descending, since PDF Y increases upward)
+    rows.sort_by(|a, b| b.y.total_cmp(&a.y));
+
+    rows
+}
+
+/// Extract column boundaries from PDF table lines.
+///
+/// The BIC directory PDF draws vertical lines to separate columns.
+/// We detect these by finding MoveTo/LineTo pairs with the same X coordinate,
+/// which lets us accurately assign text to columns.
+fn extract_column_boundaries_from_ops(ops: &[Op]) -> Vec<f32> {
+    let mut vertical_lines: Vec<f32> = Vec::new();
+
+    // Look for vertical lines (same X for MoveTo and LineTo)
+    let mut last_move_x: Option<f32> = None;
+
+    for op in ops {
+        match op {
+            Op::MoveTo { p } => {
+                last_move_x = Some(p.x);
+            }
+            Op::LineTo { p } => {
+                if let Some(move_x) = last_move_x {
+                    // Check if this is a vertical line (same X position)
+                    if (move_x - p.x).abs() < VERTICAL_LINE_TOLERANCE {
+                        vertical_lines.push(move_x);
+                    }
+                }
+                last_move_x = None;
+            }
+            _ => {}
+        }
+    }
+
+    // Remove duplicates and sort
+    vertical_lines.sort_by(|a, b| a.total_cmp(b));
+    vertical_lines.dedup_by(|a, b| (*a - *b).abs() < LINE_DEDUP_TOLERANCE);
+
+    // Add end boundary
+    if !vertical_lines.is_empty() {
+        vertical_lines.push(f32::MAX);
+    }
+
+    vertical_lines
+}
+
+/// Assign cells to columns based on X position
+fn assign_cells_to_columns(row: &TableRow, boundaries: &[f32]) -> Vec<String> {
+    let num_columns = boundaries.len() - 1;
+    let mut columns: Vec<String> = vec![String::new(); num_columns];
+
+    for (x, text) in &row.cells {
+        // Find which column this cell belongs to
+        for i in 0..num_columns {
+            if *x >= boundaries[i] && *x < boundaries[i + 1] {
+                if !columns[i].is_empty() {
+                    columns[i].push(' ');
+                }
+                columns[i].push_str(text);
+                break;
+            }
+        }
+    }
+
+    // Clean up: normalize whitespace in each column
+    columns
+        .into_iter()
+        .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+/// Check if a row is a header row that should be skipped.
+///
+/// The PDF repeats column headers on each page. We detect these by looking
+/// for characteristic header text and exclude them from the output.
+fn is_header_row(cells: &[String]) -> bool {
+    let combined = cells.join(" ").to_lowercase();
+    combined.contains("record") && combined.contains("creation")
+        || combined.contains("last update")
+        || combined.contains("brch code")
+        || combined.contains("bic brch")
+        || combined.contains("full legal name")
+        || combined.contains("instit. type")
+        || combined.contains("inst. type")
+        || combined.contains("iso bic directory")
+        || combined.contains("registration authority")
+        || combined.contains("iso 9362")
+}
+
+/// Check if a row holds the wrapped lines of a multi-line column header.
+///
+/// Headers such as "Record creation date" wrap over several lines, and only the
+/// first line contains enough text to be recognized by itself. The remaining
+/// lines ("creation", "date", ...) are detected by every cell being made of
+/// words from its own column's header. Only rows above the page's first data
+/// row are checked, as a continuation row could be made of such words too.
+fn is_wrapped_header_row(cells: &[String]) -> bool {
+    let mut has_content = false;
+
+    for (cell, header) in cells.iter().zip(HEADERS) {
+        if cell.is_empty() {
+            continue;
+        }
+        has_content = true;
+
+        let header_words: Vec<String> = header
+            .split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect();
+        if !cell
+            .split_whitespace()
+            .all(|w| header_words.contains(&w.to_lowercase()))
+        {
+            return false;
+        }
+    }
+
+    has_content
+}
+
+/// Check if a row starts a new data record (has a date in the first column).
+///
+/// BIC records always start with a creation date in YYYY-MM-DD format.
+/// Rows without a date are continuation rows containing wrapped address content.
+fn is_data_row(cells: &[String]) -> bool {
+    if cells.is_empty() || cells[0].is_empty() {
+        return false;
+    }
+
+    // Check if first cell looks like a date (YYYY-MM-DD)
+    let first = cells[0].trim();
+    if first.len() >= 10 {
+        let parts: Vec<&str> = first.split('-').collect();
+        if parts.len() >= 3 {
+            return parts[0].len() == 4
+                && parts[0].chars().all(|c| c.is_ascii_digit())
+                && parts[1].len() == 2
+                && parts[2].len() >= 2;
+        }
+    }
+
+    false
+}
+
+/// Merge a continuation row into the current record.
+///
+/// Address fields often span multiple lines in the PDF. Continuation rows
+/// (those without a date) contain wrapped content that belongs to the
+/// previous record. We append each column's content to preserve multi-line
+/// addresses as single fields.
+fn merge_continuation_row(record: &mut [String], continuation: &[String]) {
+    for (i, cell) in continuation.iter().enumerate() {
+        if i < record.len() && !cell.is_empty() {
+            if !record[i].is_empty() {
+                record[i].push(' ');
+            }
+            record[i].push_str(cell);
+        }
+    }
+}
+
+/// How a visual row was interpreted while rebuilding records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    /// No text fell inside the column grid.
+    Empty,
+    /// Repeated page header or title text.
+    Header,
+    /// Starts a new record (has a creation date in the first column).
+    Data,
+    /// Wrapped content merged into the record above it.
+    Continuation,
+    /// Continuation content with no open record to merge into, so it is dropped.
+    Orphan,
+}
+
+/// A row after column assignment, together with how it was classified.
+#[derive(Debug, Clone)]
+struct ClassifiedRow {
+    row: TableRow,
+    cells: Vec<String>,
+    kind: RowKind,
+}
+
+/// Assign each row's cells to columns and classify it.
+///
+/// Classification depends on the rows above: a row without a date is only a
+/// continuation if a data row has already opened a record on this page, and
+/// only a wrapped header line if none has.
+fn classify_rows(rows: Vec<TableRow>, boundaries: &[f32]) -> Vec<ClassifiedRow> {
+    let mut has_open_record = false;
+
+    rows.into_iter()
+        .map(|row| {
+            let cells = assign_cells_to_columns(&row, boundaries);
+
+            let kind = if cells.iter().all(|c| c.is_empty()) {
+                RowKind::Empty
+            } else if is_header_row(&cells) || !has_open_record && is_wrapped_header_row(&cells) {
+                RowKind::Header
+            } else if is_data_row(&cells) {
+                has_open_record = true;
+                RowKind::Data
+            } else if has_open_record {
+                RowKind::Continuation
+            } else {
+                RowKind::Orphan
+            };
+
+            ClassifiedRow { row, cells, kind }
+        })
+        .collect()
+}
+
+/// Records extracted from a single page, plus what was dropped along the way.
+#[derive(Debug, Clone, Default)]
+struct PageExtraction {
+    records: Vec<Vec<String>>,
+    /// Continuation rows that had no open record to merge into.
+    orphan_rows: Vec<Vec<String>>,
+    /// Text left of the first column boundary, which no column picks up.
+    out_of_grid: Vec<TextElement>,
+    /// Number of text elements found on the page.
+    text_elements: usize,
+}
+
+/// Process a page's content and extract complete records.
+///
+/// This is the core extraction logic: extract positioned text, group into rows,
+/// assign to columns, identify record boundaries (rows starting with dates),
+/// and merge continuation rows into their parent records.
+fn process_page_rows(ops: &[Op], boundaries: &[f32]) -> PageExtraction {
+    let mut extraction = PageExtraction::default();
+
+    let elements = extract_text_from_ops(ops);
+    extraction.text_elements = elements.len();
+    if elements.is_empty() {
+        return extraction;
+    }
+
+    let first_boundary = boundaries.first().copied().unwrap_or(f32::MIN);
+    extraction.out_of_grid = elements
+        .iter()
+        .filter(|e| e.x < first_boundary)
+        .cloned()
+        .collect();
+
+    let rows = group_into_rows(elements, Y_TOLERANCE);
+    if rows.is_empty() {
+        return extraction;
+    }
+
+    let mut current_record: Option<Vec<String>> = None;
+
+    for classified in classify_rows(rows, boundaries) {
+        match classified.kind {
+            RowKind::Data => {
+                // Save the previous record if any
+                if let Some(record) = current_record.take() {
+                    extraction.records.push(record);
+                }
+                // Start a new record
+                current_record = Some(
+                    classified
+                        .cells
+                        .iter()
+                        .map(|c| c.trim().to_string())
+                        .collect(),
+                );
+            }
+            RowKind::Continuation => {
+                if let Some(record) = current_record.as_mut() {
+                    merge_continuation_row(record, &classified.cells);
+                }
+            }
+            RowKind::Orphan => extraction.orphan_rows.push(classified.cells),
+            RowKind::Empty | RowKind::Header => {}
+        }
+    }
+
+    // Don't forget the last record
+    if let Some(record) = current_record.take() {
+        extraction.records.push(record);
+    }
+
+    extraction
+}
+
+/// Check whether a page's table lines match the column boundaries in use.
+fn boundaries_match(boundaries: &[f32], page_boundaries: &[f32]) -> bool {
+    page_boundaries.len() >= REQUIRED_BOUNDARIES
+        && boundaries
+            .iter()
+            .zip(page_boundaries)
+            .all(|(a, b)| (a - b).abs() < LINE_DEDUP_TOLERANCE)
+}
+
+/// Detect the table's column boundaries from a page's drawing operations.
+///
+/// Fails if the page doesn't draw enough vertical lines to delimit all columns.
+fn detect_column_boundaries(ops: &[Op]) -> Result<Vec<f32>> {
+    let mut detected = extract_column_boundaries_from_ops(ops);
+    if detected.len() < REQUIRED_BOUNDARIES {
+        anyhow::bail!(
+            "Failed to detect column boundaries from PDF. Expected at least {} vertical lines, found {}. \
+             This PDF may have a different format than the standard ISO BIC directory.",
+            REQUIRED_BOUNDARIES,
+            detected.len()
+        );
+    }
+    detected.truncate(REQUIRED_BOUNDARIES);
+    Ok(detected)
+}
+
+/// Extract a single page's records.
+///
+/// `page_number` is 1-based, as shown by PDF viewers. Column boundaries are
+/// detected from the first page processed and reused for the following pages,
+/// which are checked for drifting table lines.
+fn extract_page(
+    page_result: pdf::error::Result<pdf::object::PageRc>,
+    page_number: usize,
+    resolver: &impl pdf::object::Resolve,
+    limits: &Limits,
+    started: Instant,
+    boundaries: &mut Option<Vec<f32>>,
+    report: &mut ExtractionReport,
+) -> Result<PageExtraction> {
+    let page = page_result.context(format!("Failed to get page {}", page_number))?;
+
+    let contents = match &page.contents {
+        Some(c) => c,
+        None => return Ok(PageExtraction::default()),
+    };
+
+    let context = || format!("Failed to parse operations on page {}", page_number);
+    let mut content = Vec::new();
+    for part in &contents.parts {
+        content.extend_from_slice(&part.data(resolver).with_context(context)?);
+    }
+    let ops = content::parse_ops(&content, limits, started, page_number).map_err(|error| {
+        // Limit errors already name the page
+        if error.is::<LimitExceeded>() {
+            error
+        } else {
+            error.context(context())
+        }
+    })?;
+
+    let boundaries = match boundaries {
+        Some(expected) => {
+            let page_boundaries = extract_column_boundaries_from_ops(&ops);
+            if !boundaries_match(expected, &page_boundaries) {
+                report.add_column_drift(page_number, &page_boundaries);
+            }
+            expected
+        }
+        None => boundaries.insert(detect_column_boundaries(&ops)?),
+    };
+
+    Ok(process_page_rows(&ops, boundaries))
+}
+
+fn extract_table_from_file<T: std::ops::Deref<Target = [u8]>>(
+    file: pdf::file::CachedFile<T>,
+    options: &ExtractOptions,
+    on_page: &mut dyn FnMut(usize, usize) -> ControlFlow<()>,
+    on_record: &mut dyn FnMut(Vec<String>) -> Result<()>,
+) -> Result<ExtractionReport> {
+    let resolver = file.resolver();
+    let mut records = 0;
+    let mut report = ExtractionReport::default();
+    let mut boundaries: Option<Vec<f32>> = None;
+    let mut sort_position = None;
+
+    let mut text_elements = 0;
+    let started = Instant::now();
+
+    let total_pages = file.num_pages() as usize;
+    options.limits.check_pages(total_pages)?;
+
+    for (page_num, page_result) in file.pages().enumerate() {
+        // Skip cover page (page 0), which has no records but should still load
+        if page_num == 0 {
+            if let Err(error) = page_result {
+                let error = anyhow::Error::from(error).context("Failed to get page 1");
+                if !options.recover {
+                    return Err(error);
+                }
+                report.add_page_error(1, &error);
+            }
+            continue;
+        }
+
+        // Reports use 1-based page numbers, as shown by PDF viewers
+        let page_number = page_num + 1;
+        options.limits.check_timeout(started, page_number)?;
+
+        let page_extraction = match extract_page(
+            page_result,
+            page_number,
+            &resolver,
+            &options.limits,
+            started,
+            &mut boundaries,
+            &mut report,
+        ) {
+            Ok(page_extraction) => page_extraction,
+            // Limits guard the whole extraction, so they're never recovered from
+            Err(error) if options.recover && !error.is::<LimitExceeded>() => {
+                report.add_page_error(page_number, &error);
+                if on_page(page_number, total_pages).is_break() {
+                    return Err(Cancelled { page: page_number }.into());
+                }
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+
+        text_elements += page_extraction.text_elements;
+        options
+            .limits
+            .check_text_elements(text_elements, page_number)?;
+
+        report.add_page(page_number, &page_extraction);
+        for record in page_extraction.records {
+            report.check_record(records, page_number, &record);
+            report.check_sort_order(&mut sort_position, records, page_number, &record);
+            on_record(record)?;
+            records += 1;
+        }
+
+        if on_page(page_number, total_pages).is_break() {
+            return Err(Cancelled { page: page_number }.into());
+        }
+    }
+
+    if options.strict && !report.is_clean() {
+        return Err(QualityError {
+            issues: report.issues(),
+        }
+        .into());
+    }
+
+    Ok(report)
+}
+
+/// Load a PDF, decrypting it with `password` if it's encrypted.
+///
+/// Files encrypted with only an owner password (to restrict permissions) open
+/// without one. Otherwise a missing or wrong password fails with a
+/// [`PasswordError`] rather than a generic load error.
+fn load_pdf<B: std::ops::Deref<Target = [u8]>>(
+    data: B,
+    password: Option<&str>,
+) -> Result<pdf::file::CachedFile<B>> {
+    match FileOptions::cached()
+        .password(password.unwrap_or_default().as_bytes())
+        .load(data)
+    {
+        Ok(file) => Ok(file),
+        Err(error) if is_invalid_password(&error) => Err(match password {
+            None => PasswordError::PasswordRequired,
+            Some(_) => PasswordError::WrongPassword,
+        }
+        .into()),
+        Err(error) => Err(error).context("Failed to load PDF from bytes"),
+    }
+}
+
+fn is_invalid_password(error: &pdf::error::PdfError) -> bool {
+    use pdf::error::PdfError;
+
+    match error {
+        PdfError::InvalidPassword => true,
+        PdfError::Try { source, .. } => is_invalid_password(source),
+        _ => false,
+    }
+}
+
+// =================================================================================
+// Public Functions
+// =================================================================================
+
+/// Extract table data from PDF bytes in memory.
+///
+/// Processes all pages (except the cover page) and extracts BIC records.
+/// Column boundaries are detected from the first data page and reused
+/// for consistency across all pages.
+///
+/// Takes owned or borrowed bytes, so a `&[u8]` is extracted without a copy.
+pub fn extract_table_from_bytes(data: impl AsRef<[u8]>) -> Result<Vec<Vec<String>>> {
+    Ok(extract_table_with_report_from_bytes(data)?.records)
+}
+
+/// Extracted records together with the quality report for the extraction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extraction {
+    pub records: Vec<Vec<String>>,
+    pub report: ExtractionReport,
+}
+
+/// Options controlling how extraction treats suspicious content.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractOptions {
+    /// Fail with a [`QualityError`] listing the offending pages and records
+    /// when the quality report finds anything suspicious, instead of
+    /// returning the records that could be extracted.
+    pub strict: bool,
+    /// Record pages that fail to load or parse in the report's `page_errors`
+    /// and carry on with the next page, instead of failing the whole document.
+    pub recover: bool,
+    /// Guards against hostile or corrupt PDFs. Exceeding one fails with a
+    /// [`LimitExceeded`] error, even when recovering.
+    pub limits: Limits,
+    /// User or owner password of an encrypted PDF.
+    pub password: Option<String>,
+    /// When the PDF fails to load, rebuild its cross-reference table by
+    /// scanning the file for objects and extract from that instead. The
+    /// report's `repaired` flag records when this was needed.
+    pub repair: bool,
+    /// Name of the PDF to extract from a zip archive holding more than one.
+    pub archive_entry: Option<String>,
+}
+
+/// Extract table data from PDF bytes in memory, reporting anything that was
+/// dropped or looks wrong along the way.
+///
+/// See [`ExtractionReport`] for what the report covers.
+pub fn extract_table_with_report_from_bytes(data: impl AsRef<[u8]>) -> Result<Extraction> {
+    extract_table_with_options_from_bytes(data, &ExtractOptions::default())
+}
+
+/// Extract table data from a PDF file.
+///
+/// Like the bytes entry points, the file may be gzip-compressed or a zip
+/// archive containing the PDF.
+pub fn extract_table_from_path(path: impl AsRef<Path>) -> Result<Vec<Vec<String>>> {
+    Ok(extract_table_with_options_from_path(path, &ExtractOptions::default())?.records)
+}
+
+/// Extract table data from a PDF file with the given options.
+pub fn extract_table_with_options_from_path(
+    path: impl AsRef<Path>,
+    options: &ExtractOptions,
+) -> Result<Extraction> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    extract_table_with_options_from_bytes(data, options)
+}
+
+/// Extract table data from a PDF read from `reader`.
+///
+/// Parsing a PDF needs random access, so the reader is read to the end into
+/// memory first. Any reader works, including decompression streams.
+pub fn extract_table_from_reader(reader: impl Read) -> Result<Vec<Vec<String>>> {
+    Ok(extract_table_with_options_from_reader(reader, &ExtractOptions::default())?.records)
+}
+
+/// Extract table data from a PDF read from `reader` with the given options.
+///
+/// Reading stops once the input exceeds `options.limits.max_input_size`.
+pub fn extract_table_with_options_from_reader(
+    reader: impl Read,
+    options: &ExtractOptions,
+) -> Result<Extraction> {
+    let data = options
+        .limits
+        .read_input(reader)
+        .context("Failed to read PDF from reader")?;
+    extract_table_with_options_from_bytes(data, options)
+}
+
+/// Extract table data from PDF bytes in memory with the given options.
+///
+/// Gzip-compressed input and zip archives containing the PDF are
+/// decompressed first.
+pub fn extract_table_with_options_from_bytes(
+    data: impl AsRef<[u8]>,
+    options: &ExtractOptions,
+) -> Result<Extraction> {
+    extract_table_with_progress_from_bytes(data, options, |_, _| ControlFlow::Continue(()))
+}
+
+/// Extraction was stopped by its caller before it finished.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cancelled {
+    /// 1-based number of the last page processed before stopping.
+    pub page: usize,
+}
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Extraction was cancelled after page {}", self.page)
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// The PDF is encrypted and couldn't be opened with the given password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum PasswordError {
+    /// No password was given, and the PDF can't be opened without one.
+    PasswordRequired,
+    /// The given password is neither the user nor the owner password.
+    WrongPassword,
+}
+
+impl std::fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PasswordError::PasswordRequired => {
+                write!(f, "PDF is encrypted and requires a password")
+            }
+            PasswordError::WrongPassword => write!(f, "Wrong password for the encrypted PDF"),
+        }
+    }
+}
+
+impl std::error::Error for PasswordError {}
+
+/// Extract table data from PDF bytes in memory, reporting progress as it goes.
+///
+/// `on_page` is called after each page following the cover with the page's
+/// 1-based number and the document's total number of pages. Returning
+/// [`ControlFlow::Break`] stops extraction with a [`Cancelled`] error.
+pub fn extract_table_with_progress_from_bytes(
+    data: impl AsRef<[u8]>,
+    options: &ExtractOptions,
+    mut on_page: impl FnMut(usize, usize) -> ControlFlow<()>,
+) -> Result<Extraction> {
+    let mut records = Vec::new();
+    let (mut report, repaired) = with_pdf(data.as_ref(), options, |file| {
+        extract_table_from_file(file, options, &mut on_page, &mut |record| {
+            records.push(record);
+            Ok(())
+        })
+    })?;
+    report.repaired = repaired;
+    Ok(Extraction { records, report })
+}
+
+/// Extract table data from PDF bytes in memory, passing each record to
+/// `on_record` as soon as its page is processed instead of collecting them.
+///
+/// Only a page's worth of records is held at a time, so exports can write
+/// the whole directory without keeping it in memory. An error returned by
+/// `on_record` stops extraction and is returned. In strict mode the
+/// [`QualityError`] comes after every record has been passed on.
+pub fn stream_table_from_bytes(
+    data: impl AsRef<[u8]>,
+    options: &ExtractOptions,
+    mut on_record: impl FnMut(Vec<String>) -> Result<()>,
+) -> Result<ExtractionReport> {
+    let (mut report, repaired) = with_pdf(data.as_ref(), options, |file| {
+        extract_table_from_file(
+            file,
+            options,
+            &mut |_, _| ControlFlow::Continue(()),
+            &mut on_record,
+        )
+    })?;
+    report.repaired = repaired;
+    Ok(report)
+}
+
+/// Decompress and load the PDF in `data` as `options` ask, and pass it to `f`.
+///
+/// Returns what `f` returns, and whether the PDF had to be repaired first.
+fn with_pdf<T>(
+    data: &[u8],
+    options: &ExtractOptions,
+    f: impl FnOnce(pdf::file::CachedFile<&[u8]>) -> Result<T>,
+) -> Result<(T, bool)> {
+    let data = archive::decompress(data, options.archive_entry.as_deref(), &options.limits)?;
+    let encrypted_streams = options.limits.check_streams(&data, Instant::now())?;
+    let password = options.password.as_deref();
+    // The repaired file only appends an xref table, so streams stay in place
+    let check_encrypted_streams = |file: &pdf::file::CachedFile<&[u8]>| {
+        options
+            .limits
+            .check_encrypted_streams(&file.resolver(), &encrypted_streams)
+    };
+
+    match load_pdf(&*data, password) {
+        Ok(file) => {
+            check_encrypted_streams(&file)?;
+            Ok((f(file)?, false))
+        }
+        // A wrong password isn't damage that repair could fix
+        Err(error) if options.repair && !error.is::<PasswordError>() => {
+            let repaired = repair::rebuild_xref(&data)
+                .with_context(|| format!("Failed to repair PDF ({:#})", error))?;
+            let file =
+                load_pdf(repaired.as_slice(), password).context("Failed to load repaired PDF")?;
+            check_encrypted_streams(&file)?;
+            Ok((f(file)?, true))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn fixtures_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+    }
+
+    #[test]
+    fn test_extract_table_from_bytes_with_fixture() {
+        let pdf_path = fixtures_path().join("ISOBIC-mini.pdf");
+        let expected_csv_path = fixtures_path().join("ISOBIC-mini-expected.csv");
+
+        let pdf_bytes = std::fs::read(&pdf_path).expect("Failed to read PDF file");
+        let actual_rows =
+            extract_table_from_bytes(pdf_bytes).expect("Failed to extract table from bytes");
+
+        let mut csv_reader =
+            csv::Reader::from_path(&expected_csv_path).expect("Failed to open expected CSV");
+
+        let expected_rows: Vec<Vec<String>> = csv_reader
+            // .records() skips the header row, so we don't have to skip it manually
+            .records()
+            .map(|r| {
+                r.expect("Failed to read CSV record")
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            actual_rows.len(),
+            expected_rows.len(),
+            "Row count mismatch. Expected {} rows, got {}",
+            expected_rows.len(),
+            actual_rows.len()
+        );
+
+        for (i, (actual, expected)) in actual_rows.iter().zip(expected_rows.iter()).enumerate() {
+            assert_eq!(
+                actual,
+                expected,
+                "Row {} mismatch.\nExpected: {:?}\nActual: {:?}",
+                i + 1,
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_table_with_report_from_bytes_with_fixture() {
+        let pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        let extraction =
+            extract_table_with_report_from_bytes(pdf_bytes).expect("Failed to extract table");
+        let report = &extraction.report;
+
+        assert_eq!(extraction.records.len(), 86);
+        assert!(report.orphan_rows.is_empty(), "{:?}", report.orphan_rows);
+        assert!(report.out_of_grid.is_empty());
+        assert!(report.missing_fields.is_empty());
+        assert!(report.invalid_dates.is_empty());
+        assert!(report.invalid_bics.is_empty());
+        assert!(report.column_drift.is_empty());
+        assert!(report.sort_violations.is_empty());
+        assert!(report.empty_pages.is_empty());
+        assert_eq!(
+            report.records_per_page,
+            vec![
+                PageRecordCount {
+                    page: 2,
+                    records: 30
+                },
+                PageRecordCount {
+                    page: 3,
+                    records: 28
+                },
+                PageRecordCount {
+                    page: 4,
+                    records: 28
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_boundaries_match() {
+        let boundaries: Vec<f32> = (0..REQUIRED_BOUNDARIES).map(|i| i as f32 * 50.0).collect();
+        let mut page_boundaries = boundaries.clone();
+        page_boundaries.push(f32::MAX);
+        assert!(boundaries_match(&boundaries, &page_boundaries));
+
+        page_boundaries[3] += 1.0;
+        assert!(boundaries_match(&boundaries, &page_boundaries));
+
+        page_boundaries[3] += 5.0;
+        assert!(!boundaries_match(&boundaries, &page_boundaries));
+
+        // A missing line shifts every boundary after it
+        let mut missing_line = boundaries.clone();
+        missing_line.remove(5);
+        assert!(!boundaries_match(&boundaries, &missing_line));
+    }
+
+    #[test]
+    fn test_extract_table_strict_with_fixture() {
+        let pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        let options = ExtractOptions {
+            strict: true,
+            ..ExtractOptions::default()
+        };
+
+        let extraction = extract_table_with_options_from_bytes(pdf_bytes, &options)
+            .expect("A clean PDF passes strict mode");
+        assert_eq!(extraction.records.len(), 86);
+    }
+
+    /// Overwrite the start of an object's stream data so it no longer decodes.
+    fn corrupt_stream(pdf_bytes: &mut [u8], object: &str) {
+        let find = |haystack: &[u8], needle: &[u8], from: usize| {
+            haystack[from..]
+                .windows(needle.len())
+                .position(|w| w == needle)
+                .map(|p| p + from)
+                .expect("Marker not found in PDF")
+        };
+        let object_start = find(pdf_bytes, format!("\n{} 0 obj", object).as_bytes(), 0);
+        let data_start = find(pdf_bytes, b"stream\n", object_start) + b"stream\n".len();
+        pdf_bytes[data_start..data_start + 16].fill(b'#');
+    }
+
+    #[test]
+    fn test_extract_table_recovers_from_page_errors() {
+        let mut pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        // Content stream of page 3
+        corrupt_stream(&mut pdf_bytes, "18");
+
+        assert!(extract_table_from_bytes(pdf_bytes.clone()).is_err());
+
+        let options = ExtractOptions {
+            recover: true,
+            ..ExtractOptions::default()
+        };
+        let extraction = extract_table_with_options_from_bytes(pdf_bytes.clone(), &options)
+            .expect("Failed to extract table");
+
+        assert_eq!(extraction.records.len(), 86 - 28);
+        let page_errors = &extraction.report.page_errors;
+        assert_eq!(page_errors.len(), 1);
+        assert_eq!(page_errors[0].page, 3);
+        assert!(
+            page_errors[0]
+                .cause
+                .starts_with("Failed to parse operations on page 3"),
+            "{}",
+            page_errors[0].cause
+        );
+
+        let strict_options = ExtractOptions {
+            strict: true,
+            recover: true,
+            ..ExtractOptions::default()
+        };
+        let error = extract_table_with_options_from_bytes(pdf_bytes, &strict_options)
+            .expect_err("Lost pages fail strict mode");
+        assert!(error.downcast_ref::<QualityError>().is_some());
+    }
+
+    #[test]
+    fn test_extract_table_with_broken_cover_page() {
+        let mut pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        // The cover's media box becomes a string instead of an array. The pdf
+        // crate loads the page tree as a whole, so every page fails with it.
+        let media_box = b"/MediaBox [0 0 841.89 1190.55]";
+        let start = pdf_bytes
+            .windows(media_box.len())
+            .position(|window| window == media_box)
+            .expect("Cover media box not found");
+        pdf_bytes[start..start + media_box.len()]
+            .copy_from_slice(b"/MediaBox (0 0 841.89 1190.55)");
+
+        let error = extract_table_from_bytes(pdf_bytes.clone()).expect_err("The cover is broken");
+        assert!(
+            error.to_string().starts_with("Failed to get page 1"),
+            "{}",
+            error
+        );
+
+        let options = ExtractOptions {
+            recover: true,
+            ..ExtractOptions::default()
+        };
+        let extraction = extract_table_with_options_from_bytes(pdf_bytes, &options)
+            .expect("Failed to extract table");
+        let pages: Vec<_> = extraction
+            .report
+            .page_errors
+            .iter()
+            .map(|error| error.page)
+            .collect();
+        assert_eq!(pages, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_extract_table_limits_with_fixture() {
+        let pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        let extract = |limits: Limits| {
+            let options = ExtractOptions {
+                recover: true,
+                limits,
+                ..ExtractOptions::default()
+            };
+            extract_table_with_options_from_bytes(pdf_bytes.clone(), &options)
+                .map(|extraction| extraction.records.len())
+                .map_err(|e| e.downcast::<LimitExceeded>().expect("Not a limit error"))
+        };
+
+        let generous = Limits {
+            max_pages: Some(4),
+            max_operations_per_page: Some(100_000),
+            max_stream_size: Some(1_000_000),
+            max_text_elements: Some(100_000),
+            timeout: Some(Duration::from_secs(60)),
+            max_input_size: None,
+        };
+        assert_eq!(extract(generous.clone()), Ok(86));
+
+        let exceeded = |limits: Limits| extract(limits).unwrap_err().limit;
+        assert_eq!(
+            exceeded(Limits {
+                max_pages: Some(3),
+                ..generous.clone()
+            }),
+            Limit::MaxPages
+        );
+        assert_eq!(
+            exceeded(Limits {
+                max_operations_per_page: Some(100),
+                ..generous.clone()
+            }),
+            Limit::MaxOperationsPerPage
+        );
+        assert_eq!(
+            exceeded(Limits {
+                max_stream_size: Some(1_000),
+                ..generous.clone()
+            }),
+            Limit::MaxStreamSize
+        );
+        assert_eq!(
+            exceeded(Limits {
+                max_text_elements: Some(1_000),
+                ..generous.clone()
+            }),
+            Limit::MaxTextElements
+        );
+        assert_eq!(
+            exceeded(Limits {
+                timeout: Some(Duration::ZERO),
+                ..generous
+            }),
+            Limit::Timeout
+        );
+    }
+
+    #[test]
+    fn test_extract_table_with_progress_with_fixture() {
+        let pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        let options = ExtractOptions::default();
+
+        let mut pages = Vec::new();
+        let extraction =
+            extract_table_with_progress_from_bytes(pdf_bytes.clone(), &options, |page, total| {
+                pages.push((page, total));
+                ControlFlow::Continue(())
+            })
+            .expect("Failed to extract table");
+
+        assert_eq!(extraction.records.len(), 86);
+        assert_eq!(pages, vec![(2, 4), (3, 4), (4, 4)]);
+
+        let error = extract_table_with_progress_from_bytes(pdf_bytes, &options, |page, _| {
+            if page == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .expect_err("Extraction should have been cancelled");
+
+        assert_eq!(
+            error.downcast::<Cancelled>().ok(),
+            Some(Cancelled { page: 3 })
+        );
+    }
+
+    #[test]
+    fn test_extract_table_from_encrypted_fixtures() {
+        let expected = extract_table_from_bytes(
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF"),
+        )
+        .expect("Failed to extract table");
+
+        let extract = |fixture: &str, password: Option<&str>| {
+            let pdf_bytes =
+                std::fs::read(fixtures_path().join(fixture)).expect("Failed to read PDF");
+            let options = ExtractOptions {
+                password: password.map(str::to_string),
+                ..ExtractOptions::default()
+            };
+            extract_table_with_options_from_bytes(pdf_bytes, &options).map(|e| e.records)
+        };
+        let password_error = |result: Result<Vec<Vec<String>>>| {
+            result
+                .expect_err("Extraction should have failed")
+                .downcast::<PasswordError>()
+                .expect("Not a password error")
+        };
+
+        for fixture in ["ISOBIC-mini-rc4.pdf", "ISOBIC-mini-aes128.pdf"] {
+            assert_eq!(extract(fixture, Some("user-secret")).unwrap(), expected);
+            assert_eq!(extract(fixture, Some("owner-secret")).unwrap(), expected);
+            assert_eq!(
+                password_error(extract(fixture, None)),
+                PasswordError::PasswordRequired
+            );
+            assert_eq!(
+                password_error(extract(fixture, Some("guess"))),
+                PasswordError::WrongPassword
+            );
+        }
+
+        // Only restricts permissions, so it opens without a password
+        assert_eq!(
+            extract("ISOBIC-mini-permissions.pdf", None).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_extract_table_stream_limit_with_fixtures() {
+        let extract = |fixture: &str, password: Option<&str>, max_stream_size: usize| {
+            let pdf_bytes =
+                std::fs::read(fixtures_path().join(fixture)).expect("Failed to read PDF");
+            let options = ExtractOptions {
+                password: password.map(str::to_string),
+                limits: Limits {
+                    max_stream_size: Some(max_stream_size),
+                    ..Limits::default()
+                },
+                ..ExtractOptions::default()
+            };
+            extract_table_with_options_from_bytes(pdf_bytes, &options)
+                .map(|extraction| extraction.records.len())
+                .map_err(|e| {
+                    e.downcast::<LimitExceeded>()
+                        .expect("Not a limit error")
+                        .limit
+                })
+        };
+
+        // Its page content inflates to 60 MB, with "endstream" early in the data
+        let bomb = "ISOBIC-mini-endstream-bomb.pdf";
+        assert_eq!(extract(bomb, None, 1_000_000), Err(Limit::MaxStreamSize));
+        assert_eq!(extract(bomb, None, 100_000_000), Ok(86));
+
+        // Encrypted streams are measured once decrypted
+        for (fixture, password) in [
+            ("ISOBIC-mini-rc4.pdf", Some("user-secret")),
+            ("ISOBIC-mini-aes128.pdf", Some("user-secret")),
+            ("ISOBIC-mini-permissions.pdf", None),
+        ] {
+            assert_eq!(extract(fixture, password, 1_000_000), Ok(86), "{}", fixture);
+            assert_eq!(
+                extract(fixture, password, 1_000),
+                Err(Limit::MaxStreamSize),
+                "{}",
+                fixture
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_table_repairs_broken_xref() {
+        let pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        let startxref = pdf_bytes
+            .windows(b"startxref\n".len())
+            .rposition(|window| window == b"startxref\n")
+            .expect("No startxref")
+            + b"startxref\n".len();
+        let mut broken = pdf_bytes[..startxref].to_vec();
+        broken.extend_from_slice(b"12345\n%%EOF\n");
+
+        assert!(extract_table_from_bytes(broken.clone()).is_err());
+
+        let options = ExtractOptions {
+            repair: true,
+            ..ExtractOptions::default()
+        };
+        let extraction =
+            extract_table_with_options_from_bytes(broken, &options).expect("Failed to repair");
+
+        assert_eq!(
+            extraction.records,
+            extract_table_from_bytes(pdf_bytes).expect("Failed to extract table")
+        );
+        assert!(extraction.report.repaired);
+        assert!(extraction.report.is_clean());
+
+        let extraction = extract_table_with_report_from_bytes(
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF"),
+        )
+        .expect("Failed to extract table");
+        assert!(!extraction.report.repaired, "Intact PDFs aren't repaired");
+    }
+
+    #[test]
+    fn test_extract_table_repairs_truncated_download() {
+        let pdf_bytes = std::fs::read(fixtures_path().join("ISOBIC-mini-truncated.pdf"))
+            .expect("Failed to read PDF");
+
+        assert!(extract_table_from_bytes(pdf_bytes.clone()).is_err());
+
+        let options = ExtractOptions {
+            repair: true,
+            recover: true,
+            ..ExtractOptions::default()
+        };
+        let extraction =
+            extract_table_with_options_from_bytes(pdf_bytes, &options).expect("Failed to repair");
+
+        assert_eq!(
+            extraction.records.len(),
+            58,
+            "The first two data pages are intact"
+        );
+        assert!(extraction.report.repaired);
+        let page_errors = &extraction.report.page_errors;
+        assert_eq!(page_errors.len(), 1, "{:?}", page_errors);
+        assert_eq!(page_errors[0].page, 4);
+    }
+
+    #[test]
+    fn test_extract_table_from_compressed_path() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let pdf_path = fixtures_path().join("ISOBIC-mini.pdf");
+        let expected = extract_table_from_path(&pdf_path).expect("Failed to extract table");
+        assert_eq!(expected.len(), 86);
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&std::fs::read(&pdf_path).expect("Failed to read PDF"))
+            .unwrap();
+        let gz_path =
+            std::env::temp_dir().join(format!("ISOBIC-mini-{}.pdf.gz", std::process::id()));
+        std::fs::write(&gz_path, encoder.finish().unwrap()).expect("Failed to write gzip");
+
+        let actual = extract_table_from_path(&gz_path);
+        std::fs::remove_file(&gz_path).ok();
+        assert_eq!(actual.expect("Failed to extract table from gzip"), expected);
+
+        let error = extract_table_from_path(fixtures_path().join("missing.pdf")).unwrap_err();
+        assert!(
+            error.to_string().starts_with("Failed to read "),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_extract_table_from_reader_and_slice() {
+        use flate2::read::GzEncoder;
+        use flate2::Compression;
+        use std::io::Cursor;
+
+        let pdf_path = fixtures_path().join("ISOBIC-mini.pdf");
+        let pdf_bytes = std::fs::read(&pdf_path).expect("Failed to read PDF");
+        let expected = extract_table_from_bytes(pdf_bytes.as_slice()).expect("Failed to extract");
+        assert_eq!(expected.len(), 86);
+
+        let file = std::fs::File::open(&pdf_path).expect("Failed to open PDF");
+        assert_eq!(extract_table_from_reader(file).unwrap(), expected);
+        assert_eq!(
+            extract_table_from_reader(Cursor::new(&pdf_bytes[..])).unwrap(),
+            expected
+        );
+
+        // Compressed on the fly, then decompressed by the pipeline
+        let gzip = GzEncoder::new(pdf_bytes.as_slice(), Compression::fast());
+        let extraction =
+            extract_table_with_options_from_reader(gzip, &ExtractOptions::default()).unwrap();
+        assert_eq!(extraction.records, expected);
+
+        // An endless reader stops at the input limit
+        let options = ExtractOptions {
+            limits: Limits {
+                max_input_size: Some(1_000),
+                ..Limits::default()
+            },
+            ..ExtractOptions::default()
+        };
+        let error =
+            extract_table_with_options_from_reader(std::io::repeat(0), &options).unwrap_err();
+        assert_eq!(
+            error
+                .downcast_ref::<LimitExceeded>()
+                .map(|error| error.limit),
+            Some(Limit::MaxInputSize)
+        );
+    }
+
+    #[test]
+    fn test_stream_table_from_bytes() {
+        let pdf_bytes =
+            std::fs::read(fixtures_path().join("ISOBIC-mini.pdf")).expect("Failed to read PDF");
+        let expected = extract_table_with_report_from_bytes(&pdf_bytes).expect("Failed to extract");
+
+        let mut records = Vec::new();
+        let report = stream_table_from_bytes(&pdf_bytes, &ExtractOptions::default(), |record| {
+            records.push(record);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(records, expected.records);
+        assert_eq!(report, expected.report);
+
+        // An error from the callback stops extraction
+        let mut seen = 0;
+        let error = stream_table_from_bytes(&pdf_bytes, &ExtractOptions::default(), |_| {
+            seen += 1;
+            anyhow::ensure!(seen < 3, "Sink full");
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Sink full");
+        assert_eq!(seen, 3);
+    }
+
+    #[test]
+    fn test_is_header_row() {
+        assert!(is_header_row(&["Record creation date".to_string()]));
+        assert!(is_header_row(&["BIC Brch Code".to_string()]));
+        assert!(!is_header_row(&["1997-03-01".to_string()]));
+    }
+
+    #[test]
+    fn test_is_wrapped_header_row() {
+        let cells = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        assert!(is_wrapped_header_row(&cells(&[
+            "creation", "Update", "", "Code", "", "", "", "", "", "Type"
+        ])));
+        assert!(is_wrapped_header_row(&cells(&["date", "date"])));
+        // Header words in the wrong column are content
+        assert!(!is_wrapped_header_row(&cells(&[
+            "", "", "", "", "", "date"
+        ])));
+        assert!(!is_wrapped_header_row(&cells(&[
+            "", "", "", "", "", "", "", "", "", ""
+        ])));
+        assert!(!is_wrapped_header_row(&cells(&[
+            "",
+            "",
+            "",
+            "",
+            "",
+            "KUWAIT 13022"
+        ])));
+    }
+
+    #[test]
+    fn test_classify_rows_wrapped_header_only_above_data() {
+        let boundaries = [0.0, 50.0, 100.0, 150.0, 200.0, 250.0, 300.0];
+        let row = |y: f32, cells: &[(f32, &str)]| TableRow {
+            y,
+            cells: cells.iter().map(|(x, t)| (*x, t.to_string())).collect(),
+        };
+        let rows = vec![
+            row(500.0, &[(10.0, "Record"), (60.0, "Last")]),
+            row(490.0, &[(10.0, "creation"), (60.0, "Update")]),
+            row(480.0, &[(10.0, "1997-03-01"), (110.0, "AAAARSBG")]),
+            // Wrapped content that happens to be a header word of its column
+            row(470.0, &[(260.0, "address")]),
+        ];
+
+        let kinds: Vec<RowKind> = classify_rows(rows, &boundaries)
+            .into_iter()
+            .map(|row| row.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                RowKind::Header,
+                RowKind::Header,
+                RowKind::Data,
+                RowKind::Continuation
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_data_row() {
+        assert!(is_data_row(&[
+            "1997-03-01".to_string(),
+            "2024-06-06".to_string()
+        ]));
+        assert!(is_data_row(&["2021-05-22".to_string()]));
+        assert!(!is_data_row(&["Record".to_string()]));
+        assert!(!is_data_row(&["".to_string()]));
+    }
+
+    #[test]
+    fn test_is_data_row_edge_cases() {
+        // Too short
+        assert!(!is_data_row(&["2021-05".to_string()]));
+        // Invalid format
+        assert!(!is_data_row(&["21-05-2021".to_string()]));
+        // Empty cells
+        assert!(!is_data_row(&[]));
+        // Non-digit year
+        assert!(!is_data_row(&["ABCD-05-22".to_string()]));
+    }
+
+    #[test]
+    fn test_group_into_rows_empty() {
+        let elements: Vec<TextElement> = vec![];
+        let rows = group_into_rows(elements, Y_TOLERANCE);
+        assert!(rows.is_empty());
+    }
+
+    #[test]
+    fn test_group_into_rows_single_element() {
+        let elements = vec![TextElement {
+            text: "Hello".to_string(),
+            x: 10.0,
+            y: 100.0,
+        }];
+        let rows = group_into_rows(elements, Y_TOLERANCE);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].cells.len(), 1);
+        assert_eq!(rows[0].cells[0].1, "Hello");
+    }
+
+    #[test]
+    fn test_group_into_rows_same_line() {
+        let elements = vec![
+            TextElement {
+                text: "First".to_string(),
+                x: 10.0,
+                y: 99.0,
+            },
+            TextElement {
+                text: "Second".to_string(),
+                x: 50.0,
+                y: 100.0, // Both 99/3=33 and 100/3=33.3 round to 33
+            },
+        ];
+        let rows = group_into_rows(elements, Y_TOLERANCE);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].cells.len(), 2);
+    }
+
+    #[test]
+    fn test_group_into_rows_different_lines() {
+        let elements = vec![
+            TextElement {
+                text: "Line1".to_string(),
+                x: 10.0,
+                y: 100.0,
+            },
+            TextElement {
+                text: "Line2".to_string(),
+                x: 10.0,
+                y: 80.0, // Different line
+            },
+        ];
+        let rows = group_into_rows(elements, Y_TOLERANCE);
+        assert_eq!(rows.len(), 2);
+        // Rows should be sorted by Y descending
+        assert_eq!(rows[0].cells[0].1, "Line1");
+        assert_eq!(rows[1].cells[0].1, "Line2");
+    }
+
+    #[test]
+    fn test_assign_cells_to_columns() {
+        let boundaries = vec![0.0, 50.0, 100.0, f32::MAX];
+        let row = TableRow {
+            y: 100.0,
+            cells: vec![
+                (10.0, "Col1".to_string()),
+                (60.0, "Col2".to_string()),
+                (110.0, "Col3".to_string()),
+            ],
+        };
+        let columns = assign_cells_to_columns(&row, &boundaries);
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0], "Col1");
+        assert_eq!(columns[1], "Col2");
+        assert_eq!(columns[2], "Col3");
+    }
+
+    #[test]
+    fn test_assign_cells_to_columns_multiple_in_same_column() {
+        let boundaries = vec![0.0, 100.0, f32::MAX];
+        let row = TableRow {
+            y: 100.0,
+            cells: vec![(10.0, "First".to_string()), (30.0, "Second".to_string())],
+        };
+        let columns = assign_cells_to_columns(&row, &boundaries);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0], "First Second");
+    }
+
+    #[test]
+    fn test_assign_cells_to_columns_empty_columns() {
+        let boundaries = vec![0.0, 50.0, 100.0, f32::MAX];
+        let row = TableRow {
+            y: 100.0,
+            cells: vec![(60.0, "OnlyCol2".to_string())],
+        };
+        let columns = assign_cells_to_columns(&row, &boundaries);
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0], "");
+        assert_eq!(columns[1], "OnlyCol2");
+        assert_eq!(columns[2], "");
+    }
+
+    #[test]
+    fn test_merge_continuation_row() {
+        let mut record = vec![
+            "2021-01-01".to_string(),
+            "".to_string(),
+            "ABCD1234".to_string(),
+        ];
+        let continuation = vec!["".to_string(), "continued".to_string(), "more".to_string()];
+        merge_continuation_row(&mut record, &continuation);
+        assert_eq!(record[0], "2021-01-01");
+        assert_eq!(record[1], "continued");
+        assert_eq!(record[2], "ABCD1234 more");
+    }
+
+    #[test]
+    fn test_merge_continuation_row_empty_continuation() {
+        let mut record = vec!["Original".to_string()];
+        let continuation = vec!["".to_string()];
+        merge_continuation_row(&mut record, &continuation);
+        assert_eq!(record[0], "Original");
+    }
+}
@@ -0,0 +1,890 @@
+//! Resource limits against hostile or corrupt PDFs.
+//!
+//! The PDF is parsed in-process, on a dirty scheduler when called from the
+//! BEAM. A crafted file could otherwise exhaust memory (e.g. a compressed
+//! stream that inflates to gigabytes) or keep a scheduler busy indefinitely.
+//! Every limit is optional; `None` means unlimited.
+
+use crate::repair::object_header;
+use anyhow::Result;
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use pdf::object::{NoResolve, PlainRef};
+use pdf::parser::{parse_indirect_object, parse_with_lexer, Lexer, ParseFlags};
+use pdf::primitive::{Dictionary, Primitive};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// Configurable guards checked during extraction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Limits {
+    /// Maximum number of pages in the document, including the cover.
+    pub max_pages: Option<usize>,
+    /// Maximum number of content stream operations on a single page.
+    pub max_operations_per_page: Option<usize>,
+    /// Maximum decoded size in bytes of any single stream. Streams that can't
+    /// be measured, such as one with an unsupported filter, count as exceeding it.
+    pub max_stream_size: Option<usize>,
+    /// Maximum number of text elements extracted from the whole document,
+    /// checked after each page, whose size is bounded by the operation limit.
+    pub max_text_elements: Option<usize>,
+    /// Maximum wall-clock time for the extraction, checked between pages and
+    /// while a page's content is parsed.
+    pub timeout: Option<Duration>,
+    /// Maximum size in bytes of the PDF read from a reader, and after
+    /// decompressing gzip or zip input.
+    pub max_input_size: Option<usize>,
+}
+
+/// The limit a [`LimitExceeded`] error refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum Limit {
+    MaxPages,
+    MaxOperationsPerPage,
+    MaxStreamSize,
+    MaxTextElements,
+    Timeout,
+    MaxInputSize,
+}
+
+/// Extraction was stopped because the PDF exceeded one of the [`Limits`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitExceeded {
+    pub limit: Limit,
+    /// The configured maximum (milliseconds for [`Limit::Timeout`]).
+    pub max: u64,
+    /// 1-based page being processed when the limit was exceeded, if any.
+    pub page: Option<usize>,
+}
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self.limit {
+            Limit::MaxPages => "pages",
+            Limit::MaxOperationsPerPage => "operations per page",
+            Limit::MaxStreamSize => "bytes in a decompressed stream",
+            Limit::MaxTextElements => "text elements",
+            Limit::Timeout => "milliseconds of extraction time",
+            Limit::MaxInputSize => "bytes of decompressed input",
+        };
+        write!(f, "PDF exceeds the limit of {} {}", self.max, description)?;
+        if let Some(page) = self.page {
+            write!(f, " on page {}", page)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl Limits {
+    pub(crate) fn check_pages(&self, pages: usize) -> Result<(), LimitExceeded> {
+        check(self.max_pages, pages, Limit::MaxPages, None)
+    }
+
+    pub(crate) fn check_operations(
+        &self,
+        operations: usize,
+        page: usize,
+    ) -> Result<(), LimitExceeded> {
+        check(
+            self.max_operations_per_page,
+            operations,
+            Limit::MaxOperationsPerPage,
+            Some(page),
+        )
+    }
+
+    pub(crate) fn check_text_elements(
+        &self,
+        text_elements: usize,
+        page: usize,
+    ) -> Result<(), LimitExceeded> {
+        check(
+            self.max_text_elements,
+            text_elements,
+            Limit::MaxTextElements,
+            Some(page),
+        )
+    }
+
+    pub(crate) fn check_timeout(&self, started: Instant, page: usize) -> Result<(), LimitExceeded> {
+        self.check_deadline(started, Some(page))
+    }
+
+    fn check_deadline(&self, started: Instant, page: Option<usize>) -> Result<(), LimitExceeded> {
+        match self.timeout {
+            Some(timeout) if started.elapsed() > timeout => Err(LimitExceeded {
+                limit: Limit::Timeout,
+                max: timeout.as_millis() as u64,
+                page,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decompress all of `reader`, failing once it exceeds `max_input_size`.
+    pub(crate) fn read_input(&self, mut reader: impl Read) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        match self.max_input_size {
+            Some(max) => reader.take(max as u64 + 1).read_to_end(&mut data)?,
+            None => reader.read_to_end(&mut data)?,
+        };
+        check(self.max_input_size, data.len(), Limit::MaxInputSize, None)?;
+        Ok(data)
+    }
+
+    /// Check no stream in the raw PDF decodes past `max_stream_size`.
+    ///
+    /// The `pdf` crate decompresses streams in full before we see them, so this
+    /// scans the file beforehand and decodes each stream's filter chain,
+    /// stopping as soon as the limit is crossed. A stream the guard can't
+    /// measure counts as exceeding the limit: one with an unsupported or
+    /// indirect filter, a `/Length` that can't be resolved or doesn't end at
+    /// `endstream`, or data that fails to decode.
+    ///
+    /// In an encrypted PDF only cross-reference streams can be measured before
+    /// the file is opened. The others are returned, to be measured with
+    /// [`Limits::check_encrypted_streams`] once they can be decrypted.
+    pub(crate) fn check_streams(
+        &self,
+        data: &[u8],
+        started: Instant,
+    ) -> Result<Vec<EncryptedStream>, LimitExceeded> {
+        let Some(max) = self.max_stream_size else {
+            return Ok(Vec::new());
+        };
+        let encrypted = find(data, b"/Encrypt", 0).is_some();
+        let mut encrypted_streams = Vec::new();
+
+        for (index, stream) in streams(data).enumerate() {
+            // Decoding every stream can take a while
+            if index % 64 == 63 {
+                self.check_deadline(started, None)?;
+            }
+
+            let Some(stream) = stream else {
+                return Err(exceeded(max));
+            };
+            let filters = filters(&stream.dictionary).ok_or_else(|| exceeded(max))?;
+            let is_xref = matches!(stream.dictionary.get("Type"), Some(Primitive::Name(name)) if name == "XRef");
+            if encrypted && !is_xref {
+                encrypted_streams.push(EncryptedStream {
+                    id: stream.id,
+                    data: stream.data,
+                    filters,
+                });
+                continue;
+            }
+
+            let size = decoded_size(&data[stream.data], &filters, max).unwrap_or(usize::MAX);
+            check(Some(max), size, Limit::MaxStreamSize, None)?;
+        }
+
+        Ok(encrypted_streams)
+    }
+
+    /// Check the streams [`Limits::check_streams`] left for after decryption.
+    ///
+    /// `resolver` decrypts them as the `pdf` crate does before decoding. Object
+    /// streams the crate reads to open the file, such as one holding the
+    /// catalog, are decoded before this can run.
+    pub(crate) fn check_encrypted_streams(
+        &self,
+        resolver: &impl pdf::object::Resolve,
+        streams: &[EncryptedStream],
+    ) -> Result<(), LimitExceeded> {
+        let Some(max) = self.max_stream_size else {
+            return Ok(());
+        };
+
+        for stream in streams {
+            let size = resolver
+                .stream_data(stream.id, stream.data.clone())
+                .ok()
+                .and_then(|data| decoded_size(&data, &stream.filters, max))
+                .unwrap_or(usize::MAX);
+            check(Some(max), size, Limit::MaxStreamSize, None)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A stream of an encrypted PDF, measured once it can be decrypted.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EncryptedStream {
+    id: PlainRef,
+    data: Range<usize>,
+    filters: Vec<Filter>,
+}
+
+/// A stream filter the guard knows how to measure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Filter {
+    Flate,
+    Lzw {
+        early_change: bool,
+    },
+    Ascii85,
+    AsciiHex,
+    RunLength,
+    /// Image data, which extraction never decodes.
+    Image,
+}
+
+fn exceeded(max: usize) -> LimitExceeded {
+    LimitExceeded {
+        limit: Limit::MaxStreamSize,
+        max: max as u64,
+        page: None,
+    }
+}
+
+fn check(
+    max: Option<usize>,
+    value: usize,
+    limit: Limit,
+    page: Option<usize>,
+) -> Result<(), LimitExceeded> {
+    match max {
+        Some(max) if value > max => Err(LimitExceeded {
+            limit,
+            max: max as u64,
+            page,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Position of the first `needle` in `haystack` at or after `from`.
+pub(crate) fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| position + from)
+}
+
+/// A stream found in the raw file, as the `pdf` crate would parse it.
+struct RawStream {
+    id: PlainRef,
+    dictionary: Dictionary,
+    /// Position of the data in the file, as given by `/Length`.
+    data: Range<usize>,
+}
+
+/// Every stream in the file, or `None` for a `stream` keyword that can't be
+/// tied to an object with a usable `/Length`.
+///
+/// Any `stream` keyword after `>>` or a comment and before an end of line is
+/// taken for a stream, including ones inside other streams' data, as a
+/// cross-reference table could point at an object there. The object header is searched for
+/// back to the previous keyword only, so the scan stays linear.
+fn streams(data: &[u8]) -> impl Iterator<Item = Option<RawStream>> + '_ {
+    let mut position = 0;
+    let mut previous_keyword = 0;
+    let mut objects = None;
+
+    std::iter::from_fn(move || loop {
+        let keyword = find(data, b"stream", position)?;
+        position = keyword + b"stream".len();
+
+        // As the pdf crate reads it, the keyword ends with "\n" or "\r\n"
+        let start = if data[position..].starts_with(b"\r\n") {
+            position + 2
+        } else if data[position..].starts_with(b"\n") {
+            position + 1
+        } else {
+            continue;
+        };
+        // The keyword must follow a dictionary, perhaps with a comment between
+        let before = data[..keyword].trim_ascii_end();
+        let last_line = before
+            .rsplit(|&byte| byte == b'\n')
+            .next()
+            .unwrap_or_default();
+        let after_token = matches!(
+            data[..keyword].last(),
+            Some(0 | b' ' | b'\r' | b'\n' | b'\t' | b'>')
+        );
+        if !after_token || !(before.ends_with(b">>") || last_line.contains(&b'%')) {
+            continue;
+        }
+
+        let header = previous_keyword..keyword;
+        previous_keyword = position;
+        return Some(raw_stream(data, header, start, &mut objects));
+    })
+}
+
+/// The stream whose keyword ends `header` and whose data starts at `start`.
+///
+/// `objects` caches the offsets of objects, looked up for an indirect `/Length`.
+fn raw_stream(
+    data: &[u8],
+    header: Range<usize>,
+    start: usize,
+    objects: &mut Option<HashMap<u32, Option<usize>>>,
+) -> Option<RawStream> {
+    let obj = data[header.clone()]
+        .windows(3)
+        .rposition(|window| window == b"obj")?
+        + header.start;
+    let (_, number, generation) = object_header(data, obj)?;
+
+    let mut lexer = Lexer::new(&data[obj + b"obj".len()..header.end]);
+    if !lexer.next().ok()?.equals(b"<<") {
+        return None;
+    }
+    let dictionary = parse_dictionary(&mut lexer)?;
+    // Nothing but whitespace may separate the dictionary from the keyword, as
+    // the pdf crate finds the data differently after a comment
+    if !lexer.get_remaining_slice().trim_ascii().is_empty() {
+        return None;
+    }
+
+    let length = match dictionary.get("Length")? {
+        &Primitive::Integer(length) => usize::try_from(length).ok()?,
+        Primitive::Reference(reference) => {
+            let objects = objects.get_or_insert_with(|| object_offsets(data));
+            let offset = (*objects.get(&u32::try_from(reference.id).ok()?)?)?;
+            let mut lexer = Lexer::new(&data[offset..]);
+            let (id, length) =
+                parse_indirect_object(&mut lexer, &NoResolve, None, ParseFlags::INTEGER).ok()?;
+            if id != *reference {
+                return None;
+            }
+            length.as_usize().ok()?
+        }
+        _ => return None,
+    };
+    let end = start.checked_add(length)?;
+    if !Lexer::new(data.get(end..)?)
+        .next()
+        .ok()?
+        .equals(b"endstream")
+    {
+        return None;
+    }
+
+    Some(RawStream {
+        id: PlainRef {
+            id: number.into(),
+            gen: generation.into(),
+        },
+        dictionary,
+        data: start..end,
+    })
+}
+
+/// Parse a dictionary after its `<<`, as the `pdf` crate does.
+///
+/// Keys aren't unescaped and a repeated key replaces the earlier value, so
+/// `/Length` and `/Filter` are the ones the crate uses.
+fn parse_dictionary(lexer: &mut Lexer) -> Option<Dictionary> {
+    let mut dictionary = Dictionary::new();
+    loop {
+        let token = lexer.next().ok()?;
+        if token.equals(b">>") {
+            return Some(dictionary);
+        }
+        if !token.starts_with(b"/") {
+            return None;
+        }
+        let key = token.reslice(1..).to_name().ok()?;
+        let value = parse_with_lexer(lexer, &NoResolve, ParseFlags::ANY).ok()?;
+        dictionary.insert(key, value);
+    }
+}
+
+/// Offset of each `N G obj` header by object number, or `None` for a number
+/// defined more than once, which can't be resolved without the xref table.
+fn object_offsets(data: &[u8]) -> HashMap<u32, Option<usize>> {
+    let mut objects = HashMap::new();
+    let mut position = 0;
+
+    while let Some(keyword) = find(data, b"obj", position) {
+        position = keyword + b"obj".len();
+        if let Some((offset, number, _)) = object_header(data, keyword) {
+            objects
+                .entry(number)
+                .and_modify(|offset| *offset = None)
+                .or_insert(Some(offset));
+        }
+    }
+
+    objects
+}
+
+/// Size of `stream` once `filters` are applied, or `None` if it can't be measured.
+///
+/// Decoding stops once the output passes `max`. Image filters end the chain:
+/// extraction never decodes image data, only the filters applied before them.
+fn decoded_size(stream: &[u8], filters: &[Filter], max: usize) -> Option<usize> {
+    let mut decoded = Cow::Borrowed(stream);
+
+    for filter in filters {
+        decoded = Cow::Owned(match *filter {
+            Filter::Flate => inflate(&decoded, max)?,
+            Filter::Lzw { early_change } => lzw_decode(&decoded, early_change, max)?,
+            Filter::Ascii85 => pdf::enc::decode_85(&decoded).ok()?,
+            Filter::AsciiHex => pdf::enc::decode_hex(&decoded).ok()?,
+            Filter::RunLength => run_length_decode(&decoded, max)?,
+            Filter::Image => break,
+        });
+        if decoded.len() > max {
+            break;
+        }
+    }
+
+    Some(decoded.len())
+}
+
+/// Filters of a stream dictionary, in the order they're applied.
+///
+/// `None` when `/Filter` isn't a name or an array of names, e.g. an indirect
+/// reference, or names a filter the guard can't measure.
+fn filters(dictionary: &Dictionary) -> Option<Vec<Filter>> {
+    let names = match dictionary.get("Filter") {
+        None => return Some(Vec::new()),
+        Some(Primitive::Name(name)) => vec![name.as_str()],
+        Some(Primitive::Array(names)) => names
+            .iter()
+            .map(|name| name.as_name().ok())
+            .collect::<Option<_>>()?,
+        Some(_) => return None,
+    };
+
+    let parameters = |index: usize| match dictionary.get("DecodeParms") {
+        Some(Primitive::Dictionary(parameters)) if index == 0 => Some(parameters),
+        Some(Primitive::Array(parameters)) => match parameters.get(index) {
+            Some(Primitive::Dictionary(parameters)) => Some(parameters),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| match name {
+            "FlateDecode" => Some(Filter::Flate),
+            "LZWDecode" => Some(Filter::Lzw {
+                // Early code size changes are the default
+                early_change: !matches!(
+                    parameters(index).and_then(|parameters| parameters.get("EarlyChange")),
+                    Some(Primitive::Integer(0))
+                ),
+            }),
+            "ASCII85Decode" => Some(Filter::Ascii85),
+            "ASCIIHexDecode" => Some(Filter::AsciiHex),
+            "RunLengthDecode" => Some(Filter::RunLength),
+            "DCTDecode" | "JPXDecode" | "CCITTFaxDecode" | "JBIG2Decode" => Some(Filter::Image),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Inflate zlib or, failing that, raw deflate data up to just past `max` bytes.
+///
+/// Data that is corrupt from the start can't be measured, but output before a
+/// later error counts, as that's all any decoder could produce from it.
+fn inflate(data: &[u8], max: usize) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let zlib = ZlibDecoder::new(data)
+        .take(max as u64 + 1)
+        .read_to_end(&mut decoded);
+    if zlib.is_err() && decoded.is_empty() {
+        DeflateDecoder::new(data)
+            .take(max as u64 + 1)
+            .read_to_end(&mut decoded)
+            .ok()?;
+    }
+    Some(decoded)
+}
+
+/// LZW-decode `data` up to just past `max` bytes, with the `pdf` crate's parameters.
+fn lzw_decode(data: &[u8], early_change: bool, max: usize) -> Option<Vec<u8>> {
+    let mut decoder = if early_change {
+        weezl::decode::Decoder::with_tiff_size_switch(weezl::BitOrder::Msb, 9)
+    } else {
+        weezl::decode::Decoder::new(weezl::BitOrder::Msb, 9)
+    };
+    let mut decoded = Vec::new();
+    let mut buffer = [0; 4096];
+    let mut input = data;
+
+    while decoded.len() <= max {
+        let result = decoder.decode_bytes(input, &mut buffer);
+        decoded.extend_from_slice(&buffer[..result.consumed_out]);
+        input = &input[result.consumed_in..];
+        match result.status.ok()? {
+            weezl::LzwStatus::Ok => {}
+            weezl::LzwStatus::Done | weezl::LzwStatus::NoProgress => break,
+        }
+    }
+    Some(decoded)
+}
+
+/// Run-length decode `data` up to just past `max` bytes.
+fn run_length_decode(data: &[u8], max: usize) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut rest = data;
+
+    while let Some((&length, tail)) = rest.split_first() {
+        if decoded.len() > max {
+            break;
+        }
+        match length {
+            0..=127 => {
+                let (literal, tail) = tail.split_at_checked(length as usize + 1)?;
+                decoded.extend_from_slice(literal);
+                rest = tail;
+            }
+            128 => break,
+            _ => {
+                let (&byte, tail) = tail.split_first()?;
+                decoded.resize(decoded.len() + 257 - length as usize, byte);
+                rest = tail;
+            }
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use pdf::enc::StreamFilter;
+    use std::io::Write;
+
+    fn zlib(content: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn pdf_with_stream(entries: &str, stream: &[u8]) -> Vec<u8> {
+        let mut pdf = format!(
+            "%PDF-1.4\n1 0 obj\n<< /Length {} {} >>\nstream\n",
+            stream.len(),
+            entries
+        )
+        .into_bytes();
+        pdf.extend_from_slice(stream);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n");
+        pdf
+    }
+
+    fn dictionary(text: &str) -> Dictionary {
+        let mut lexer = Lexer::new(text.as_bytes());
+        assert!(lexer.next().unwrap().equals(b"<<"));
+        parse_dictionary(&mut lexer).unwrap()
+    }
+
+    #[test]
+    fn test_check_streams() {
+        let bomb = pdf_with_stream("/Filter /FlateDecode", &zlib(&[0; 100_000]));
+        let limits = Limits {
+            max_stream_size: Some(50_000),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            limits.check_streams(&bomb, Instant::now()),
+            Err(LimitExceeded {
+                limit: Limit::MaxStreamSize,
+                max: 50_000,
+                page: None,
+            })
+        );
+        assert!(Limits::default()
+            .check_streams(&bomb, Instant::now())
+            .is_ok());
+
+        let small = pdf_with_stream("/Filter [ /FlateDecode ]", &zlib(&[0; 50_000]));
+        assert_eq!(limits.check_streams(&small, Instant::now()), Ok(vec![]));
+
+        let uncompressed = pdf_with_stream("", &[b'x'; 50_000]);
+        assert!(limits.check_streams(&uncompressed, Instant::now()).is_ok());
+
+        // Image data isn't decoded by extraction
+        let image = pdf_with_stream("/Filter /DCTDecode", &[0xff; 1_000]);
+        assert!(limits.check_streams(&image, Instant::now()).is_ok());
+
+        // Words ending in "stream" inside the data aren't streams
+        let text = pdf_with_stream("", b"upstream\n and stream\n");
+        assert!(limits.check_streams(&text, Instant::now()).is_ok());
+    }
+
+    #[test]
+    fn test_check_streams_measures_up_to_length() {
+        let limits = Limits {
+            max_stream_size: Some(50_000),
+            ..Limits::default()
+        };
+
+        // An "endstream" inside the data doesn't end the stream early
+        let mut data = b"% endstream\n".to_vec();
+        data.extend_from_slice(&[b' '; 60_000]);
+        let pdf = pdf_with_stream("", &data);
+        assert!(limits.check_streams(&pdf, Instant::now()).is_err());
+
+        let indirect = |length: usize, objects: &str| {
+            let mut pdf = b"%PDF-1.4\n1 0 obj\n<< /Length 2 0 R >>\nstream\n".to_vec();
+            pdf.extend_from_slice(&data);
+            pdf.extend_from_slice(b"\nendstream\nendobj\n");
+            pdf.extend_from_slice(format!("2 0 obj\n{}\nendobj\n{}", length, objects).as_bytes());
+            pdf
+        };
+        assert!(limits
+            .check_streams(&indirect(data.len(), ""), Instant::now())
+            .is_err());
+        assert!(Limits {
+            max_stream_size: Some(100_000),
+            ..Limits::default()
+        }
+        .check_streams(&indirect(data.len(), ""), Instant::now())
+        .is_ok());
+
+        // Which definition the cross-reference table points at is unknown
+        let duplicate = indirect(data.len(), "2 0 obj\n11\nendobj\n");
+        assert!(Limits {
+            max_stream_size: Some(100_000),
+            ..Limits::default()
+        }
+        .check_streams(&duplicate, Instant::now())
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_streams_with_filter_chains() {
+        let limits = Limits {
+            max_stream_size: Some(50_000),
+            ..Limits::default()
+        };
+
+        let ascii85 = pdf::enc::encode(&zlib(&[0; 100_000]), &StreamFilter::ASCII85Decode).unwrap();
+        let bomb = pdf_with_stream("/Filter [/ASCII85Decode /FlateDecode]", &ascii85);
+        assert_eq!(
+            limits
+                .check_streams(&bomb, Instant::now())
+                .unwrap_err()
+                .limit,
+            Limit::MaxStreamSize
+        );
+
+        let hex = pdf::enc::encode(&zlib(&[0; 10_000]), &StreamFilter::ASCIIHexDecode).unwrap();
+        let small = pdf_with_stream("/Filter [/ASCIIHexDecode /FlateDecode]", &hex);
+        assert!(limits.check_streams(&small, Instant::now()).is_ok());
+
+        let nested = pdf_with_stream(
+            "/Filter [/FlateDecode /FlateDecode]",
+            &zlib(&zlib(&[0; 100_000])),
+        );
+        assert!(limits.check_streams(&nested, Instant::now()).is_err());
+    }
+
+    #[test]
+    fn test_check_streams_with_lzw() {
+        let limits = Limits {
+            max_stream_size: Some(50_000),
+            ..Limits::default()
+        };
+        let larger = Limits {
+            max_stream_size: Some(100_000),
+            ..Limits::default()
+        };
+        let content = [0; 100_000];
+
+        let encoded = weezl::encode::Encoder::with_tiff_size_switch(weezl::BitOrder::Msb, 9)
+            .encode(&content)
+            .unwrap();
+        // The pdf crate decodes it back in full
+        let filter = StreamFilter::LZWDecode(Default::default());
+        assert_eq!(pdf::enc::decode(&encoded, &filter).unwrap(), content);
+
+        let bomb = pdf_with_stream("/Filter /LZWDecode", &encoded);
+        assert_eq!(
+            limits
+                .check_streams(&bomb, Instant::now())
+                .unwrap_err()
+                .limit,
+            Limit::MaxStreamSize
+        );
+        assert!(larger.check_streams(&bomb, Instant::now()).is_ok());
+
+        let encoded = weezl::encode::Encoder::new(weezl::BitOrder::Msb, 9)
+            .encode(&content)
+            .unwrap();
+        let late = pdf_with_stream(
+            "/Filter /LZWDecode /DecodeParms << /EarlyChange 0 >>",
+            &encoded,
+        );
+        assert!(limits.check_streams(&late, Instant::now()).is_err());
+        assert!(larger.check_streams(&late, Instant::now()).is_ok());
+    }
+
+    #[test]
+    fn test_check_streams_that_cant_be_measured() {
+        let limits = Limits {
+            max_stream_size: Some(50_000),
+            ..Limits::default()
+        };
+        let unmeasurable = |pdf: &[u8]| limits.check_streams(pdf, Instant::now()).is_err();
+
+        let corrupt = pdf_with_stream("/Filter /FlateDecode", b"\x8f\x12\xa4\xc3\x07");
+        assert!(unmeasurable(&corrupt));
+
+        let indirect = pdf_with_stream("/Filter 5 0 R", &zlib(b"BT ET"));
+        assert!(unmeasurable(&indirect));
+
+        let unknown = pdf_with_stream("/Filter /Crypt", b"BT ET");
+        assert!(unmeasurable(&unknown));
+        assert!(Limits::default()
+            .check_streams(&unknown, Instant::now())
+            .is_ok());
+
+        let abbreviated = pdf_with_stream("/Filter /Fl", &zlib(b"BT ET"));
+        assert!(unmeasurable(&abbreviated));
+
+        // The last /Length is the one the pdf crate uses
+        let inconsistent = pdf_with_stream("/Length 2", b"BT ET");
+        assert!(unmeasurable(&inconsistent));
+
+        let missing = b"%PDF-1.4\n1 0 obj\n<< >>\nstream\nBT ET\nendstream\nendobj\n";
+        assert!(unmeasurable(missing));
+
+        let unresolved = b"%PDF-1.4\n1 0 obj\n<< /Length 2 0 R >>\nstream\nBT ET\nendstream\n";
+        assert!(unmeasurable(unresolved));
+
+        let comment = b"%PDF-1.4\n1 0 obj\n<< /Length 5 >> % data\nstream\nBT ET\nendstream\n";
+        assert!(unmeasurable(comment));
+    }
+
+    #[test]
+    fn test_check_streams_of_an_encrypted_pdf() {
+        let limits = Limits {
+            max_stream_size: Some(50_000),
+            ..Limits::default()
+        };
+        let mut pdf = pdf_with_stream("/Filter /FlateDecode", b"\x8f\x12\xa4\xc3\x07");
+        pdf.extend_from_slice(
+            b"2 0 obj\n<< /Type /XRef /Length 3 /Encrypt 3 0 R >>\nstream\nabc\nendstream\n",
+        );
+
+        // Only the cross-reference stream is measured before decryption
+        let streams = limits.check_streams(&pdf, Instant::now()).unwrap();
+        assert_eq!(streams.len(), 1);
+        assert_eq!(streams[0].id, PlainRef { id: 1, gen: 0 });
+        assert_eq!(streams[0].filters, vec![Filter::Flate]);
+        assert_eq!(&pdf[streams[0].data.clone()], b"\x8f\x12\xa4\xc3\x07");
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(
+            filters(&dictionary("<< /Length 5 /Filter /FlateDecode >>")),
+            Some(vec![Filter::Flate])
+        );
+        assert_eq!(
+            filters(&dictionary("<</Filter[/ASCII85Decode/FlateDecode]>>")),
+            Some(vec![Filter::Ascii85, Filter::Flate])
+        );
+        assert_eq!(
+            filters(&dictionary("<< /Filter [/FlateDecode /DCTDecode] >>")),
+            Some(vec![Filter::Flate, Filter::Image])
+        );
+        assert_eq!(filters(&dictionary("<< /Length 5 >>")), Some(vec![]));
+        assert_eq!(filters(&dictionary("<< /Filter 5 0 R >>")), None);
+        assert_eq!(filters(&dictionary("<< /Filter [/FlateDecode 5] >>")), None);
+
+        assert_eq!(
+            filters(&dictionary("<< /Filter /LZWDecode >>")),
+            Some(vec![Filter::Lzw { early_change: true }])
+        );
+        assert_eq!(
+            filters(&dictionary(
+                "<< /Filter /LZWDecode /DecodeParms << /EarlyChange 0 >> >>"
+            )),
+            Some(vec![Filter::Lzw {
+                early_change: false
+            }])
+        );
+        assert_eq!(
+            filters(&dictionary(
+                "<< /Filter [/FlateDecode /LZWDecode] /DecodeParms [null << /EarlyChange 0 >>] >>"
+            )),
+            Some(vec![
+                Filter::Flate,
+                Filter::Lzw {
+                    early_change: false
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_run_length_decode() {
+        assert_eq!(
+            run_length_decode(b"\x02abc\xfdx\x80ignored", 100),
+            Some(b"abcxxxx".to_vec())
+        );
+        // Stops at the first run past the limit
+        let runs = [0x81, 0].repeat(100);
+        assert_eq!(run_length_decode(&runs, 1_000).unwrap().len(), 1_024);
+        assert_eq!(run_length_decode(b"\x05ab", 100), None);
+    }
+
+    #[test]
+    fn test_streams_dictionary_search_is_bounded() {
+        // Searching back from each stream for "obj" stops at the previous stream
+        let mut data = b"%PDF-1.4\n1 0 obj\n".to_vec();
+        for _ in 0..20_000 {
+            data.extend_from_slice(b"<< /Length 16 >>\nstream\nxxxxxxxxxxxxxxxx\nendstream\n");
+        }
+        assert_eq!(streams(&data).count(), 20_000);
+        assert!(streams(&data).skip(1).all(|stream| stream.is_none()));
+    }
+
+    #[test]
+    fn test_check_counts() {
+        let limits = Limits {
+            max_pages: Some(10),
+            max_operations_per_page: Some(100),
+            max_text_elements: Some(1000),
+            ..Limits::default()
+        };
+
+        assert!(limits.check_pages(10).is_ok());
+        assert_eq!(limits.check_pages(11).unwrap_err().limit, Limit::MaxPages);
+        assert_eq!(limits.check_operations(101, 3).unwrap_err().page, Some(3));
+        assert!(limits.check_text_elements(1000, 3).is_ok());
+        assert_eq!(
+            limits.check_text_elements(1001, 3).unwrap_err().to_string(),
+            "PDF exceeds the limit of 1000 text elements on page 3"
+        );
+    }
+
+    #[test]
+    fn test_check_timeout() {
+        let limits = Limits {
+            timeout: Some(Duration::from_millis(5)),
+            ..Limits::default()
+        };
+        let started = Instant::now() - Duration::from_millis(10);
+
+        assert_eq!(
+            limits.check_timeout(started, 2).unwrap_err().limit,
+            Limit::Timeout
+        );
+        assert!(limits.check_timeout(Instant::now(), 2).is_ok());
+        assert!(Limits::default().check_timeout(started, 2).is_ok());
+    }
+}
@@ -0,0 +1,151 @@
+//! Document-level information about a BIC directory PDF.
+//!
+//! The directory's edition is only printed on the cover page, as a date such
+//! as `04 Dec 2025` below the title. The PDF's Info dictionary adds the
+//! producer's title and timestamps when present.
+
+use crate::{
+    decode_pdf_string, extract_text_from_ops, group_into_rows, with_pdf, Date, ExtractOptions,
+    Y_TOLERANCE,
+};
+use anyhow::{Context, Result};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Information about the directory as a whole, read without extracting records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata {
+    /// Publication date printed on the cover page.
+    pub edition: Option<Date>,
+    /// Number of pages, including the cover.
+    pub pages: usize,
+    /// Title from the PDF's Info dictionary.
+    pub title: Option<String>,
+    /// Creation date from the PDF's Info dictionary.
+    pub created: Option<Date>,
+    /// Modification date from the PDF's Info dictionary.
+    pub modified: Option<Date>,
+    /// Whether the PDF's cross-reference table had to be rebuilt, when
+    /// [`ExtractOptions::repair`] is set.
+    pub repaired: bool,
+}
+
+/// Read the metadata of a BIC directory PDF.
+///
+/// Input is decompressed, decrypted and repaired as for extraction, using
+/// the same options. Only the cover page is parsed.
+pub fn metadata_from_bytes(data: impl AsRef<[u8]>, options: &ExtractOptions) -> Result<Metadata> {
+    let (mut metadata, repaired) = with_pdf(data.as_ref(), options, |file| {
+        let resolver = file.resolver();
+        let cover = file.get_page(0).context("Failed to get page 1")?;
+        let edition = match &cover.contents {
+            Some(contents) => {
+                let ops = contents
+                    .operations(&resolver)
+                    .context("Failed to parse operations on page 1")?;
+                let text = group_into_rows(extract_text_from_ops(&ops), Y_TOLERANCE)
+                    .into_iter()
+                    .flat_map(|row| row.cells.into_iter().map(|(_, text)| text))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                parse_edition(&text)
+            }
+            None => None,
+        };
+
+        let info = file.trailer.info_dict.as_ref();
+        let date = |date: &pdf::primitive::Date| {
+            Date::new(date.year.into(), date.month.into(), date.day.into())
+        };
+
+        Ok(Metadata {
+            edition,
+            pages: file.num_pages() as usize,
+            title: info
+                .and_then(|info| info.title.as_ref())
+                .map(decode_pdf_string),
+            created: info
+                .and_then(|info| info.creation_date.as_ref())
+                .and_then(date),
+            modified: info.and_then(|info| info.mod_date.as_ref()).and_then(date),
+            repaired: false,
+        })
+    })?;
+    metadata.repaired = repaired;
+    Ok(metadata)
+}
+
+/// Find the first `DD Mon YYYY` date in the cover page's text.
+fn parse_edition(text: &str) -> Option<Date> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.windows(3).find_map(|window| {
+        let [day, month, year] = window else {
+            return None;
+        };
+        if day.len() > 2 || year.len() != 4 {
+            return None;
+        }
+        let month = MONTHS.iter().position(|name| name == month)? as u32 + 1;
+        Date::new(year.parse().ok()?, month, day.parse().ok()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name);
+        std::fs::read(path).expect("Failed to read PDF")
+    }
+
+    #[test]
+    fn test_parse_edition() {
+        assert_eq!(
+            parse_edition("ISO BIC DIRECTORY This directory provides 04 Dec 2025 1 of 3934"),
+            Date::new(2025, 12, 4)
+        );
+        assert_eq!(parse_edition("1 of 3934"), None);
+        assert_eq!(parse_edition("31 Feb 2025"), None);
+        assert_eq!(parse_edition("004 Dec 2025"), None);
+    }
+
+    #[test]
+    fn test_metadata_from_fixture() {
+        let metadata = metadata_from_bytes(fixture("ISOBIC-mini.pdf"), &ExtractOptions::default())
+            .expect("Failed to read metadata");
+
+        assert_eq!(
+            metadata,
+            Metadata {
+                edition: Date::new(2025, 12, 4),
+                pages: 4,
+                title: None,
+                created: None,
+                modified: Date::new(2025, 12, 5),
+                repaired: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_metadata_from_encrypted_fixture() {
+        let data = fixture("ISOBIC-mini-aes128.pdf");
+        let options = ExtractOptions {
+            password: Some("user-secret".to_string()),
+            ..ExtractOptions::default()
+        };
+
+        let metadata = metadata_from_bytes(&data, &options).expect("Failed to read metadata");
+        assert_eq!(metadata.edition, Date::new(2025, 12, 4));
+
+        let error = metadata_from_bytes(&data, &ExtractOptions::default()).unwrap_err();
+        assert!(error.is::<crate::PasswordError>());
+    }
+}
@@ -0,0 +1,315 @@
+//! Recovery of PDFs whose cross-reference table is unusable.
+//!
+//! Truncated downloads lose the xref table and trailer at the end of the
+//! file, and some writers produce tables with wrong offsets. The objects
+//! themselves are usually intact, so the table can be rebuilt by scanning the
+//! file for `N G obj` ... `endobj` markers, as PDF viewers do. Objects stored
+//! in compressed object streams can't be found this way.
+//!
+//! The rebuilt table only lists the objects found, so its size follows the
+//! file's rather than the object numbers it claims.
+
+use crate::limits::find;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Largest object number ISO 32000 allows, in its implementation limits.
+const MAX_OBJECT_NUMBER: u32 = 8_388_607;
+
+/// Rebuild the cross-reference table of a damaged PDF.
+///
+/// Returns the data with a new xref table and trailer appended, which the PDF
+/// parser then uses in place of the damaged ones. An object defined more than
+/// once (e.g. by an incremental update) resolves to its last definition, and
+/// an object cut off before its `endobj` is left out.
+pub(crate) fn rebuild_xref(data: &[u8]) -> Result<Vec<u8>> {
+    let objects = scan_objects(data);
+    if objects.is_empty() {
+        anyhow::bail!("No objects found in the PDF");
+    }
+
+    let trailer = find_last(data, b"trailer").map(|position| &data[position..]);
+    let root = trailer
+        .and_then(|trailer| reference_after(trailer, b"/Root"))
+        .or_else(|| find_catalog(data, &objects))
+        .context("No document catalog found in the PDF")?;
+
+    let size = objects.keys().last().map_or(1, |number| number + 1);
+    let mut xref = "\nxref\n".to_string();
+    // A subsection per run of consecutive numbers, starting with the free
+    // object 0
+    let mut entries = std::iter::once((0, None))
+        .chain(
+            objects
+                .iter()
+                .map(|(&number, object)| (number, Some(object))),
+        )
+        .filter(|&(number, object)| number > 0 || object.is_none())
+        .peekable();
+    while let Some((first, object)) = entries.next() {
+        let mut run = vec![object];
+        while let Some((_, object)) =
+            entries.next_if(|&(number, _)| number == first + run.len() as u32)
+        {
+            run.push(object);
+        }
+        let _ = writeln!(xref, "{} {}", first, run.len());
+        for object in run {
+            match object {
+                Some(object) => {
+                    let _ = write!(xref, "{:010} {:05} n\r\n", object.offset, object.generation);
+                }
+                None => xref.push_str("0000000000 65535 f\r\n"),
+            }
+        }
+    }
+
+    let _ = write!(
+        xref,
+        "trailer\n<< /Size {} /Root {} {} R",
+        size, root.0, root.1
+    );
+    if let Some(trailer) = trailer {
+        for key in [&b"/Info"[..], b"/Encrypt"] {
+            if let Some((number, generation)) = reference_after(trailer, key) {
+                let _ = write!(
+                    xref,
+                    " {} {} {} R",
+                    String::from_utf8_lossy(key),
+                    number,
+                    generation
+                );
+            }
+        }
+        if let Some(id) = array_after(trailer, b"/ID") {
+            let _ = write!(xref, " /ID {}", String::from_utf8_lossy(id));
+        }
+    }
+
+    let mut repaired = data.to_vec();
+    // The xref section starts after the leading newline
+    let xref_offset = repaired.len() + 1;
+    let _ = write!(xref, " >>\nstartxref\n{}\n%%EOF\n", xref_offset);
+    repaired.extend_from_slice(xref.as_bytes());
+
+    Ok(repaired)
+}
+
+/// Where an object found by [`scan_objects`] starts, and its generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ObjectLocation {
+    offset: usize,
+    generation: u16,
+}
+
+/// Find every complete `N G obj` ... `endobj` object, by object number.
+///
+/// Numbers above ISO 32000's limit, or above the file's size, which numbering
+/// objects in order can't reach, are taken as damage and left out.
+fn scan_objects(data: &[u8]) -> BTreeMap<u32, ObjectLocation> {
+    let max_number =
+        u32::try_from(data.len()).map_or(MAX_OBJECT_NUMBER, |len| len.min(MAX_OBJECT_NUMBER));
+    let mut objects = BTreeMap::new();
+    let mut position = 0;
+
+    while let Some(keyword) = find(data, b"obj", position) {
+        position = keyword + b"obj".len();
+
+        let Some((offset, number, generation)) = object_header(data, keyword) else {
+            continue;
+        };
+        let Some(end) = find(data, b"endobj", position) else {
+            // Truncated, and nothing complete can follow
+            break;
+        };
+
+        if number <= max_number {
+            objects.insert(number, ObjectLocation { offset, generation });
+        }
+        // Skips stream data, which may contain anything
+        position = end + b"endobj".len();
+    }
+
+    objects
+}
+
+/// Parse the `N G` before an `obj` keyword at `keyword`.
+///
+/// Returns where the header starts, with the object and generation numbers.
+pub(crate) fn object_header(data: &[u8], keyword: usize) -> Option<(usize, u32, u16)> {
+    let before = &data[..keyword];
+    if !before.last()?.is_ascii_whitespace() {
+        // e.g. the end of "endobj"
+        return None;
+    }
+
+    let generation_end = before.iter().rposition(|b| !b.is_ascii_whitespace())? + 1;
+    let generation_start = digits_start(before, generation_end)?;
+    let number_end = before[..generation_start]
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())?
+        + 1;
+    if number_end == generation_start {
+        return None;
+    }
+    let number_start = digits_start(before, number_end)?;
+    if number_start > 0 && !data[number_start - 1].is_ascii_whitespace() {
+        return None;
+    }
+
+    let number = std::str::from_utf8(&data[number_start..number_end]).ok()?;
+    let generation = std::str::from_utf8(&data[generation_start..generation_end]).ok()?;
+    Some((number_start, number.parse().ok()?, generation.parse().ok()?))
+}
+
+/// Start of the run of digits ending at `end`, if there is one.
+fn digits_start(data: &[u8], end: usize) -> Option<usize> {
+    let start = data[..end]
+        .iter()
+        .rposition(|b| !b.is_ascii_digit())
+        .map_or(0, |position| position + 1);
+    (start < end).then_some(start)
+}
+
+fn find_last(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+/// The reference `N G R` following `key`, as in `/Root 1 0 R`.
+fn reference_after(dictionary: &[u8], key: &[u8]) -> Option<(u32, u16)> {
+    let start = find(dictionary, key, 0)? + key.len();
+    let value = String::from_utf8_lossy(&dictionary[start..dictionary.len().min(start + 32)]);
+    let mut words = value
+        .split(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+        .filter(|word| !word.is_empty());
+
+    let number = words.next()?.parse().ok()?;
+    let generation = words.next()?.parse().ok()?;
+    (words.next()? == "R").then_some((number, generation))
+}
+
+/// The array following `key`, brackets included, as in `/ID [<...> <...>]`.
+fn array_after<'a>(dictionary: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let start = find(dictionary, key, 0)? + key.len();
+    let open = start
+        + dictionary[start..]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())?;
+    if dictionary[open] != b'[' {
+        return None;
+    }
+    let close = find(dictionary, b"]", open)?;
+    Some(&dictionary[open..=close])
+}
+
+/// The last object whose dictionary declares `/Type /Catalog`.
+fn find_catalog(data: &[u8], objects: &BTreeMap<u32, ObjectLocation>) -> Option<(u32, u16)> {
+    let mut catalogs: Vec<_> = objects
+        .iter()
+        .filter(|(_, object)| {
+            let end = find(data, b"endobj", object.offset).unwrap_or(data.len());
+            let body: Vec<u8> = data[object.offset..end]
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            find(&body, b"/Type/Catalog", 0).is_some()
+        })
+        .map(|(number, object)| (object.offset, *number, object.generation))
+        .collect();
+
+    catalogs.sort_unstable();
+    catalogs
+        .last()
+        .map(|&(_, number, generation)| (number, generation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OBJECTS: &[u8] = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+        2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n\
+        3 0 obj\n<< /Length 10 >>\nstream\n4 0 obj fake\nendstream\nendobj\n";
+
+    #[test]
+    fn test_scan_objects() {
+        let objects = scan_objects(OBJECTS);
+
+        assert_eq!(objects.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(objects[&1].offset, 9);
+        assert_eq!(
+            &OBJECTS[objects[&2].offset..objects[&2].offset + 7],
+            b"2 0 obj"
+        );
+    }
+
+    #[test]
+    fn test_scan_objects_skips_truncated_object() {
+        let truncated = &OBJECTS[..OBJECTS.len() - 20];
+
+        let objects = scan_objects(truncated);
+
+        assert_eq!(objects.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_rebuild_xref_without_trailer() {
+        let repaired = rebuild_xref(OBJECTS).expect("Failed to rebuild xref");
+        let appended = std::str::from_utf8(&repaired[OBJECTS.len()..]).unwrap();
+
+        assert!(appended.starts_with("\nxref\n0 4\n0000000000 65535 f\r\n0000000009 00000 n\r\n"));
+        assert!(appended.contains("trailer\n<< /Size 4 /Root 1 0 R >>"));
+        assert!(appended.ends_with(&format!("startxref\n{}\n%%EOF\n", OBJECTS.len() + 1)));
+    }
+
+    #[test]
+    fn test_rebuild_xref_with_sparse_numbers() {
+        let data = b"%PDF-1.4\n2 0 obj\n<< /Type /Catalog >>\nendobj\n\
+            3 0 obj\n<< >>\nendobj\n7 0 obj\n<< >>\nendobj\n";
+
+        let repaired = rebuild_xref(data).expect("Failed to rebuild xref");
+        let appended = std::str::from_utf8(&repaired[data.len()..]).unwrap();
+
+        assert!(
+            appended.starts_with("\nxref\n0 1\n0000000000 65535 f\r\n2 2\n0000000009 00000 n\r\n")
+        );
+        assert!(appended.contains("\n7 1\n"));
+        assert!(appended.contains("<< /Size 8 /Root 2 0 R >>"));
+    }
+
+    #[test]
+    fn test_rebuild_xref_ignores_huge_object_numbers() {
+        let data = b"%PDF\n4000000000 0 obj<</Type/Catalog>>endobj";
+        assert!(rebuild_xref(data).is_err());
+
+        let data = b"%PDF\n1 0 obj<</Type/Catalog>>endobj\n4294967295 0 obj<<>>endobj\n\
+            8388607 0 obj<<>>endobj";
+        let repaired = rebuild_xref(data).expect("Failed to rebuild xref");
+        let appended = std::str::from_utf8(&repaired[data.len()..]).unwrap();
+        assert!(appended.contains("<< /Size 2 /Root 1 0 R >>"));
+    }
+
+    #[test]
+    fn test_rebuild_xref_keeps_trailer_entries() {
+        let mut data = OBJECTS.to_vec();
+        data.extend_from_slice(
+            b"xref\n0 1\n0000000000 65535 f\r\ntrailer\n<< /Size 99 /Root 1 0 R /Info 2 0 R\n\
+            /ID [<AB> <CD>] >>\nstartxref\n999999\n%%EOF\n",
+        );
+
+        let repaired = rebuild_xref(&data).expect("Failed to rebuild xref");
+        let appended = std::str::from_utf8(&repaired[data.len()..]).unwrap();
+
+        assert!(appended.contains("<< /Size 4 /Root 1 0 R /Info 2 0 R /ID [<AB> <CD>] >>"));
+    }
+
+    #[test]
+    fn test_rebuild_xref_without_catalog() {
+        assert!(rebuild_xref(b"%PDF-1.4\n2 0 obj\n<< >>\nendobj\n").is_err());
+        assert!(rebuild_xref(b"not a pdf").is_err());
+    }
+}
@@ -0,0 +1,779 @@
+//! Extraction quality report.
+//!
+//! Extraction is lenient: rows it can't place are dropped and text outside the
+//! column grid is ignored. Without a report, a partially failed extraction looks
+//! exactly like a successful one. The report lists everything that was dropped
+//! or looks wrong, so callers can decide whether to trust the result.
+
+use crate::date::Date;
+use crate::{column, Bic, BicError, PageExtraction, HEADERS};
+
+/// A row with content that appeared before any record was opened on its page.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct OrphanRow {
+    pub page: usize,
+    pub cells: Vec<String>,
+}
+
+/// A text fragment left of the first column boundary, ignored by extraction.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct OutOfGridText {
+    pub page: usize,
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A record with one or more empty mandatory fields.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct MissingFields {
+    /// 0-based index of the record in the extracted records.
+    pub record: usize,
+    pub page: usize,
+    pub fields: Vec<String>,
+}
+
+/// A record whose date column isn't a valid `YYYY-MM-DD` date.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct InvalidDate {
+    /// 0-based index of the record in the extracted records.
+    pub record: usize,
+    pub page: usize,
+    pub field: String,
+    pub value: String,
+}
+
+/// A record whose BIC or branch code doesn't have the ISO 9362 structure.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct InvalidBic {
+    /// 0-based index of the record in the extracted records.
+    pub record: usize,
+    pub page: usize,
+    pub bic: String,
+    pub branch_code: String,
+    pub error: BicError,
+}
+
+/// A record whose BIC has a country code that isn't in ISO 3166.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct UnknownCountry {
+    /// 0-based index of the record in the extracted records.
+    pub record: usize,
+    pub page: usize,
+    pub bic: String,
+    pub country_code: String,
+}
+
+/// A page whose vertical table lines don't match the column boundaries in use.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct ColumnDrift {
+    pub page: usize,
+    /// Boundaries detected on this page, without the end marker.
+    pub boundaries: Vec<f32>,
+}
+
+/// How a record breaks the directory's sort order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum SortViolationKind {
+    /// The record sorts before the record preceding it.
+    Inversion,
+    /// The record has the same BIC and branch code as the record preceding it.
+    Duplicate,
+    /// The record sorts after the record preceding it, but pages in between
+    /// produced no records, so records are likely missing.
+    ///
+    /// This is a heuristic: sort order alone can't tell that records are
+    /// missing, so only whole pages without records are noticed. Records lost
+    /// from a page that still produced some aren't reported, and a page that
+    /// legitimately has no records is reported as a gap.
+    Gap,
+}
+
+/// A record out of the BIC order the directory is published in.
+///
+/// This usually means a row was mis-segmented, e.g. part of one record was
+/// taken for the start of another.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct SortViolation {
+    pub kind: SortViolationKind,
+    /// 0-based index of the record in the extracted records.
+    pub record: usize,
+    pub page: usize,
+    /// BIC followed by branch code.
+    pub bic: String,
+    /// 0-based index of the preceding record it was compared with.
+    pub previous_record: usize,
+    pub previous_page: usize,
+    pub previous_bic: String,
+}
+
+/// A page that failed to load or parse and was skipped.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct PageError {
+    pub page: usize,
+    pub cause: String,
+}
+
+/// Number of records extracted from a page.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct PageRecordCount {
+    pub page: usize,
+    pub records: usize,
+}
+
+/// Everything extraction dropped or found suspicious.
+///
+/// Page numbers are 1-based, as shown by PDF viewers (the cover is page 1).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct ExtractionReport {
+    pub orphan_rows: Vec<OrphanRow>,
+    pub out_of_grid: Vec<OutOfGridText>,
+    pub missing_fields: Vec<MissingFields>,
+    pub invalid_dates: Vec<InvalidDate>,
+    pub invalid_bics: Vec<InvalidBic>,
+    pub unknown_countries: Vec<UnknownCountry>,
+    pub column_drift: Vec<ColumnDrift>,
+    pub sort_violations: Vec<SortViolation>,
+    /// Pages skipped because they failed to load or parse, when recovering.
+    pub page_errors: Vec<PageError>,
+    /// Pages after the cover that produced no records.
+    pub empty_pages: Vec<usize>,
+    pub records_per_page: Vec<PageRecordCount>,
+    /// Whether the PDF's cross-reference table was unusable and had to be
+    /// rebuilt, e.g. because the download was truncated.
+    pub repaired: bool,
+}
+
+/// The kind of problem a [`QualityIssue`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifUnitEnum))]
+pub enum IssueKind {
+    OrphanRow,
+    OutOfGrid,
+    MissingFields,
+    InvalidDate,
+    InvalidBic,
+    UnknownCountry,
+    ColumnDrift,
+    SortOrder,
+    EmptyPage,
+    PageError,
+}
+
+/// A single suspicious finding, pointing at the page and record involved.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "rustler", derive(rustler::NifMap))]
+pub struct QualityIssue {
+    pub kind: IssueKind,
+    pub page: usize,
+    /// 0-based index of the record involved, if the issue is about a record.
+    pub record: Option<usize>,
+    pub message: String,
+}
+
+/// Columns that every record must have a value for.
+const MANDATORY_COLUMNS: [usize; 3] = [column::BIC, column::FULL_LEGAL_NAME, column::INSTIT_TYPE];
+
+/// Columns that must hold a valid date.
+const DATE_COLUMNS: [usize; 2] = [column::RECORD_CREATION_DATE, column::LAST_UPDATE_DATE];
+
+impl ExtractionReport {
+    /// Record what was dropped while processing a page.
+    pub(crate) fn add_page(&mut self, page: usize, extraction: &PageExtraction) {
+        self.orphan_rows
+            .extend(extraction.orphan_rows.iter().map(|cells| OrphanRow {
+                page,
+                cells: cells.clone(),
+            }));
+        self.out_of_grid
+            .extend(extraction.out_of_grid.iter().map(|element| OutOfGridText {
+                page,
+                text: element.text.clone(),
+                x: element.x,
+                y: element.y,
+            }));
+
+        if extraction.records.is_empty() {
+            self.empty_pages.push(page);
+        }
+        self.records_per_page.push(PageRecordCount {
+            page,
+            records: extraction.records.len(),
+        });
+    }
+
+    /// Record a page that was skipped because it failed to load or parse.
+    pub(crate) fn add_page_error(&mut self, page: usize, error: &anyhow::Error) {
+        self.page_errors.push(PageError {
+            page,
+            // The alternate format includes the underlying causes
+            cause: format!("{:#}", error),
+        });
+    }
+
+    /// Record a page whose table lines differ from the boundaries in use.
+    pub(crate) fn add_column_drift(&mut self, page: usize, boundaries: &[f32]) {
+        self.column_drift.push(ColumnDrift {
+            page,
+            boundaries: boundaries
+                .iter()
+                .copied()
+                .filter(|b| *b != f32::MAX)
+                .collect(),
+        });
+    }
+
+    /// Check a record's fields, `index` being its position in the extracted records.
+    pub(crate) fn check_record(&mut self, index: usize, page: usize, record: &[String]) {
+        let value = |i: usize| record.get(i).map(String::as_str).unwrap_or_default();
+        let is_empty = |i: usize| value(i).is_empty();
+
+        let fields: Vec<String> = MANDATORY_COLUMNS
+            .iter()
+            .filter(|&&i| is_empty(i))
+            .map(|&i| HEADERS[i].to_string())
+            .collect();
+        if !fields.is_empty() {
+            self.missing_fields.push(MissingFields {
+                record: index,
+                page,
+                fields,
+            });
+        }
+
+        for i in DATE_COLUMNS {
+            if Date::parse(value(i)).is_none() {
+                self.invalid_dates.push(InvalidDate {
+                    record: index,
+                    page,
+                    field: HEADERS[i].to_string(),
+                    value: value(i).to_string(),
+                });
+            }
+        }
+
+        let bic = value(column::BIC);
+        let branch_code = value(column::BRCH_CODE);
+        match Bic::from_parts(bic, branch_code) {
+            Err(error) if !bic.is_empty() => self.invalid_bics.push(InvalidBic {
+                record: index,
+                page,
+                bic: bic.to_string(),
+                branch_code: branch_code.to_string(),
+                error,
+            }),
+            Ok(parsed) if parsed.country().is_none() => {
+                self.unknown_countries.push(UnknownCountry {
+                    record: index,
+                    page,
+                    bic: bic.to_string(),
+                    country_code: parsed.country_code().to_string(),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    /// Check a record sorts after the previous record with a BIC.
+    ///
+    /// The directory is published sorted by BIC and branch code, so records
+    /// are expected in strictly increasing order. Records without a BIC are
+    /// already reported as missing fields and aren't compared.
+    ///
+    /// Gaps are only found where whole pages between two records produced
+    /// none. BICs aren't consecutive, so records missing between two records
+    /// that are in order can't be told apart from a directory that never had
+    /// them without a reference list to compare against.
+    pub(crate) fn check_sort_order(
+        &mut self,
+        previous: &mut Option<SortPosition>,
+        index: usize,
+        page: usize,
+        record: &[String],
+    ) {
+        let bic = record
+            .get(column::BIC)
+            .map(String::as_str)
+            .unwrap_or_default();
+        if bic.is_empty() {
+            return;
+        }
+        let branch_code = record
+            .get(column::BRCH_CODE)
+            .map(String::as_str)
+            .unwrap_or_default();
+        let current = SortPosition {
+            key: format!("{}{}", bic, branch_code),
+            record: index,
+            page,
+        };
+
+        if let Some(previous) = previous.as_ref() {
+            let kind = match current.key.cmp(&previous.key) {
+                std::cmp::Ordering::Less => Some(SortViolationKind::Inversion),
+                std::cmp::Ordering::Equal => Some(SortViolationKind::Duplicate),
+                std::cmp::Ordering::Greater if current.page > previous.page + 1 => {
+                    Some(SortViolationKind::Gap)
+                }
+                std::cmp::Ordering::Greater => None,
+            };
+            if let Some(kind) = kind {
+                self.sort_violations.push(SortViolation {
+                    kind,
+                    record: current.record,
+                    page: current.page,
+                    bic: current.key.clone(),
+                    previous_record: previous.record,
+                    previous_page: previous.page,
+                    previous_bic: previous.key.clone(),
+                });
+            }
+        }
+
+        *previous = Some(current);
+    }
+
+    /// List every suspicious finding, ordered by page.
+    ///
+    /// Statistics such as `records_per_page` aren't issues by themselves.
+    pub fn issues(&self) -> Vec<QualityIssue> {
+        let mut issues = Vec::new();
+
+        for row in &self.orphan_rows {
+            issues.push(QualityIssue {
+                kind: IssueKind::OrphanRow,
+                page: row.page,
+                record: None,
+                message: format!(
+                    "row without an open record was dropped: {:?}",
+                    row.cells.join(" ").trim()
+                ),
+            });
+        }
+        for text in &self.out_of_grid {
+            issues.push(QualityIssue {
+                kind: IssueKind::OutOfGrid,
+                page: text.page,
+                record: None,
+                message: format!(
+                    "text left of the first column was ignored: {:?} at ({:.2}, {:.2})",
+                    text.text, text.x, text.y
+                ),
+            });
+        }
+        for missing in &self.missing_fields {
+            issues.push(QualityIssue {
+                kind: IssueKind::MissingFields,
+                page: missing.page,
+                record: Some(missing.record),
+                message: format!("empty mandatory fields: {}", missing.fields.join(", ")),
+            });
+        }
+        for invalid in &self.invalid_dates {
+            issues.push(QualityIssue {
+                kind: IssueKind::InvalidDate,
+                page: invalid.page,
+                record: Some(invalid.record),
+                message: format!("invalid {}: {:?}", invalid.field, invalid.value),
+            });
+        }
+        for invalid in &self.invalid_bics {
+            issues.push(QualityIssue {
+                kind: IssueKind::InvalidBic,
+                page: invalid.page,
+                record: Some(invalid.record),
+                message: format!(
+                    "invalid BIC structure: {:?} with branch code {:?}: {}",
+                    invalid.bic, invalid.branch_code, invalid.error
+                ),
+            });
+        }
+        for unknown in &self.unknown_countries {
+            issues.push(QualityIssue {
+                kind: IssueKind::UnknownCountry,
+                page: unknown.page,
+                record: Some(unknown.record),
+                message: format!(
+                    "unknown country code {:?} in BIC {:?}",
+                    unknown.country_code, unknown.bic
+                ),
+            });
+        }
+        for drift in &self.column_drift {
+            issues.push(QualityIssue {
+                kind: IssueKind::ColumnDrift,
+                page: drift.page,
+                record: None,
+                message: format!(
+                    "table lines don't match the column boundaries in use ({} lines found)",
+                    drift.boundaries.len()
+                ),
+            });
+        }
+        for violation in &self.sort_violations {
+            let problem = match violation.kind {
+                SortViolationKind::Inversion => "sorts before",
+                SortViolationKind::Duplicate => "duplicates",
+                SortViolationKind::Gap => "skips pages without records after",
+            };
+            issues.push(QualityIssue {
+                kind: IssueKind::SortOrder,
+                page: violation.page,
+                record: Some(violation.record),
+                message: format!(
+                    "{} {} {} (record {}, page {})",
+                    violation.bic,
+                    problem,
+                    violation.previous_bic,
+                    violation.previous_record,
+                    violation.previous_page
+                ),
+            });
+        }
+        for &page in &self.empty_pages {
+            issues.push(QualityIssue {
+                kind: IssueKind::EmptyPage,
+                page,
+                record: None,
+                message: "page produced no records".to_string(),
+            });
+        }
+        for error in &self.page_errors {
+            issues.push(QualityIssue {
+                kind: IssueKind::PageError,
+                page: error.page,
+                record: None,
+                message: format!("page was skipped: {}", error.cause),
+            });
+        }
+
+        // Stable sort keeps the per-kind order within a page
+        issues.sort_by_key(|issue| issue.page);
+        issues
+    }
+
+    /// Whether extraction found nothing suspicious.
+    pub fn is_clean(&self) -> bool {
+        self.issues().is_empty()
+    }
+}
+
+/// The last record checked by [`ExtractionReport::check_sort_order`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SortPosition {
+    key: String,
+    record: usize,
+    page: usize,
+}
+
+/// Extraction in strict mode found suspicious content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityError {
+    pub issues: Vec<QualityIssue>,
+}
+
+impl std::fmt::Display for QualityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Strict extraction found {} quality issue(s):",
+            self.issues.len()
+        )?;
+        for issue in &self.issues {
+            write!(f, "\n  page {}", issue.page)?;
+            if let Some(record) = issue.record {
+                write!(f, ", record {}", record)?;
+            }
+            write!(f, ": {}", issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for QualityError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextElement;
+
+    fn record(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_add_page() {
+        let mut report = ExtractionReport::default();
+        let extraction = PageExtraction {
+            records: vec![],
+            orphan_rows: vec![record(&["", "", "", "", "", "WRAPPED ADDRESS"])],
+            out_of_grid: vec![TextElement {
+                text: "stray".to_string(),
+                x: 1.0,
+                y: 2.0,
+            }],
+            ..PageExtraction::default()
+        };
+
+        report.add_page(3, &extraction);
+
+        assert_eq!(report.orphan_rows.len(), 1);
+        assert_eq!(report.orphan_rows[0].page, 3);
+        assert_eq!(report.out_of_grid[0].text, "stray");
+        assert_eq!(report.empty_pages, vec![3]);
+        assert_eq!(
+            report.records_per_page,
+            vec![PageRecordCount {
+                page: 3,
+                records: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_record_valid() {
+        let mut report = ExtractionReport::default();
+        let valid = record(&[
+            "1997-03-01",
+            "2024-06-06",
+            "AAAARSBG",
+            "XXX",
+            "YETTEL BANK AD",
+            "",
+            "",
+            "",
+            "",
+            "FIIN",
+        ]);
+
+        report.check_record(0, 2, &valid);
+
+        assert_eq!(report, ExtractionReport::default());
+    }
+
+    #[test]
+    fn test_check_record_problems() {
+        let mut report = ExtractionReport::default();
+        let broken = record(&[
+            "1997-03-01",
+            "2024-02-30",
+            "AAAARSBG",
+            "XXX",
+            "",
+            "",
+            "",
+            "",
+            "",
+        ]);
+
+        report.check_record(7, 4, &broken);
+
+        assert_eq!(
+            report.missing_fields,
+            vec![MissingFields {
+                record: 7,
+                page: 4,
+                fields: vec!["Full legal name".to_string(), "Instit. Type".to_string()],
+            }]
+        );
+        assert_eq!(
+            report.invalid_dates,
+            vec![InvalidDate {
+                record: 7,
+                page: 4,
+                field: "Last Update date".to_string(),
+                value: "2024-02-30".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_record_invalid_bic() {
+        let mut report = ExtractionReport::default();
+        let mut values = [
+            "1997-03-01",
+            "2024-06-06",
+            "AAAARSB",
+            "XXX",
+            "YETTEL BANK AD",
+            "",
+            "",
+            "",
+            "",
+            "FIIN",
+        ];
+        report.check_record(0, 2, &record(&values));
+        values[2] = "1AAARSBG";
+        values[3] = "xx";
+        report.check_record(1, 2, &record(&values));
+        values[3] = "XX1";
+        report.check_record(2, 2, &record(&values));
+
+        let invalid: Vec<(usize, BicError)> = report
+            .invalid_bics
+            .iter()
+            .map(|invalid| (invalid.record, invalid.error))
+            .collect();
+        assert_eq!(invalid, vec![(0, BicError::Length), (1, BicError::Length)]);
+    }
+
+    #[test]
+    fn test_check_record_unknown_country() {
+        let mut report = ExtractionReport::default();
+        let mut values = [
+            "2008-10-29",
+            "2024-06-06",
+            "AAAAXKPR",
+            "XXX",
+            "KOSOVO BANK",
+            "",
+            "",
+            "",
+            "",
+            "FIIN",
+        ];
+        report.check_record(0, 2, &record(&values));
+        values[2] = "AAAAQQPR";
+        report.check_record(1, 2, &record(&values));
+        // Reported as an invalid BIC instead
+        values[2] = "AAAAQ1PR";
+        report.check_record(2, 2, &record(&values));
+
+        assert_eq!(
+            report.unknown_countries,
+            vec![UnknownCountry {
+                record: 1,
+                page: 2,
+                bic: "AAAAQQPR".to_string(),
+                country_code: "QQ".to_string(),
+            }]
+        );
+        assert_eq!(report.invalid_bics.len(), 1);
+        assert_eq!(
+            report.issues()[1].message,
+            "unknown country code \"QQ\" in BIC \"AAAAQQPR\""
+        );
+    }
+
+    #[test]
+    fn test_issues_and_quality_error() {
+        let mut report = ExtractionReport::default();
+        assert!(report.is_clean());
+
+        report.empty_pages.push(4);
+        report.add_column_drift(3, &[20.5, 71.5, f32::MAX]);
+        report.check_record(
+            5,
+            2,
+            &record(&[
+                "1997-03-01",
+                "2024-06-06",
+                "AAAARSBG",
+                "XXX",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "FIIN",
+            ]),
+        );
+
+        let issues = report.issues();
+        let kinds: Vec<IssueKind> = issues.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                IssueKind::MissingFields,
+                IssueKind::ColumnDrift,
+                IssueKind::EmptyPage
+            ]
+        );
+        assert_eq!(issues[0].record, Some(5));
+        assert_eq!(report.column_drift[0].boundaries, vec![20.5, 71.5]);
+        assert!(!report.is_clean());
+
+        let error = QualityError { issues };
+        assert_eq!(
+            error.to_string(),
+            "Strict extraction found 3 quality issue(s):\n  \
+             page 2, record 5: empty mandatory fields: Full legal name\n  \
+             page 3: table lines don't match the column boundaries in use (2 lines found)\n  \
+             page 4: page produced no records"
+        );
+    }
+
+    #[test]
+    fn test_check_sort_order() {
+        let mut report = ExtractionReport::default();
+        let mut previous = None;
+        let bics = [
+            ("AAAARSBG", "XXX", 2),
+            ("AAACKWKW", "XXX", 2),
+            ("AAACKWKW", "XXX", 2),
+            ("", "", 2),
+            ("AAAAKWKW", "XXX", 3),
+            ("AAADFRP1", "XXX", 3),
+            ("AAAEFRP1", "XXX", 4),
+            ("AAAFFRP1", "XXX", 6),
+        ];
+
+        for (index, (bic, branch_code, page)) in bics.into_iter().enumerate() {
+            let record = record(&["", "", bic, branch_code]);
+            report.check_sort_order(&mut previous, index, page, &record);
+        }
+
+        assert_eq!(
+            report.sort_violations,
+            vec![
+                SortViolation {
+                    kind: SortViolationKind::Duplicate,
+                    record: 2,
+                    page: 2,
+                    bic: "AAACKWKWXXX".to_string(),
+                    previous_record: 1,
+                    previous_page: 2,
+                    previous_bic: "AAACKWKWXXX".to_string(),
+                },
+                SortViolation {
+                    kind: SortViolationKind::Inversion,
+                    record: 4,
+                    page: 3,
+                    bic: "AAAAKWKWXXX".to_string(),
+                    previous_record: 2,
+                    previous_page: 2,
+                    previous_bic: "AAACKWKWXXX".to_string(),
+                },
+                SortViolation {
+                    kind: SortViolationKind::Gap,
+                    record: 7,
+                    page: 6,
+                    bic: "AAAFFRP1XXX".to_string(),
+                    previous_record: 6,
+                    previous_page: 4,
+                    previous_bic: "AAAEFRP1XXX".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            report.issues()[1].message,
+            "AAAAKWKWXXX sorts before AAACKWKWXXX (record 2, page 2)"
+        );
+        assert_eq!(
+            report.issues()[2].message,
+            "AAAFFRP1XXX skips pages without records after AAAEFRP1XXX (record 6, page 4)"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+#!/usr/bin/env python3
+"""Generate the encrypted copies of ISOBIC-mini.pdf used by the tests.
+
+Implements the PDF standard security handler (ISO 32000-2, 7.6.4) for the
+revisions we test, so no PDF tool is needed to build the fixtures:
+
+    ISOBIC-mini-rc4.pdf          RC4 128-bit (V2, R3), user and owner password
+    ISOBIC-mini-aes128.pdf       AES-128 (V4, R4), user and owner password
+    ISOBIC-mini-permissions.pdf  AES-128 (V4, R4), owner password only
+
+Requires the `cryptography` package. Run from this directory:
+
+    python3 encrypt.py
+"""
+
+import hashlib
+import re
+
+from cryptography.hazmat.decrepit.ciphers.algorithms import ARC4
+from cryptography.hazmat.primitives import padding
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+
+USER_PASSWORD = b"user-secret"
+OWNER_PASSWORD = b"owner-secret"
+
+PADDING = bytes.fromhex(
+    "28BF4E5E4E758A4164004E56FFFA01082E2E00B6D0683E802F0CA9FE6453697A"
+)
+
+# Printing and copying only
+PERMISSIONS = -3904
+
+# Fixed so regenerating the fixtures is reproducible
+IV = bytes(range(16))
+
+
+def rc4(key, data):
+    encryptor = Cipher(ARC4(key), mode=None).encryptor()
+    return encryptor.update(data)
+
+
+def aes_cbc(key, data):
+    padder = padding.PKCS7(128).padder()
+    data = padder.update(data) + padder.finalize()
+    encryptor = Cipher(algorithms.AES(key), modes.CBC(IV)).encryptor()
+    return encryptor.update(data) + encryptor.finalize()
+
+
+def padded(password):
+    return (password + PADDING)[:32]
+
+
+def owner_entry_rc4(owner_password, user_password, revision, key_length):
+    """Algorithm 3: the O entry for revisions 2 to 4."""
+    digest = hashlib.md5(padded(owner_password)).digest()
+    for _ in range(50):
+        digest = hashlib.md5(digest).digest()
+    key = digest[:key_length]
+
+    data = rc4(key, padded(user_password))
+    for i in range(1, 20):
+        data = rc4(bytes(b ^ i for b in key), data)
+    return data
+
+
+def file_key_rc4(user_password, owner_entry, file_id, key_length):
+    """Algorithm 2: the file encryption key for revisions 2 to 4."""
+    digest = hashlib.md5(
+        padded(user_password)
+        + owner_entry
+        + PERMISSIONS.to_bytes(4, "little", signed=True)
+        + file_id
+    ).digest()
+    for _ in range(50):
+        digest = hashlib.md5(digest[:key_length]).digest()
+    return digest[:key_length]
+
+
+def user_entry_rc4(key, file_id):
+    """Algorithm 5: the U entry for revisions 3 and 4."""
+    data = rc4(key, hashlib.md5(PADDING + file_id).digest())
+    for i in range(1, 20):
+        data = rc4(bytes(b ^ i for b in key), data)
+    return data + bytes(16)
+
+
+def encrypt_pdf(source, method, user_password, owner_password):
+    trailer = source[source.rindex(b"trailer") :]
+    file_id = bytes.fromhex(re.search(rb"/ID \[<([0-9A-F]+)>", trailer).group(1).decode())
+
+    key_length = 16
+    revision = 3 if method == "rc4" else 4
+    o = owner_entry_rc4(owner_password, user_password, revision, key_length)
+    key = file_key_rc4(user_password, o, file_id, key_length)
+    u = user_entry_rc4(key, file_id)
+    if method == "rc4":
+        crypt_filters = "/V 2 /R 3 /Length 128"
+    else:
+        crypt_filters = (
+            "/V 4 /R 4 /Length 128 "
+            "/CF << /StdCF << /CFM /AESV2 /AuthEvent /DocOpen /Length 16 >> >> "
+            "/StmF /StdCF /StrF /StdCF"
+        )
+    encrypt_dict = (
+        f"<< /Filter /Standard {crypt_filters} "
+        f"/O <{o.hex()}> /U <{u.hex()}> /P {PERMISSIONS} >>"
+    )
+
+    def encrypt(number, generation, data):
+        object_key = key + number.to_bytes(3, "little") + generation.to_bytes(2, "little")
+        if method == "rc4":
+            return rc4(hashlib.md5(object_key).digest()[: len(key) + 5], data)
+        return IV + aes_cbc(hashlib.md5(object_key + b"sAlT").digest()[:16], data)
+
+    objects = []
+    for match in re.finditer(rb"(\d+) (\d+) obj\n(.*?)\nendobj", source, re.S):
+        number, generation, body = int(match.group(1)), int(match.group(2)), match.group(3)
+
+        stream = re.match(rb"(<<.*?>>)\nstream\n(.*)\nendstream$", body, re.S)
+        if stream:
+            data = encrypt(number, generation, stream.group(2))
+            dictionary = re.sub(rb"/Length \d+", b"/Length %d" % len(data), stream.group(1))
+            body = dictionary + b"\nstream\n" + data + b"\nendstream"
+        else:
+            # Only the Info dictionary has strings, and they hold no parentheses
+            body = re.sub(
+                rb"\(([^()]*)\)",
+                lambda s: b"<" + encrypt(number, generation, s.group(1)).hex().encode() + b">",
+                body,
+            )
+        objects.append((number, generation, body))
+
+    encrypt_number = max(number for number, _, _ in objects) + 1
+    objects.append((encrypt_number, 0, encrypt_dict.encode()))
+
+    output = bytearray(b"%PDF-1.6\n%\xe2\xe3\xcf\xd3\n")
+    offsets = {}
+    for number, generation, body in objects:
+        offsets[number] = len(output)
+        output += b"%d %d obj\n" % (number, generation) + body + b"\nendobj\n"
+
+    xref_offset = len(output)
+    output += b"xref\n0 %d\n0000000000 65535 f\r\n" % (encrypt_number + 1)
+    for number in range(1, encrypt_number + 1):
+        output += b"%010d 00000 n\r\n" % offsets[number]
+
+    trailer_entries = re.search(rb"trailer\n<<\n(.*?)>>", trailer, re.S).group(1)
+    trailer_entries = re.sub(rb"/Size \d+", b"/Size %d" % (encrypt_number + 1), trailer_entries)
+    output += b"trailer\n<<\n" + trailer_entries + b"/Encrypt %d 0 R\n>>\n" % encrypt_number
+    output += b"startxref\n%d\n%%%%EOF\n" % xref_offset
+    return bytes(output)
+
+
+def main():
+    with open("ISOBIC-mini.pdf", "rb") as f:
+        source = f.read()
+
+    fixtures = {
+        "ISOBIC-mini-rc4.pdf": ("rc4", USER_PASSWORD),
+        "ISOBIC-mini-aes128.pdf": ("aes128", USER_PASSWORD),
+        "ISOBIC-mini-permissions.pdf": ("aes128", b""),
+    }
+    for path, (method, user_password) in fixtures.items():
+        with open(path, "wb") as f:
+            f.write(encrypt_pdf(source, method, user_password, OWNER_PASSWORD))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,62 @@
+#!/usr/bin/env python3
+"""Generate ISOBIC-mini-endstream-bomb.pdf, a decompression bomb for the stream guard.
+
+The content stream of the first data page is recompressed with about 60 MB
+of trailing spaces. Its zlib data starts with a stored block holding the
+literal bytes "% endstream", so a scanner that cuts streams at the first
+"endstream" sees only a few bytes, while the PDF parser reads the whole
+stream by its /Length. The xref table is rewritten for the new offsets.
+
+Run from this directory:
+
+    python3 endstream_bomb.py
+"""
+
+import re
+import struct
+import zlib
+
+BOMB_OBJECT = 18
+PADDING = b" " * 60_000_000
+MARKER = b"% endstream\n"
+
+
+def bomb(content):
+    # A non-final stored block with the marker, then the deflated content
+    stored = b"\x00" + struct.pack("<HH", len(MARKER), len(MARKER) ^ 0xFFFF) + MARKER
+    compressor = zlib.compressobj(9, zlib.DEFLATED, -15)
+    deflated = compressor.compress(content) + compressor.flush()
+    checksum = zlib.adler32(MARKER + content)
+    return b"x\x01" + stored + deflated + struct.pack(">I", checksum)
+
+
+def main():
+    with open("ISOBIC-mini.pdf", "rb") as f:
+        source = f.read()
+
+    def replace(match):
+        data = zlib.decompress(match.group(2))
+        stream = bomb(data + PADDING)
+        header = re.sub(rb"/Length \d+", b"/Length %d" % len(stream), match.group(1))
+        return header + stream + match.group(3)
+
+    pattern = rb"(%d 0 obj\n.*?stream\n)(.*?)(\nendstream\nendobj\n)" % BOMB_OBJECT
+    output = re.sub(pattern, replace, source, count=1, flags=re.S)
+
+    body = output[: output.rindex(b"\nxref\n") + 1]
+    offsets = {
+        int(match.group(1)): match.start()
+        for match in re.finditer(rb"(?m)^(\d+) 0 obj\n", body)
+    }
+    size = max(offsets) + 1
+    xref = b"xref\n0 %d\n0000000000 65535 f\r\n" % size
+    for number in range(1, size):
+        xref += b"%010d 00000 n\r\n" % offsets[number]
+    trailer = output[output.index(b"trailer", len(body)) : output.rindex(b"startxref")]
+
+    with open("ISOBIC-mini-endstream-bomb.pdf", "wb") as f:
+        f.write(body + xref + trailer + b"startxref\n%d\n%%%%EOF\n" % len(body))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,43 @@
+#!/usr/bin/env python3
+"""Generate ISOBIC-mini-truncated.pdf, a truncated download of ISOBIC-mini.pdf.
+
+ISOBIC-mini.pdf writes its page tree after the content streams, so cutting
+it short loses every page. This rewrites it with the catalog, page tree and
+Info dictionary first, then cuts the file halfway through the last page's
+content stream. The xref table and trailer are lost, as in a real truncated
+download, while the first two data pages remain intact.
+
+Run from this directory:
+
+    python3 truncate.py
+"""
+
+import re
+
+FIRST = [1, 2, 3, 4, 5, 6, 20]
+TRUNCATED_OBJECT = 19
+
+
+def main():
+    with open("ISOBIC-mini.pdf", "rb") as f:
+        source = f.read()
+
+    objects = {
+        int(match.group(1)): match.group(0)
+        for match in re.finditer(rb"(\d+) \d+ obj\n.*?\nendobj\n", source, re.S)
+    }
+    order = FIRST + [number for number in objects if number not in FIRST]
+
+    output = bytearray(source[: source.index(b"\n", source.index(b"\n") + 1) + 1])
+    for number in order:
+        if number == TRUNCATED_OBJECT:
+            output += objects[number][: len(objects[number]) // 2]
+            break
+        output += objects[number]
+
+    with open("ISOBIC-mini-truncated.pdf", "wb") as f:
+        f.write(output)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,815 @@
+//! NIF functions for Elixir/Erlang integration via Rustler.
+
+use bic_exporter_core::export::csv::{write_csv, CsvOptions, LineEnding, QuoteStyle};
+use bic_exporter_core::export::fixed_width::{
+    truncated_fields, write_fixed_width, Align, FieldLayout, FixedWidthLayout, TruncatedField,
+    Truncation,
+};
+use bic_exporter_core::export::json::{write_json, write_ndjson, JsonHeader, SCHEMA};
+use bic_exporter_core::export::postgres::{copy_statement, write_copy, write_inserts, SqlOptions};
+use bic_exporter_core::export::sanitize::{formula_fields, SanitizedField};
+use bic_exporter_core::export::xlsx::{write_xlsx, XlsxOptions};
+use bic_exporter_core::export::{output_len, FIELDS};
+use bic_exporter_core::{
+    debug, extract_table_from_bytes, extract_table_with_options_from_bytes,
+    extract_table_with_progress_from_bytes, Bic, BicError, Cancelled, Country, Date, Directory,
+    ExtractOptions, ExtractionReport, LimitExceeded, Limits, PasswordError, QualityError,
+    SuggestOptions, HEADERS,
+};
+use rustler::{Binary, Encoder, Env, LocalPid, OwnedBinary, ResourceArc};
+use std::fs::File;
+use std::io::{self, BufWriter, Cursor, Write};
+use std::ops::ControlFlow;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// NIF: Extract BIC records from PDF binary data.
+///
+/// Returns `{:ok, records}` on success or `{:error, reason}` on failure.
+/// Each record is a list of 10 strings corresponding to the CSV columns.
+///
+/// Why schedule = "DirtyCpu"?
+/// From the Rustler docs:
+/// > For functions that may take some time to return - let’s say more than 1 millisecond -
+/// > it is recommended to use the schedule flag. This tells the BEAM to allocate that NIF call to a special scheduler.
+/// Here we have an intensive operation of parsing a big file that can take multiple seconds.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn extract_table_from_binary(data: Binary) -> Result<Vec<Vec<String>>, String> {
+    extract_table_from_bytes(data.as_slice()).map_err(|e| e.to_string())
+}
+
+rustler::atoms! {
+    ok,
+    error,
+    quality_issues,
+    limit_exceeded,
+    cancelled,
+    progress,
+    panic,
+    bic_exporter,
+}
+
+/// Extraction options as passed from Elixir, with every key present.
+///
+/// Limits are `nil` when unset, and the timeout is in milliseconds.
+#[derive(rustler::NifMap)]
+struct NifExtractOptions {
+    strict: bool,
+    recover: bool,
+    max_pages: Option<usize>,
+    max_operations_per_page: Option<usize>,
+    max_stream_size: Option<usize>,
+    max_text_elements: Option<usize>,
+    timeout: Option<u64>,
+    password: Option<String>,
+    repair: bool,
+    archive_entry: Option<String>,
+    max_input_size: Option<usize>,
+}
+
+impl From<NifExtractOptions> for ExtractOptions {
+    fn from(options: NifExtractOptions) -> Self {
+        ExtractOptions {
+            strict: options.strict,
+            recover: options.recover,
+            limits: Limits {
+                max_pages: options.max_pages,
+                max_operations_per_page: options.max_operations_per_page,
+                max_stream_size: options.max_stream_size,
+                max_text_elements: options.max_text_elements,
+                timeout: options.timeout.map(Duration::from_millis),
+                max_input_size: options.max_input_size,
+            },
+            password: options.password,
+            repair: options.repair,
+            archive_entry: options.archive_entry,
+        }
+    }
+}
+
+/// Convert an extraction error into the `reason` of `{:error, reason}`.
+///
+/// Quality errors become `{:quality_issues, issues}` so callers can inspect
+/// the offending pages and records, and exceeded limits become
+/// `{:limit_exceeded, limit, max}`. Cancellation and password errors become
+/// `:cancelled`, `:password_required` or `:wrong_password`. Anything else
+/// becomes a message string.
+fn to_nif_error(error: anyhow::Error) -> rustler::Error {
+    rustler::Error::Term(error_reason(error))
+}
+
+fn error_reason(error: anyhow::Error) -> Box<dyn Encoder> {
+    if error.is::<Cancelled>() {
+        return Box::new(cancelled());
+    }
+    if let Some(password_error) = error.downcast_ref::<PasswordError>() {
+        return Box::new(*password_error);
+    }
+
+    let error = match error.downcast::<QualityError>() {
+        Ok(quality_error) => return Box::new((quality_issues(), quality_error.issues)),
+        Err(error) => error,
+    };
+
+    match error.downcast::<LimitExceeded>() {
+        Ok(exceeded) => Box::new((limit_exceeded(), exceeded.limit, exceeded.max)),
+        Err(error) => Box::new(error.to_string()),
+    }
+}
+
+/// NIF: Extract BIC records from PDF binary data with options, along with a quality report.
+///
+/// Returns `{:ok, {records, report}}` on success or `{:error, reason}` on failure.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn extract_table_with_options_from_binary(
+    data: Binary,
+    options: NifExtractOptions,
+) -> Result<(Vec<Vec<String>>, ExtractionReport), rustler::Error> {
+    extract_table_with_options_from_bytes(data.as_slice(), &options.into())
+        .map(|extraction| (extraction.records, extraction.report))
+        .map_err(to_nif_error)
+}
+
+/// Handle to an extraction running on its own thread, used to cancel it.
+struct ExtractionHandle {
+    cancelled: AtomicBool,
+}
+
+#[rustler::resource_impl]
+impl rustler::Resource for ExtractionHandle {}
+
+/// NIF: Start extracting BIC records on a separate thread.
+///
+/// Returns `{ref, handle}` straight away. `pid` then receives
+/// `{:bic_exporter, ref, {:progress, page, total}}` after each page and
+/// finally `{:bic_exporter, ref, {:ok, records, report}}` or
+/// `{:bic_exporter, ref, {:error, reason}}`, with `:panic` as the reason if
+/// extraction panicked. Passing `handle` to `cancel_extraction/1` stops
+/// extraction before the next page, with `:cancelled` as the reason.
+/// Extraction also stops if `pid` is gone.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn start_extraction<'a>(
+    env: Env<'a>,
+    data: Binary,
+    options: NifExtractOptions,
+    pid: LocalPid,
+) -> (rustler::Reference<'a>, ResourceArc<ExtractionHandle>) {
+    let reference = env.make_ref();
+    let handle = ResourceArc::new(ExtractionHandle {
+        cancelled: AtomicBool::new(false),
+    });
+
+    let options = ExtractOptions::from(options);
+    let thread_handle = handle.clone();
+    // The thread works in an environment that outlives the NIF call. Saving
+    // the binary there keeps it alive without copying it, and the environment
+    // isn't cleared between sends so the saved terms stay valid.
+    let owned_env = rustler::OwnedEnv::new();
+    let saved_reference = owned_env.save(reference);
+    let saved_data = owned_env.save(data);
+
+    spawn_with_reply(owned_env, saved_reference, pid, move |env, send| {
+        let result = match saved_data.load(env).decode::<Binary>() {
+            Ok(data) => {
+                extract_table_with_progress_from_bytes(data.as_slice(), &options, |page, total| {
+                    let sent = send((progress(), page, total).encode(env));
+                    if !sent || thread_handle.cancelled.load(Ordering::Relaxed) {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                })
+            }
+            Err(_) => Err(anyhow::anyhow!("Failed to read PDF binary")),
+        };
+
+        match result {
+            Ok(extraction) => (ok(), extraction.records, extraction.report).encode(env),
+            Err(reason) => (error(), error_reason(reason).encode(env)).encode(env),
+        }
+    });
+
+    (reference, handle)
+}
+
+/// Run `work` on a new thread and send what it returns to `pid`, as
+/// `{:bic_exporter, reference, result}` with `reference` saved in `owned_env`.
+///
+/// `work` gets the thread's environment and a function sending `pid` a
+/// message in the same form, which returns false once `pid` is gone. If
+/// `work` panics, `{:error, :panic}` is sent instead, so a result always
+/// arrives.
+fn spawn_with_reply<F>(
+    owned_env: rustler::OwnedEnv,
+    saved_reference: rustler::env::SavedTerm,
+    pid: LocalPid,
+    work: F,
+) where
+    F: for<'a> FnOnce(Env<'a>, &dyn Fn(rustler::Term<'a>) -> bool) -> rustler::Term<'a>
+        + Send
+        + 'static,
+{
+    std::thread::spawn(move || {
+        owned_env.run(|env| {
+            let reference = saved_reference.load(env);
+            let send = |message: rustler::Term| {
+                env.send(&pid, (bic_exporter(), reference, message)).is_ok()
+            };
+
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| work(env, &send)))
+                .unwrap_or_else(|_| (error(), panic()).encode(env));
+            send(result);
+        });
+    });
+}
+
+/// NIF: Cancel an extraction started with `start_extraction/3`.
+///
+/// Extraction stops after the page it's currently processing. Cancelling an
+/// extraction that already finished has no effect.
+#[rustler::nif]
+fn cancel_extraction(handle: ResourceArc<ExtractionHandle>) -> rustler::Atom {
+    handle.cancelled.store(true, Ordering::Relaxed);
+    ok()
+}
+
+/// NIF: Render a page's extracted text and detected table grid as SVG.
+///
+/// Returns `{:ok, svg}` on success or `{:error, reason}` on failure.
+/// `page_number` is 1-based, as shown by PDF viewers.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn render_page_svg(data: Binary, page_number: usize) -> Result<String, String> {
+    debug::render_page_svg_from_bytes(data.as_slice(), page_number).map_err(|e| e.to_string())
+}
+
+/// CSV options as passed from Elixir, with every key present.
+///
+/// The delimiter is a one-character string.
+#[derive(rustler::NifMap)]
+struct NifCsvOptions {
+    delimiter: String,
+    quote_style: QuoteStyle,
+    line_ending: LineEnding,
+    bom: bool,
+    header: bool,
+    sanitize: bool,
+}
+
+impl TryFrom<NifCsvOptions> for CsvOptions {
+    type Error = String;
+
+    fn try_from(options: NifCsvOptions) -> Result<Self, String> {
+        let delimiter = match options.delimiter.as_bytes() {
+            [delimiter] => *delimiter,
+            _ => return Err(format!("Invalid CSV delimiter {:?}", options.delimiter)),
+        };
+
+        Ok(CsvOptions {
+            delimiter,
+            quote_style: options.quote_style,
+            line_ending: options.line_ending,
+            bom: options.bom,
+            header: options.header,
+            sanitize: options.sanitize,
+        })
+    }
+}
+
+/// The fields sanitizing neutralizes, when the options ask for it.
+fn sanitized_fields(records: &[Vec<&str>], sanitize: bool) -> Vec<SanitizedField> {
+    if sanitize {
+        formula_fields(records)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Write output straight into a binary of its exact size, measured by a first
+/// pass of `write` that only counts bytes.
+fn write_binary<'a>(
+    env: Env<'a>,
+    write: impl Fn(&mut dyn Write) -> io::Result<()>,
+) -> Result<Binary<'a>, String> {
+    let len = output_len(&write).map_err(|e| e.to_string())?;
+    let mut binary = OwnedBinary::new(len).ok_or("Failed to allocate the output binary")?;
+    write(&mut binary.as_mut_slice()).map_err(|e| e.to_string())?;
+    Ok(binary.release(env))
+}
+
+/// NIF: Write records as a CSV binary.
+///
+/// Returns `{:ok, {csv, sanitized}}`, where `sanitized` lists the fields
+/// neutralized when sanitizing. Fields are borrowed from the Erlang binaries,
+/// and the CSV is written straight into a binary of its exact size.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn records_to_csv<'a>(
+    env: Env<'a>,
+    records: Vec<Vec<&str>>,
+    options: NifCsvOptions,
+) -> Result<(Binary<'a>, Vec<SanitizedField>), String> {
+    let options = CsvOptions::try_from(options)?;
+    let csv = write_binary(env, |writer| {
+        write_csv(&records, writer, &options).map(drop)
+    })?;
+    Ok((csv, sanitized_fields(&records, options.sanitize)))
+}
+
+/// NIF: Write records to a CSV file at `path`.
+///
+/// Returns `{:ok, sanitized}` like `records_to_csv/2`.
+#[rustler::nif(schedule = "DirtyIo")]
+fn write_csv_file(
+    records: Vec<Vec<&str>>,
+    path: String,
+    options: NifCsvOptions,
+) -> Result<Vec<SanitizedField>, String> {
+    let options = CsvOptions::try_from(options)?;
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    write_csv(&records, BufWriter::new(file), &options)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(sanitized_fields(&records, options.sanitize))
+}
+
+/// A fixed-width field as passed from Elixir, named as in the JSON export.
+#[derive(rustler::NifMap)]
+struct NifFieldLayout {
+    field: String,
+    width: usize,
+    align: Align,
+}
+
+/// Fixed-width layout options as passed from Elixir, with every key present.
+///
+/// `fields` is `nil` for the default layout, and the padding is a
+/// one-character string.
+#[derive(rustler::NifMap)]
+struct NifFixedWidthOptions {
+    fields: Option<Vec<NifFieldLayout>>,
+    padding: String,
+    truncation: Truncation,
+    line_ending: LineEnding,
+}
+
+impl TryFrom<NifFixedWidthOptions> for FixedWidthLayout {
+    type Error = String;
+
+    fn try_from(options: NifFixedWidthOptions) -> Result<Self, String> {
+        let default = FixedWidthLayout::default();
+        let fields = match options.fields {
+            Some(fields) => fields
+                .into_iter()
+                .map(|field| {
+                    let column = FIELDS
+                        .iter()
+                        .position(|name| *name == field.field)
+                        .ok_or(format!("Unknown field {:?}", field.field))?;
+                    Ok(FieldLayout {
+                        column,
+                        width: field.width,
+                        align: field.align,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            None => default.fields,
+        };
+        let mut padding = options.padding.chars();
+        let padding = match (padding.next(), padding.next()) {
+            (Some(padding), None) => padding,
+            _ => return Err(format!("Invalid fixed-width padding {:?}", options.padding)),
+        };
+
+        Ok(FixedWidthLayout {
+            fields,
+            padding,
+            truncation: options.truncation,
+            line_ending: options.line_ending,
+        })
+    }
+}
+
+/// NIF: Write records as fixed-width lines.
+///
+/// Returns `{:ok, {data, truncated}}`, where `truncated` lists the values cut
+/// to fit their field. Like `records_to_csv/2`, fields are borrowed and the
+/// output is written straight into a binary of its exact size.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn records_to_fixed_width<'a>(
+    env: Env<'a>,
+    records: Vec<Vec<&str>>,
+    options: NifFixedWidthOptions,
+) -> Result<(Binary<'a>, Vec<TruncatedField>), String> {
+    let layout = FixedWidthLayout::try_from(options)?;
+    let data = write_binary(env, |writer| {
+        write_fixed_width(&records, writer, &layout).map(drop)
+    })?;
+    Ok((data, truncated_fields(&records, &layout)))
+}
+
+/// NIF: Write records as fixed-width lines to a file at `path`.
+///
+/// Returns `{:ok, truncated}` like `records_to_fixed_width/2`.
+#[rustler::nif(schedule = "DirtyIo")]
+fn write_fixed_width_file(
+    records: Vec<Vec<&str>>,
+    path: String,
+    options: NifFixedWidthOptions,
+) -> Result<Vec<TruncatedField>, String> {
+    let layout = FixedWidthLayout::try_from(options)?;
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    write_fixed_width(&records, BufWriter::new(file), &layout)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(truncated_fields(&records, &layout))
+}
+
+/// XLSX options as passed from Elixir, with every key present.
+#[derive(rustler::NifMap)]
+struct NifXlsxOptions {
+    sanitize: bool,
+}
+
+impl From<NifXlsxOptions> for XlsxOptions {
+    fn from(options: NifXlsxOptions) -> Self {
+        XlsxOptions {
+            sanitize: options.sanitize,
+            ..XlsxOptions::default()
+        }
+    }
+}
+
+/// NIF: Write records as an XLSX binary.
+///
+/// Returns `{:ok, {xlsx, sanitized}}` like `records_to_csv/2`. A zip archive's
+/// size isn't known until it's written, so the workbook is built in memory
+/// and then copied into the binary.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn records_to_xlsx<'a>(
+    env: Env<'a>,
+    records: Vec<Vec<&str>>,
+    options: NifXlsxOptions,
+) -> Result<(Binary<'a>, Vec<SanitizedField>), String> {
+    let options = XlsxOptions::from(options);
+    let xlsx = write_xlsx(&records, Cursor::new(Vec::new()), &options)
+        .map_err(|e| e.to_string())?
+        .into_inner();
+    let mut binary = OwnedBinary::new(xlsx.len()).ok_or("Failed to allocate the output binary")?;
+    binary.as_mut_slice().copy_from_slice(&xlsx);
+    Ok((
+        binary.release(env),
+        sanitized_fields(&records, options.sanitize),
+    ))
+}
+
+/// NIF: Write records to an XLSX file at `path`.
+///
+/// Returns `{:ok, sanitized}` like `records_to_csv/2`.
+#[rustler::nif(schedule = "DirtyIo")]
+fn write_xlsx_file(
+    records: Vec<Vec<&str>>,
+    path: String,
+    options: NifXlsxOptions,
+) -> Result<Vec<SanitizedField>, String> {
+    let options = XlsxOptions::from(options);
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    write_xlsx(&records, BufWriter::new(file), &options)
+        .and_then(|mut writer| Ok(writer.flush()?))
+        .map_err(|e| format!("Failed to write {}: {:#}", path, e))?;
+    Ok(sanitized_fields(&records, options.sanitize))
+}
+
+/// Which of the JSON formats to write.
+#[derive(Clone, Copy, rustler::NifUnitEnum)]
+enum JsonFormat {
+    Json,
+    Ndjson,
+}
+
+/// JSON options as passed from Elixir, with every key present.
+///
+/// The edition is an ISO 8601 date string, or `nil` when unknown.
+#[derive(rustler::NifMap)]
+struct NifJsonOptions {
+    format: JsonFormat,
+    metadata: bool,
+    edition: Option<String>,
+}
+
+impl NifJsonOptions {
+    /// The metadata header, stamped with the current time.
+    fn header(&self) -> Result<Option<JsonHeader>, String> {
+        if !self.metadata {
+            return Ok(None);
+        }
+        let edition = match &self.edition {
+            Some(edition) => {
+                Some(Date::parse(edition).ok_or(format!("Invalid edition {:?}", edition))?)
+            }
+            None => None,
+        };
+        Ok(Some(JsonHeader::new(edition)))
+    }
+
+    fn write<W: Write>(
+        &self,
+        records: &[Vec<&str>],
+        writer: W,
+        header: Option<&JsonHeader>,
+    ) -> io::Result<W> {
+        match self.format {
+            JsonFormat::Json => write_json(records, writer, header),
+            JsonFormat::Ndjson => write_ndjson(records, writer, header),
+        }
+    }
+}
+
+/// NIF: Write records as a JSON or JSON Lines binary.
+///
+/// Returns `{:ok, json}`. Like `records_to_csv/2`, fields are borrowed and
+/// the output is written straight into a binary of its exact size.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn records_to_json<'a>(
+    env: Env<'a>,
+    records: Vec<Vec<&str>>,
+    options: NifJsonOptions,
+) -> Result<Binary<'a>, String> {
+    let header = options.header()?;
+    write_binary(env, |writer| {
+        options.write(&records, writer, header.as_ref()).map(drop)
+    })
+}
+
+/// NIF: Write records to a JSON or JSON Lines file at `path`.
+///
+/// Returns `{:ok, {}}` on success or `{:error, reason}` on failure.
+#[rustler::nif(schedule = "DirtyIo")]
+fn write_json_file(
+    records: Vec<Vec<&str>>,
+    path: String,
+    options: NifJsonOptions,
+) -> Result<(), String> {
+    let header = options.header()?;
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    options
+        .write(&records, BufWriter::new(file), header.as_ref())
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(())
+}
+
+/// NIF: Get the JSON Schema describing the JSON and JSON Lines output.
+#[rustler::nif]
+fn json_schema() -> &'static str {
+    SCHEMA
+}
+
+/// SQL script options as passed from Elixir, with every key present.
+#[derive(rustler::NifMap)]
+struct NifSqlOptions {
+    table: String,
+    rows_per_insert: usize,
+    create_table: bool,
+}
+
+impl From<NifSqlOptions> for SqlOptions {
+    fn from(options: NifSqlOptions) -> Self {
+        SqlOptions {
+            table: options.table,
+            rows_per_insert: options.rows_per_insert,
+            create_table: options.create_table,
+        }
+    }
+}
+
+/// NIF: Write records as PostgreSQL `COPY` data in text format.
+///
+/// Returns `{:ok, data}`, written straight into a binary of its exact size,
+/// to send after the statement from `pg_copy_statement/1`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn records_to_pg_copy<'a>(env: Env<'a>, records: Vec<Vec<&str>>) -> Result<Binary<'a>, String> {
+    write_binary(env, |writer| write_copy(&records, writer).map(drop))
+}
+
+/// NIF: Get the `COPY ... FROM STDIN` statement for `table`.
+#[rustler::nif]
+fn pg_copy_statement(table: &str) -> String {
+    copy_statement(table)
+}
+
+/// NIF: Write records as a script of multi-row `INSERT` statements.
+///
+/// Returns `{:ok, sql}` or `{:error, reason}` for invalid options.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn records_to_sql_inserts<'a>(
+    env: Env<'a>,
+    records: Vec<Vec<&str>>,
+    options: NifSqlOptions,
+) -> Result<Binary<'a>, String> {
+    let options = SqlOptions::from(options);
+    write_binary(env, |writer| {
+        write_inserts(&records, writer, &options).map(drop)
+    })
+}
+
+/// NIF: Get the CSV column headers.
+///
+/// Returns the list of column headers used in the CSV output.
+#[rustler::nif]
+fn headers() -> Vec<&'static str> {
+    HEADERS.to_vec()
+}
+
+/// A country as returned to Elixir.
+#[derive(rustler::NifMap)]
+struct NifCountry {
+    code: String,
+    name: String,
+}
+
+impl From<&'static Country> for NifCountry {
+    fn from(country: &'static Country) -> Self {
+        NifCountry {
+            code: country.code.to_string(),
+            name: country.name.to_string(),
+        }
+    }
+}
+
+/// NIF: Get the country of a BIC, from its 5th and 6th characters.
+///
+/// Returns `nil` when they aren't a known ISO 3166 code.
+#[rustler::nif]
+fn bic_country(bic: &str) -> Option<NifCountry> {
+    Country::from_bic(bic).map(NifCountry::from)
+}
+
+/// A parsed BIC as returned to Elixir, with its parts and the flags derived
+/// from them.
+#[derive(rustler::NifMap)]
+struct NifBic {
+    bic: String,
+    bic8: String,
+    business_party_prefix: String,
+    country_code: String,
+    location_code: String,
+    branch_code: String,
+    country: Option<NifCountry>,
+    primary_office: bool,
+    test: bool,
+    passive: bool,
+    reverse_billing: bool,
+}
+
+impl From<&Bic> for NifBic {
+    fn from(bic: &Bic) -> Self {
+        NifBic {
+            bic: bic.to_string(),
+            bic8: bic.bic8().to_string(),
+            business_party_prefix: bic.business_party_prefix().to_string(),
+            country_code: bic.country_code().to_string(),
+            location_code: bic.location_code().to_string(),
+            branch_code: bic.branch_code().to_string(),
+            country: bic.country().map(NifCountry::from),
+            primary_office: bic.is_primary_office(),
+            test: bic.is_test(),
+            passive: bic.is_passive(),
+            reverse_billing: bic.is_reverse_billing(),
+        }
+    }
+}
+
+/// NIF: Parse and validate an 8 or 11-character BIC.
+///
+/// Returns `{:ok, bic}` or `{:error, reason}`, the reason being the part of
+/// the code found invalid, such as `:branch_code`.
+#[rustler::nif]
+fn parse_bic(code: &str) -> Result<NifBic, BicError> {
+    Bic::parse(code).map(|bic| NifBic::from(&bic))
+}
+
+/// Records indexed by BIC, loaded once and shared by lookups.
+struct DirectoryResource(Directory);
+
+#[rustler::resource_impl]
+impl rustler::Resource for DirectoryResource {}
+
+/// NIF: Index extracted records by BIC for `lookup_bic/2`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn load_directory(records: Vec<Vec<String>>) -> ResourceArc<DirectoryResource> {
+    ResourceArc::new(DirectoryResource(Directory::new(records)))
+}
+
+/// The result of looking up a BIC, as returned to Elixir.
+#[derive(rustler::NifMap)]
+struct NifLookup {
+    bic: String,
+    exists: bool,
+    primary_office: bool,
+    record: Option<Vec<String>>,
+}
+
+/// NIF: Look up a BIC as typed, ignoring case and whitespace.
+///
+/// Returns `{:ok, lookup}`, or `{:error, reason}` as `parse_bic/1` does when
+/// the input isn't a well-formed BIC.
+#[rustler::nif]
+fn lookup_bic(
+    directory: ResourceArc<DirectoryResource>,
+    input: &str,
+) -> Result<NifLookup, BicError> {
+    let lookup = directory.0.lookup(input)?;
+    Ok(NifLookup {
+        bic: lookup.bic.to_string(),
+        exists: lookup.exists(),
+        primary_office: !lookup.is_branch(),
+        record: lookup.record.map(<[String]>::to_vec),
+    })
+}
+
+/// Suggestion options as passed from Elixir, with every key present.
+#[derive(rustler::NifMap)]
+struct NifSuggestOptions {
+    limit: usize,
+    max_distance: f64,
+    same_country: bool,
+}
+
+impl From<NifSuggestOptions> for SuggestOptions {
+    fn from(options: NifSuggestOptions) -> Self {
+        SuggestOptions {
+            limit: options.limit,
+            max_distance: options.max_distance as f32,
+            same_country: options.same_country,
+        }
+    }
+}
+
+/// A suggested BIC, as returned to Elixir.
+#[derive(rustler::NifMap)]
+struct NifSuggestion {
+    bic: String,
+    distance: f64,
+    record: Vec<String>,
+}
+
+/// NIF: Find the BICs in `directory` closest to `input`, closest first.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn suggest_bics(
+    directory: ResourceArc<DirectoryResource>,
+    input: &str,
+    options: NifSuggestOptions,
+) -> Vec<NifSuggestion> {
+    directory
+        .0
+        .suggest(input, &options.into())
+        .into_iter()
+        .map(|suggestion| NifSuggestion {
+            bic: suggestion.bic.to_string(),
+            distance: f64::from(suggestion.distance),
+            record: suggestion.record.to_vec(),
+        })
+        .collect()
+}
+
+/// NIF: Get every country a BIC can belong to, sorted by code.
+#[rustler::nif]
+fn countries() -> Vec<NifCountry> {
+    Country::all().iter().map(NifCountry::from).collect()
+}
+
+// =============================================================================
+// Test-only NIFs to verify Rustler prevents BEAM crashes
+// =============================================================================
+/// NIFs that deliberately panic/unwrap to verify Rustler's runtime safety.
+/// This is only compiled when the `panic_test` feature is enabled.
+/// Rustler should catch this panic and convert it to an Erlang error
+/// rather than crashing the BEAM.
+#[cfg(feature = "panic_test")]
+#[rustler::nif]
+fn deliberate_panic() -> bool {
+    panic!("deliberate_panic: testing Rustler panic safety");
+}
+
+#[cfg(feature = "panic_test")]
+#[rustler::nif]
+#[allow(clippy::unnecessary_literal_unwrap)]
+fn deliberate_unwrap_panic() -> bool {
+    let option: Option<bool> = None;
+    option.expect("deliberate_unwrap_panic: testing Rustler panic safety")
+}
+
+/// Panics on the thread that `start_extraction/3` runs extraction on, to
+/// verify `pid` still gets a result.
+#[cfg(feature = "panic_test")]
+#[rustler::nif]
+fn deliberate_thread_panic(env: Env, pid: LocalPid) -> rustler::Reference {
+    let reference = env.make_ref();
+    let owned_env = rustler::OwnedEnv::new();
+    let saved_reference = owned_env.save(reference);
+
+    spawn_with_reply(owned_env, saved_reference, pid, |_, _| {
+        panic!("deliberate_thread_panic: testing extraction thread panic safety");
+    });
+
+    reference
+}
+
+rustler::init!("Elixir.BicExporter.Native");
@@ -1,7 +1,7 @@
 defmodule BicExporterTest do
   use ExUnit.Case
 
-  @fixture_path Path.join([__DIR__, "..", "native", "bic_exporter", "tests", "fixtures"])
+  @fixture_path Path.join([__DIR__, "..", "native", "bic_exporter", "core", "tests", "fixtures"])
   @pdf_path Path.join(@fixture_path, "ISOBIC-mini.pdf")
   @expected_record_count 86
 
@@ -22,6 +22,109 @@ defmodule BicExporterTest do
     end
   end
 
+  describe "country/1" do
+    test "returns the country of a BIC or record" do
+      {:ok, [record | _]} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+
+      assert BicExporter.country(record) == {:ok, %{code: "RS", name: "Serbia"}}
+      assert BicExporter.country("AAAAXKPRXXX") == {:ok, %{code: "XK", name: "Kosovo"}}
+      assert BicExporter.country("AAAAQQBG") == :error
+      assert BicExporter.country("AAA") == :error
+    end
+
+    test "knows every country in countries/0" do
+      countries = BicExporter.countries()
+
+      assert length(countries) == 250
+      assert %{code: "AD", name: "Andorra"} = hd(countries)
+    end
+  end
+
+  describe "parse_bic/1" do
+    test "decomposes a BIC" do
+      assert {:ok,
+              %{
+                bic: "AAAARSBGXXX",
+                bic8: "AAAARSBG",
+                business_party_prefix: "AAAA",
+                country_code: "RS",
+                location_code: "BG",
+                branch_code: "XXX",
+                country: %{code: "RS", name: "Serbia"},
+                primary_office: true,
+                test: false,
+                passive: false,
+                reverse_billing: false
+              }} = BicExporter.parse_bic("AAAARSBG")
+
+      assert {:ok, %{primary_office: false, passive: true, country: nil}} =
+               BicExporter.parse_bic("AAAAQQ21001")
+
+      assert {:ok, %{test: true}} = BicExporter.parse_bic("AAAADEF0")
+    end
+
+    test "rejects invalid BICs" do
+      assert BicExporter.parse_bic("AAAARSB") == {:error, :length}
+      assert BicExporter.parse_bic("aaaaRSBG") == {:error, :business_party_prefix}
+      assert BicExporter.parse_bic("AAAA1SBG") == {:error, :country_code}
+      assert BicExporter.parse_bic("AAAARSB-") == {:error, :location_code}
+      assert BicExporter.parse_bic("AAAARSBGxxx") == {:error, :branch_code}
+
+      assert BicExporter.valid_bic?("AAAARSBGXXX")
+      refute BicExporter.valid_bic?(" AAAARSBG")
+    end
+  end
+
+  describe "lookup_bic/2" do
+    setup do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+      %{records: records, directory: BicExporter.load_directory(records)}
+    end
+
+    test "finds head offices and branches", %{records: [record | _], directory: directory} do
+      assert BicExporter.lookup_bic(directory, " aaaa rs bg ") ==
+               {:ok,
+                %{bic: "AAAARSBGXXX", exists: true, primary_office: true, record: record}}
+
+      assert {:ok, %{bic: "AABAFI22TET", exists: true, primary_office: false}} =
+               BicExporter.lookup_bic(directory, "aabafi22 tet")
+    end
+
+    test "reports unknown and invalid BICs", %{directory: directory} do
+      assert BicExporter.lookup_bic(directory, "AAAARSBG001") ==
+               {:ok, %{bic: "AAAARSBG001", exists: false, primary_office: false, record: nil}}
+
+      assert BicExporter.lookup_bic(directory, "AAAARSB") == {:error, :length}
+    end
+  end
+
+  describe "suggest_bics/3" do
+    setup do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+      %{records: records, directory: BicExporter.load_directory(records)}
+    end
+
+    test "suggests the closest BICs", %{records: [record | _], directory: directory} do
+      assert [%{bic: "AAAARSBGXXX", distance: 0.5, record: ^record} | _] =
+               suggestions = BicExporter.suggest_bics(directory, "aaaa rs gb")
+
+      assert length(suggestions) <= 5
+
+      assert [%{bic: "AABAFI22TET", distance: 0.25}] =
+               BicExporter.suggest_bics(directory, "AABAF122TET", limit: 1)
+
+      assert BicExporter.suggest_bics(directory, "ZZZZZZZZ", max_distance: 1) == []
+    end
+
+    test "restricts suggestions to the input's country", %{directory: directory} do
+      suggestions =
+        BicExporter.suggest_bics(directory, "AAAAKWBG", same_country: true, max_distance: 4)
+
+      assert suggestions != []
+      assert Enum.all?(suggestions, &(binary_part(&1.bic, 4, 2) == "KW"))
+    end
+  end
+
   describe "extract_table_from_binary/1" do
     test "extracts records from PDF binary " do
       pdf_data = File.read!(@pdf_path)
@@ -103,9 +206,442 @@ defmodule BicExporterTest do
       end)
     end
 
+    test "returns a quality report when requested" do
+      pdf_data = File.read!(@pdf_path)
+
+      {:ok, records, report} = BicExporter.extract_table_from_binary(pdf_data, report: true)
+      assert length(records) == @expected_record_count
+
+      assert %{
+               orphan_rows: [],
+               out_of_grid: [],
+               missing_fields: [],
+               invalid_dates: [],
+               invalid_bics: [],
+               unknown_countries: [],
+               column_drift: [],
+               sort_violations: [],
+               page_errors: [],
+               empty_pages: [],
+               repaired: false,
+               records_per_page: [
+                 %{page: 2, records: 30},
+                 %{page: 3, records: 28},
+                 %{page: 4, records: 28}
+               ]
+             } = report
+    end
+
+    test "accepts a clean PDF in strict mode" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:ok, records} = BicExporter.extract_table_from_binary(pdf_data, strict: true)
+      assert length(records) == @expected_record_count
+    end
+
+    test "returns page errors when recovering" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:ok, records, []} = BicExporter.extract_table_from_binary(pdf_data, recover: true)
+      assert length(records) == @expected_record_count
+    end
+
+    test "stops extraction when a limit is exceeded" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:error, {:limit_exceeded, :max_pages, 3}} =
+               BicExporter.extract_table_from_binary(pdf_data, max_pages: 3)
+
+      assert {:ok, records} =
+               BicExporter.extract_table_from_binary(pdf_data, max_pages: 4, timeout: 60_000)
+
+      assert length(records) == @expected_record_count
+    end
+
+    test "extracts from encrypted PDFs with a password" do
+      for fixture <- ["ISOBIC-mini-rc4.pdf", "ISOBIC-mini-aes128.pdf"] do
+        pdf_data = File.read!(Path.join(@fixture_path, fixture))
+
+        assert {:ok, records} =
+                 BicExporter.extract_table_from_binary(pdf_data, password: "user-secret")
+
+        assert length(records) == @expected_record_count
+
+        assert {:error, :password_required} = BicExporter.extract_table_from_binary(pdf_data)
+
+        assert {:error, :wrong_password} =
+                 BicExporter.extract_table_from_binary(pdf_data, password: "guess")
+      end
+    end
+
+    test "extracts from PDFs encrypted only to restrict permissions" do
+      pdf_data = File.read!(Path.join(@fixture_path, "ISOBIC-mini-permissions.pdf"))
+
+      assert {:ok, records} = BicExporter.extract_table_from_binary(pdf_data)
+      assert length(records) == @expected_record_count
+    end
+
+    test "repairs truncated downloads" do
+      pdf_data = File.read!(Path.join(@fixture_path, "ISOBIC-mini-truncated.pdf"))
+
+      assert {:error, _reason} = BicExporter.extract_table_from_binary(pdf_data)
+
+      assert {:ok, records, report} =
+               BicExporter.extract_table_from_binary(pdf_data,
+                 repair: true,
+                 recover: true,
+                 report: true
+               )
+
+      assert length(records) == 58
+      assert report.repaired
+      assert [%{page: 4}] = report.page_errors
+    end
+
+    test "extracts from gzip-compressed data" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:ok, records} = BicExporter.extract_table_from_binary(:zlib.gzip(pdf_data))
+      assert length(records) == @expected_record_count
+    end
+
+    test "extracts from zip archives" do
+      pdf_data = File.read!(@pdf_path)
+
+      {:ok, {_name, single}} =
+        :zip.create(~c"mem", [{~c"readme.txt", "hello"}, {~c"ISOBIC.pdf", pdf_data}], [:memory])
+
+      assert {:ok, records} = BicExporter.extract_table_from_binary(single)
+      assert length(records) == @expected_record_count
+
+      {:ok, {_name, several}} =
+        :zip.create(~c"mem", [{~c"a.pdf", pdf_data}, {~c"b.pdf", pdf_data}], [:memory])
+
+      assert {:error, "Zip archive contains 2 PDFs" <> _} =
+               BicExporter.extract_table_from_binary(several)
+
+      assert {:ok, records} =
+               BicExporter.extract_table_from_binary(several, archive_entry: "b.pdf")
+
+      assert length(records) == @expected_record_count
+    end
+
+    test "limits the size of decompressed input" do
+      gzip_data = @pdf_path |> File.read!() |> :zlib.gzip()
+
+      assert {:error, {:limit_exceeded, :max_input_size, 1000}} =
+               BicExporter.extract_table_from_binary(gzip_data, max_input_size: 1000)
+    end
+
     test "returns error for invalid PDF data" do
       assert {:error, "Failed to load PDF from bytes"} =
                BicExporter.extract_table_from_binary("not a pdf")
     end
   end
+
+  describe "extract_table_from_path/2" do
+    test "extracts records from a PDF file" do
+      assert {:ok, records} = BicExporter.extract_table_from_path(@pdf_path)
+      assert length(records) == @expected_record_count
+    end
+
+    test "returns error for a missing file" do
+      assert {:error, :enoent} = BicExporter.extract_table_from_path("missing.pdf")
+    end
+  end
+
+  describe "extract_table_async/2" do
+    test "sends progress and the result to the calling process" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:ok, ref, _handle} = BicExporter.extract_table_async(pdf_data)
+
+      for page <- 2..4 do
+        assert_receive {:bic_exporter, ^ref, {:progress, ^page, 4}}, 5_000
+      end
+
+      assert_receive {:bic_exporter, ^ref, {:ok, records, report}}, 5_000
+      assert length(records) == @expected_record_count
+      assert report.page_errors == []
+    end
+
+    test "sends messages to the given pid" do
+      pdf_data = File.read!(@pdf_path)
+      parent = self()
+      pid = spawn(fn -> receive do: (message -> send(parent, {:forwarded, message})) end)
+
+      assert {:ok, ref, _handle} = BicExporter.extract_table_async(pdf_data, pid: pid)
+      assert_receive {:forwarded, {:bic_exporter, ^ref, {:progress, 2, 4}}}, 5_000
+    end
+
+    test "stops when cancelled" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:ok, ref, handle} = BicExporter.extract_table_async(pdf_data)
+      assert :ok = BicExporter.cancel_extraction(handle)
+
+      assert_receive {:bic_exporter, ^ref, {:error, :cancelled}}, 5_000
+      refute_received {:bic_exporter, ^ref, {:progress, 3, 4}}
+    end
+
+    test "sends errors as a message" do
+      assert {:ok, ref, _handle} = BicExporter.extract_table_async("not a pdf")
+      assert_receive {:bic_exporter, ^ref, {:error, "Failed to load PDF from bytes"}}, 5_000
+    end
+  end
+
+  describe "to_csv/2" do
+    test "matches the expected CSV" do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+
+      assert {:ok, csv} = BicExporter.to_csv(records)
+      assert csv == File.read!(Path.join(@fixture_path, "ISOBIC-mini-expected.csv"))
+    end
+
+    test "applies the options" do
+      record = ["2024-01-01", "2024-01-02", "AAAARSBG", "XXX", "A; B", "", "", "", "", "FIIN"]
+
+      assert {:ok, "\uFEFF2024-01-01;2024-01-02;AAAARSBG;XXX;\"A; B\";;;;;FIIN\r\n"} =
+               BicExporter.to_csv([record],
+                 delimiter: ";",
+                 line_ending: :crlf,
+                 bom: true,
+                 header: false
+               )
+
+      assert {:error, "Invalid CSV delimiter" <> _} = BicExporter.to_csv([record], delimiter: ";;")
+    end
+
+    test "neutralizes formulas when sanitizing" do
+      record = ["2024-01-01", "2024-01-02", "AAAARSBG", "XXX", "=1+1", "", "", "", "", "FIIN"]
+
+      assert {:ok, "2024-01-01,2024-01-02,AAAARSBG,XXX,=1+1,,,,,FIIN\n"} =
+               BicExporter.to_csv([record], header: false)
+
+      assert {:ok, "2024-01-01,2024-01-02,AAAARSBG,XXX,'=1+1,,,,,FIIN\n",
+              [%{record: 0, column: 4, value: "=1+1"}]} =
+               BicExporter.to_csv([record], header: false, sanitize: true)
+    end
+  end
+
+  describe "write_csv/3" do
+    @tag :tmp_dir
+    test "writes the CSV to a file", %{tmp_dir: tmp_dir} do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+      path = Path.join(tmp_dir, "ISOBIC.csv")
+
+      assert :ok = BicExporter.write_csv(records, path)
+      assert File.read!(path) == File.read!(Path.join(@fixture_path, "ISOBIC-mini-expected.csv"))
+    end
+  end
+
+  describe "to_xlsx/2" do
+    test "writes a workbook" do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+
+      assert {:ok, xlsx} = BicExporter.to_xlsx(records)
+      assert {:ok, files} = :zip.unzip(xlsx, [:memory])
+      assert {_, sheet} = List.keyfind(files, ~c"xl/worksheets/sheet1.xml", 0)
+      assert sheet =~ ~s(<autoFilter ref="A1:J87"/>)
+    end
+
+    test "reports formulas when sanitizing" do
+      record = ["2024-01-01", "2024-01-02", "AAAARSBG", "XXX", "=1+1", "", "", "", "", "FIIN"]
+
+      assert {:ok, _xlsx, [%{record: 0, column: 4, value: "=1+1"}]} =
+               BicExporter.to_xlsx([record], sanitize: true)
+    end
+  end
+
+  describe "write_xlsx/3" do
+    @tag :tmp_dir
+    test "writes the workbook to a file", %{tmp_dir: tmp_dir} do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+      path = Path.join(tmp_dir, "ISOBIC.xlsx")
+
+      assert :ok = BicExporter.write_xlsx(records, path)
+      assert {:ok, files} = :zip.unzip(String.to_charlist(path), [:memory])
+      assert List.keymember?(files, ~c"xl/workbook.xml", 0)
+    end
+  end
+
+  describe "to_fixed_width/2" do
+    test "pads every field with the default layout" do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+
+      assert {:ok, data, []} = BicExporter.to_fixed_width(records)
+      lines = String.split(data, "\n", trim: true)
+      assert length(lines) == 86
+      assert lines |> Enum.map(&String.length/1) |> Enum.uniq() == [630]
+    end
+
+    test "applies the layout and reports truncated values" do
+      record = [
+        "2024-01-01",
+        "2024-01-02",
+        "AAAARSBG",
+        "XXX",
+        "BANK OF EXAMPLE",
+        "",
+        "",
+        "",
+        "",
+        "FIIN"
+      ]
+
+      assert {:ok, "AAAARSBGXXXBANK OF EXAM\r\n",
+              [%{record: 0, column: 4, value: "BANK OF EXAMPLE"}]} =
+               BicExporter.to_fixed_width([record],
+                 fields: [bic: 8, branch_code: 3, full_legal_name: 12],
+                 line_ending: :crlf
+               )
+
+      assert {:ok, "AAAARSBGXXX___FIIN\n", []} =
+               BicExporter.to_fixed_width([record],
+                 fields: [{:bic, 8}, {:branch_code, 3}, {:institution_type, 7, :right}],
+                 padding: "_"
+               )
+
+      assert {:error, "Record 0's full_legal_name is longer than 12 characters" <> _} =
+               BicExporter.to_fixed_width([record],
+                 fields: [full_legal_name: 12],
+                 truncation: :error
+               )
+
+      assert {:error, "Unknown field \"name\""} =
+               BicExporter.to_fixed_width([record], fields: [name: 10])
+    end
+  end
+
+  describe "write_fixed_width/3" do
+    @tag :tmp_dir
+    test "writes the lines to a file", %{tmp_dir: tmp_dir} do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+      path = Path.join(tmp_dir, "ISOBIC.txt")
+
+      assert {:ok, []} = BicExporter.write_fixed_width(records, path)
+      assert {:ok, data, []} = BicExporter.to_fixed_width(records)
+      assert File.read!(path) == data
+    end
+  end
+
+  describe "to_json/2" do
+    test "keys records by field name" do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+
+      assert {:ok, json} = BicExporter.to_json(records)
+      assert %{"records" => [first | _] = decoded} = JSON.decode!(json)
+      assert length(decoded) == 86
+      assert first["bic"] == "AAAARSBG"
+      assert first["branch_code"] == "XXX"
+      assert first["institution_type"] == "FIIN"
+      assert first["country_code"] == "RS"
+      assert first["country_name"] == "Serbia"
+    end
+
+    test "writes JSON Lines with a metadata header" do
+      record = [
+        "1997-03-01",
+        "2024-06-06",
+        "AAAARSBG",
+        "XXX",
+        "YETTEL BANK AD",
+        "",
+        "",
+        "",
+        "",
+        "FIIN"
+      ]
+
+      assert {:ok, ndjson} =
+               BicExporter.to_json([record, record],
+                 format: :ndjson,
+                 metadata: true,
+                 edition: ~D[2025-12-04]
+               )
+
+      assert [header, line, _] =
+               ndjson |> String.split("\n", trim: true) |> Enum.map(&JSON.decode!/1)
+
+      assert %{"metadata" => %{"schema_version" => 2, "edition" => "2025-12-04"}} = header
+      assert line["full_legal_name"] == "YETTEL BANK AD"
+    end
+
+    test "returns the JSON Schema" do
+      schema = JSON.decode!(BicExporter.json_schema())
+
+      assert "bic" in schema["$defs"]["record"]["required"]
+    end
+  end
+
+  describe "write_json/3" do
+    @tag :tmp_dir
+    test "writes JSON Lines to a file", %{tmp_dir: tmp_dir} do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+      path = Path.join(tmp_dir, "ISOBIC.ndjson")
+
+      assert :ok = BicExporter.write_json(records, path, format: :ndjson)
+      assert path |> File.read!() |> String.split("\n", trim: true) |> length() == 86
+    end
+  end
+
+  describe "to_pg_copy/1" do
+    test "writes one escaped line per record" do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+
+      assert {:ok, data} = BicExporter.to_pg_copy(records)
+      assert [first | _] = lines = String.split(data, "\n", trim: true)
+      assert length(lines) == 86
+
+      assert first ==
+               Enum.join(
+                 [
+                   "1997-03-01",
+                   "2024-06-06",
+                   "AAAARSBG",
+                   "XXX",
+                   "YETTEL BANK AD",
+                   "88 OMLADINSKIH BRIGADA BEOGRAD 11070 SERBIA",
+                   "88 OMLADINSKIH BRIGADA BEOGRAD 11070 BEOGRAD SERBIA",
+                   "\\N",
+                   "\\N",
+                   "FIIN"
+                 ],
+                 "\t"
+               )
+    end
+
+    test "returns the COPY statement" do
+      assert BicExporter.pg_copy_statement("public.bics") =~
+               ~s(COPY "public"."bics" ("record_creation_date", )
+    end
+  end
+
+  describe "to_sql_inserts/2" do
+    test "writes multi-row INSERT statements" do
+      {:ok, records} = @pdf_path |> File.read!() |> BicExporter.extract_table_from_binary()
+
+      assert {:ok, sql} =
+               BicExporter.to_sql_inserts(records, rows_per_insert: 50, create_table: true)
+      assert sql =~ ~s(CREATE TABLE IF NOT EXISTS "bic_records")
+      assert length(String.split(sql, "INSERT INTO")) == 3
+      assert {:error, _} = BicExporter.to_sql_inserts(records, rows_per_insert: 0)
+    end
+  end
+
+  describe "render_page_svg/2" do
+    test "renders a data page as SVG" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:ok, "<svg" <> _ = svg} = BicExporter.render_page_svg(pdf_data, 2)
+      assert svg =~ "AAAARSBG"
+    end
+
+    test "returns error for a page out of range" do
+      pdf_data = File.read!(@pdf_path)
+
+      assert {:error, "Page 100 is out of range" <> _} =
+               BicExporter.render_page_svg(pdf_data, 100)
+    end
+  end
 end
@@ -28,5 +28,12 @@ defmodule RustlerPanicSafetyTest do
 
       assert is_list(BicExporter.headers())
     end
+
+    test "a panic on the extraction thread is sent as an error (BEAM does not crash)" do
+      ref = BicExporter.Native.deliberate_thread_panic(self())
+
+      assert_receive {:bic_exporter, ^ref, {:error, :panic}}, 5_000
+      assert is_list(BicExporter.headers())
+    end
   end
 end